@@ -0,0 +1,112 @@
+//! Benchmarks of the stages every chart goes through, on synthetic exports far larger than real
+//! ones so that regressions stand out. Run with `cargo bench`, optionally followed by `--` and part
+//! of a benchmark's name to run only the benchmarks matching it.
+
+use chrono::{Duration as Days, TimeZone, Utc};
+use clap::Parser;
+use rasorite::config::Config;
+use rasorite::data::{get_data_range_multi, Padding};
+use rasorite::parse::parse_analytics;
+use rasorite::plot::{normalize_data, render_svg, PlotArgs};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// How long each benchmark runs for after warming up
+const MEASUREMENT_TIME: Duration = Duration::from_secs(2);
+
+/// An export of daily active users over the given number of days, with a total, a benchmark and
+/// the given number of breakdown series. Values follow a weekly cycle with a little noise, like
+/// real exports.
+fn synthetic_export(days: i64, breakdowns: usize) -> String {
+    let start = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+    let mut csv = "Experience ID,0\n\nBreakdown,Date,Daily Active Users\n".to_string();
+    let mut series = vec![
+        "Total".to_string(),
+        "Benchmark (Top n experience)".to_string(),
+    ];
+    series.extend((0..breakdowns).map(|index| format!("Country {}", index)));
+
+    for (index, name) in series.iter().enumerate() {
+        for day in 0..days {
+            let weekly = [0, 12, 9, -4, -10, 30, 45][(day % 7) as usize];
+            let noise = (day * 7919 + index as i64 * 104_729) % 17;
+            let value = 400 + (index as i64 * 37) % 300 + weekly + noise + day / 10;
+            csv.push_str(&format!(
+                "{},{},{}\n",
+                name,
+                (start + Days::days(day)).format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+                value
+            ));
+        }
+    }
+    csv
+}
+
+/// Runs the benchmark repeatedly and prints the mean and fastest time per run
+fn bench<T>(filter: Option<&str>, name: &str, mut run: impl FnMut() -> T) {
+    if filter.is_some_and(|filter| !name.contains(filter)) {
+        return;
+    }
+
+    for _ in 0..3 {
+        black_box(run());
+    }
+    let mut samples = Vec::new();
+    let started = Instant::now();
+    while started.elapsed() < MEASUREMENT_TIME || samples.len() < 10 {
+        let start = Instant::now();
+        black_box(run());
+        samples.push(start.elapsed());
+    }
+
+    let mean = samples.iter().sum::<Duration>() / samples.len() as u32;
+    let fastest = samples.iter().min().copied().unwrap_or_default();
+    println!(
+        "{:<32} mean {:>10.3} ms   fastest {:>10.3} ms   ({} runs)",
+        name,
+        mean.as_secs_f64() * 1000.0,
+        fastest.as_secs_f64() * 1000.0,
+        samples.len()
+    );
+}
+
+fn main() {
+    // Cargo passes --bench to every benchmark binary, which is not a filter
+    let filter = std::env::args().skip(1).find(|arg| !arg.starts_with('-'));
+    let filter = filter.as_deref();
+
+    // Five years of 50 breakdowns, and a year of the total alone as exported by most experiences
+    let large = synthetic_export(5 * 365, 50);
+    let small = synthetic_export(365, 0);
+
+    bench(filter, "parse/5y-50-series", || {
+        parse_analytics(large.as_bytes()).unwrap()
+    });
+    bench(filter, "parse/1y-total", || {
+        parse_analytics(small.as_bytes()).unwrap()
+    });
+
+    let data = parse_analytics(large.as_bytes()).unwrap();
+    let total = data.data["Total"].clone();
+    let benchmark = data.data["Benchmark (Top n experience)"].clone();
+    bench(filter, "normalize/5y", || {
+        normalize_data(total.clone(), benchmark.clone()).unwrap()
+    });
+
+    bench(filter, "ranges/5y-50-series", || {
+        get_data_range_multi(
+            data.data.values().map(|points| points.as_slice()),
+            Padding::default(),
+        )
+    });
+
+    let args = PlotArgs::try_parse_from(["--deterministic"]).unwrap();
+    let style = args.plot.resolve_style(&Config::default());
+    bench(filter, "render/svg-5y", || {
+        render_svg(data.clone(), &args.plot, &style).unwrap()
+    });
+    let small_data = parse_analytics(small.as_bytes()).unwrap();
+    bench(filter, "render/svg-1y", || {
+        render_svg(small_data.clone(), &args.plot, &style).unwrap()
+    });
+}
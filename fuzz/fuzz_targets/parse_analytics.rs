@@ -0,0 +1,12 @@
+//! Feeds arbitrary bytes to the export parser, which must reject anything it cannot read with an
+//! error rather than panicking. Run with `cargo fuzz run parse_analytics` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use rasorite::parse::{has_analytics_header, parse_analytics};
+
+fuzz_target!(|data: &[u8]| {
+    let _ = has_analytics_header(data);
+    let _ = parse_analytics(data);
+});
@@ -0,0 +1,71 @@
+chart-title = { $kpi } für Erlebnis-ID { $universe }
+chart-title-named = { $kpi } für { $name }
+plotted-against =
+    Verglichen mit { $count ->
+        [one] Benchmark
+       *[other] Benchmarks
+    }: { $benchmarks }
+normalized-over = Normalisiert auf { $reference }
+percentage-of = Als Prozentsatz von { $reference }
+combined-axis = Index (erster Tag = 100)
+combined-kpis = { $count } KPIs
+reference-benchmark = Benchmark: { $benchmark }
+reference-series = „{ $series }“
+reference-from-file = { $reference } aus { $file }
+peer = Erlebnis-ID { $universe }
+rolling-average = { $days }-Tage-Durchschnitt
+goal = Ziel: { $value }
+delta-badge = { $arrow } { $change } ggü. den { $days } Tagen davor
+donut-subtitle = Anteil jeder Aufschlüsselung am { $date }
+donut-other = Sonstige
+footer-data-range = Daten vom { $start } bis { $end }
+footer-generated = Erstellt am { $generated }
+inline-stats-last = Zuletzt ({ $date })
+inline-stats-week-average = 7-Tage-Durchschnitt
+inline-stats-month-change = Änderung über 30 Tage
+
+benchmark-with-percentile = { $source } ({ $percentile })
+benchmark-unnamed = Benchmark
+percentile =
+    { $percentile ->
+        [50] Median
+       *[other] { $percentile }. Perzentil
+    }
+
+kpi-daily-active-users = Täglich aktive Nutzer
+kpi-monthly-active-users = Monatlich aktive Nutzer
+kpi-sessions = Sitzungen
+kpi-playtime = Spielzeit
+kpi-daily-revenue = Tagesumsatz
+kpi-paying-users = Zahlende Nutzer
+kpi-stickiness = Stickiness
+kpi-arpdau = ARPDAU
+kpi-conversion-rate = Konversionsrate
+
+kpi-in-unit = { $kpi } ({ $unit })
+unit =
+    { $unit ->
+        [robux] Robux
+        [percent] %
+        [minutes] Minuten
+        [hours] Stunden
+       *[days] Tage
+    }
+
+month =
+    { $month ->
+        [1] Jan.
+        [2] Feb.
+        [3] März
+        [4] Apr.
+        [5] Mai
+        [6] Juni
+        [7] Juli
+        [8] Aug.
+        [9] Sept.
+        [10] Okt.
+        [11] Nov.
+       *[12] Dez.
+    }
+x-axis = Datum
+axis-date = { $day }. { $month } { $year }
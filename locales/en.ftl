@@ -0,0 +1,79 @@
+# Chart text in English, which is also used for anything missing from the other languages
+
+chart-title = { $kpi } for Experience ID { $universe }
+chart-title-named = { $kpi } for { $name }
+plotted-against =
+    Plotted against { $count ->
+        [one] benchmark
+       *[other] benchmarks
+    }: { $benchmarks }
+normalized-over = Normalized over { $reference }
+percentage-of = As a percentage of { $reference }
+combined-axis = Index (first day = 100)
+combined-kpis = { $count } KPIs
+reference-benchmark = benchmark: { $benchmark }
+reference-series = "{ $series }"
+reference-from-file = { $reference } from { $file }
+peer = Experience ID { $universe }
+rolling-average = { $days }-day average
+goal = Goal: { $value }
+delta-badge = { $arrow } { $change } vs prior { $days } days
+donut-subtitle = Share of each breakdown on { $date }
+donut-other = Other
+footer-data-range = Data from { $start } to { $end }
+footer-generated = Generated { $generated }
+inline-stats-last = Last ({ $date })
+inline-stats-week-average = 7-day average
+inline-stats-month-change = 30-day change
+
+benchmark-with-percentile = { $source } ({ $percentile })
+benchmark-unnamed = Benchmark
+percentile =
+    { $percentile ->
+        [50] median
+       *[other]
+            { $percentile }{ $ordinal ->
+                [one] st
+                [two] nd
+                [few] rd
+               *[other] th
+            } percentile
+    }
+
+kpi-daily-active-users = Daily Active Users
+kpi-monthly-active-users = Monthly Active Users
+kpi-sessions = Sessions
+kpi-playtime = Playtime
+kpi-daily-revenue = Daily Revenue
+kpi-paying-users = Paying Users
+kpi-stickiness = Stickiness
+kpi-arpdau = ARPDAU
+kpi-conversion-rate = Conversion Rate
+
+kpi-in-unit = { $kpi } ({ $unit })
+unit =
+    { $unit ->
+        [robux] Robux
+        [percent] %
+        [minutes] minutes
+        [hours] hours
+       *[days] days
+    }
+
+month =
+    { $month ->
+        [1] Jan
+        [2] Feb
+        [3] Mar
+        [4] Apr
+        [5] May
+        [6] Jun
+        [7] Jul
+        [8] Aug
+        [9] Sep
+        [10] Oct
+        [11] Nov
+       *[12] Dec
+    }
+x-axis = Date
+axis-date = { $month } { $day }, { $year }
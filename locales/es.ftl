@@ -0,0 +1,71 @@
+chart-title = { $kpi } del ID de experiencia { $universe }
+chart-title-named = { $kpi } de { $name }
+plotted-against =
+    Comparado con { $count ->
+        [one] la referencia
+       *[other] las referencias
+    }: { $benchmarks }
+normalized-over = Normalizado respecto a { $reference }
+percentage-of = Como porcentaje de { $reference }
+combined-axis = Índice (primer día = 100)
+combined-kpis = { $count } indicadores
+reference-benchmark = la referencia: { $benchmark }
+reference-series = «{ $series }»
+reference-from-file = { $reference } de { $file }
+peer = ID de experiencia { $universe }
+rolling-average = media de { $days } días
+goal = Objetivo: { $value }
+delta-badge = { $arrow } { $change } frente a los { $days } días anteriores
+donut-subtitle = Proporción de cada desglose el { $date }
+donut-other = Otros
+footer-data-range = Datos del { $start } al { $end }
+footer-generated = Generado el { $generated }
+inline-stats-last = Último ({ $date })
+inline-stats-week-average = Media de 7 días
+inline-stats-month-change = Cambio en 30 días
+
+benchmark-with-percentile = { $source } ({ $percentile })
+benchmark-unnamed = Referencia
+percentile =
+    { $percentile ->
+        [50] mediana
+       *[other] percentil { $percentile }
+    }
+
+kpi-daily-active-users = Usuarios activos diarios
+kpi-monthly-active-users = Usuarios activos mensuales
+kpi-sessions = Sesiones
+kpi-playtime = Tiempo de juego
+kpi-daily-revenue = Ingresos diarios
+kpi-paying-users = Usuarios que pagan
+kpi-stickiness = Adherencia
+kpi-arpdau = ARPDAU
+kpi-conversion-rate = Tasa de conversión
+
+kpi-in-unit = { $kpi } ({ $unit })
+unit =
+    { $unit ->
+        [robux] Robux
+        [percent] %
+        [minutes] minutos
+        [hours] horas
+       *[days] días
+    }
+
+month =
+    { $month ->
+        [1] ene
+        [2] feb
+        [3] mar
+        [4] abr
+        [5] may
+        [6] jun
+        [7] jul
+        [8] ago
+        [9] sept
+        [10] oct
+        [11] nov
+       *[12] dic
+    }
+x-axis = Fecha
+axis-date = { $day } { $month } { $year }
@@ -0,0 +1,75 @@
+chart-title = { $kpi } de l’ID d’expérience { $universe }
+chart-title-named = { $kpi } de { $name }
+plotted-against =
+    Comparé { $count ->
+        [one] à la référence
+       *[other] aux références
+    } : { $benchmarks }
+normalized-over = Normalisé par rapport à la { $reference }
+percentage-of = En pourcentage de la { $reference }
+combined-axis = Indice (premier jour = 100)
+combined-kpis = { $count } indicateurs
+reference-benchmark = référence : { $benchmark }
+reference-series = série « { $series } »
+reference-from-file = { $reference } de { $file }
+peer = ID d’expérience { $universe }
+rolling-average = moyenne sur { $days } jours
+goal = Objectif : { $value }
+delta-badge = { $arrow } { $change } par rapport aux { $days } jours précédents
+donut-subtitle = Part de chaque ventilation le { $date }
+donut-other = Autres
+footer-data-range = Données du { $start } au { $end }
+footer-generated = Généré le { $generated }
+inline-stats-last = Dernier ({ $date })
+inline-stats-week-average = Moyenne sur 7 jours
+inline-stats-month-change = Variation sur 30 jours
+
+benchmark-with-percentile = { $source } ({ $percentile })
+benchmark-unnamed = Référence
+percentile =
+    { $percentile ->
+        [50] médiane
+       *[other]
+            { $percentile }{ $ordinal ->
+                [one] er
+               *[other] e
+            } centile
+    }
+
+kpi-daily-active-users = Utilisateurs actifs quotidiens
+kpi-monthly-active-users = Utilisateurs actifs mensuels
+kpi-sessions = Sessions
+kpi-playtime = Temps de jeu
+kpi-daily-revenue = Revenus quotidiens
+kpi-paying-users = Utilisateurs payants
+kpi-stickiness = Fidélité
+kpi-arpdau = ARPDAU
+kpi-conversion-rate = Taux de conversion
+
+kpi-in-unit = { $kpi } ({ $unit })
+unit =
+    { $unit ->
+        [robux] Robux
+        [percent] %
+        [minutes] minutes
+        [hours] heures
+       *[days] jours
+    }
+
+month =
+    { $month ->
+        [1] janv.
+        [2] févr.
+        [3] mars
+        [4] avr.
+        [5] mai
+        [6] juin
+        [7] juil.
+        [8] août
+        [9] sept.
+        [10] oct.
+        [11] nov.
+       *[12] déc.
+    }
+x-axis = Date
+axis-date = { $day } { $month } { $year }
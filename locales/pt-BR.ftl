@@ -0,0 +1,71 @@
+chart-title = { $kpi } do ID de experiência { $universe }
+chart-title-named = { $kpi } de { $name }
+plotted-against =
+    Comparado com { $count ->
+        [one] a referência
+       *[other] as referências
+    }: { $benchmarks }
+normalized-over = Normalizado por { $reference }
+percentage-of = Como porcentagem de { $reference }
+combined-axis = Índice (primeiro dia = 100)
+combined-kpis = { $count } indicadores
+reference-benchmark = referência: { $benchmark }
+reference-series = série "{ $series }"
+reference-from-file = { $reference } de { $file }
+peer = ID de experiência { $universe }
+rolling-average = média de { $days } dias
+goal = Meta: { $value }
+delta-badge = { $arrow } { $change } em relação aos { $days } dias anteriores
+donut-subtitle = Participação de cada detalhamento em { $date }
+donut-other = Outros
+footer-data-range = Dados de { $start } a { $end }
+footer-generated = Gerado em { $generated }
+inline-stats-last = Último ({ $date })
+inline-stats-week-average = Média de 7 dias
+inline-stats-month-change = Variação em 30 dias
+
+benchmark-with-percentile = { $source } ({ $percentile })
+benchmark-unnamed = Referência
+percentile =
+    { $percentile ->
+        [50] mediana
+       *[other] percentil { $percentile }
+    }
+
+kpi-daily-active-users = Usuários ativos diários
+kpi-monthly-active-users = Usuários ativos mensais
+kpi-sessions = Sessões
+kpi-playtime = Tempo de jogo
+kpi-daily-revenue = Receita diária
+kpi-paying-users = Usuários pagantes
+kpi-stickiness = Aderência
+kpi-arpdau = ARPDAU
+kpi-conversion-rate = Taxa de conversão
+
+kpi-in-unit = { $kpi } ({ $unit })
+unit =
+    { $unit ->
+        [robux] Robux
+        [percent] %
+        [minutes] minutos
+        [hours] horas
+       *[days] dias
+    }
+
+month =
+    { $month ->
+        [1] jan
+        [2] fev
+        [3] mar
+        [4] abr
+        [5] mai
+        [6] jun
+        [7] jul
+        [8] ago
+        [9] set
+        [10] out
+        [11] nov
+       *[12] dez
+    }
+x-axis = Data
+axis-date = { $day } { $month } { $year }
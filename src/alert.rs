@@ -0,0 +1,133 @@
+use crate::data::{format_number, DataPointArithmeticError};
+use crate::digest::KpiDigest;
+use crate::parse::AnalyticsData;
+use serde::{Deserialize, Serialize};
+
+/// A condition checked against each export once it is plotted, such as daily active users falling
+/// by more than 15% week over week. Configured as a `[[rules]]` table in the config file.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AlertRule {
+    /// The name the alert is sent under, e.g. "DAU falling"
+    pub name: String,
+    /// The KPI the rule applies to, e.g. "Daily Active Users". Every KPI if omitted
+    pub kpi: Option<String>,
+    /// The series checked. Defaults to the export's "Total" series
+    pub series: Option<String>,
+    /// The number of days averaged, compared with the same number of days before them
+    #[serde(default = "default_days")]
+    pub days: u32,
+    /// Fires when the average falls by more than this percentage
+    pub drop_percent: Option<f64>,
+    /// Fires when the average rises by more than this percentage
+    pub rise_percent: Option<f64>,
+    /// Fires when the average is below this value
+    pub below: Option<f64>,
+    /// Fires when the average is above this value
+    pub above: Option<f64>,
+}
+
+fn default_days() -> u32 {
+    7
+}
+
+/// A rule that fired for an export
+#[derive(Serialize, Clone, PartialEq, Debug)]
+pub struct Alert {
+    pub rule: String,
+    pub universe_id: u64,
+    pub kpi: String,
+    pub series: String,
+    /// The average over the last `days` days
+    pub current: f64,
+    /// The average over the `days` days before them, if the export goes back that far
+    pub previous: Option<f64>,
+    pub change_percent: Option<f64>,
+    /// What fired, e.g. "Daily Active Users fell by 18.2% (7-day average 1234 vs 1509)"
+    pub message: String,
+}
+
+impl AlertRule {
+    /// Whether the rule applies to exports of the given KPI
+    fn applies_to(&self, data: &AnalyticsData) -> bool {
+        let kpi = data.kpi_type.to_string();
+        self.kpi
+            .as_ref()
+            .is_none_or(|rule_kpi| rule_kpi.eq_ignore_ascii_case(&kpi))
+    }
+
+    /// Checks the rule against an export that has a "Total" series, returning the alert if it
+    /// fires
+    fn check(&self, data: &AnalyticsData) -> Option<Alert> {
+        if !self.applies_to(data) {
+            return None;
+        }
+
+        let digest = KpiDigest::new(data, self.days)?;
+        let delta = match &self.series {
+            Some(name) => digest
+                .series
+                .into_iter()
+                .find(|series| series.name == *name)?,
+            None => digest
+                .series
+                .into_iter()
+                .filter(|series| series.name.starts_with("Total"))
+                .min_by(|a, b| a.name.cmp(&b.name))?,
+        };
+        let current = delta.current?;
+        let change_percent = delta.change_percent();
+
+        let kpi = data.kpi_type.to_string();
+        let reason = if let Some(change) =
+            change_percent.filter(|change| self.drop_percent.is_some_and(|drop| -change > drop))
+        {
+            format!("{} fell by {:.1}%", kpi, -change)
+        } else if let Some(change) =
+            change_percent.filter(|change| self.rise_percent.is_some_and(|rise| *change > rise))
+        {
+            format!("{} rose by {:.1}%", kpi, change)
+        } else if let Some(below) = self.below.filter(|below| current < *below) {
+            format!("{} is below {}", kpi, format_number(below))
+        } else if let Some(above) = self.above.filter(|above| current > *above) {
+            format!("{} is above {}", kpi, format_number(above))
+        } else {
+            return None;
+        };
+        let averages = match delta.previous {
+            Some(previous) => format!(
+                "{}-day average {} vs {}",
+                self.days,
+                format_number(current),
+                format_number(previous)
+            ),
+            None => format!("{}-day average {}", self.days, format_number(current)),
+        };
+
+        Some(Alert {
+            rule: self.name.clone(),
+            universe_id: data.universe_id,
+            kpi,
+            message: format!("{} ({})", reason, averages),
+            series: delta.name,
+            current,
+            previous: delta.previous,
+            change_percent,
+        })
+    }
+}
+
+/// Checks every rule against the export, returning the alerts of the rules that fired
+pub fn check_rules(
+    rules: &[AlertRule],
+    data: &AnalyticsData,
+) -> Result<Vec<Alert>, DataPointArithmeticError> {
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Rules on the total need one, even for exports viewed by a breakdown
+    let mut data = data.clone();
+    data.add_computed_total()?;
+    Ok(rules.iter().filter_map(|rule| rule.check(&data)).collect())
+}
@@ -0,0 +1,116 @@
+use std::fmt::Display;
+
+/// A benchmark series, described by the label Roblox gives it in the export, such as
+/// "Benchmark (Top n experience)" or "Benchmark: Similar Experiences (Median)"
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BenchmarkSeries {
+    /// The name of the series as it appears in the export
+    pub key: String,
+    /// The group of experiences the benchmark is drawn from, e.g. "Similar Experiences"
+    pub source: Option<String>,
+    /// The percentile of that group the benchmark follows, e.g. 50 for the median
+    pub percentile: Option<u8>,
+}
+
+/// Reads a percentile from labels such as "Median", "Top 25%", "P90" or "90th percentile"
+fn parse_percentile(text: &str) -> Option<u8> {
+    let text = text.trim().to_lowercase();
+    let percent = |value: &str| {
+        value
+            .trim()
+            .strip_suffix('%')?
+            .trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|value| *value <= 100)
+    };
+
+    match text.as_str() {
+        "median" => return Some(50),
+        "upper quartile" | "top quartile" => return Some(75),
+        "lower quartile" | "bottom quartile" => return Some(25),
+        _ => {}
+    }
+
+    if let Some(value) = text.strip_prefix("top ") {
+        return percent(value).map(|value| 100 - value);
+    }
+    if let Some(value) = text.strip_prefix("bottom ") {
+        return percent(value);
+    }
+    if let Some(value) = text.strip_suffix(" percentile") {
+        return value
+            .trim_end_matches(char::is_alphabetic)
+            .parse()
+            .ok()
+            .filter(|value| *value <= 100);
+    }
+    text.strip_prefix('p')
+        .and_then(|value| value.parse().ok())
+        .filter(|value| *value <= 100)
+}
+
+impl BenchmarkSeries {
+    /// Describes the series with the given name, or returns None if it is not a benchmark series
+    pub fn parse(key: &str) -> Option<Self> {
+        let label = key
+            .strip_prefix("Benchmark")?
+            .trim_start_matches([':', '-', ' '])
+            .trim();
+
+        // The qualifier in parentheses is a percentile, unless it is the only description given
+        let (description, qualifier) = match label
+            .strip_suffix(')')
+            .and_then(|label| label.rsplit_once('('))
+        {
+            Some((description, qualifier)) => (description.trim(), Some(qualifier.trim())),
+            None => (label, None),
+        };
+
+        let (source, percentile) = match qualifier {
+            Some(qualifier) if !description.is_empty() => match parse_percentile(qualifier) {
+                Some(percentile) => (Some(description), Some(percentile)),
+                None => (Some(label), None),
+            },
+            qualifier => {
+                let description = qualifier.unwrap_or(description);
+                match parse_percentile(description) {
+                    Some(percentile) => (None, Some(percentile)),
+                    None => (Some(description).filter(|source| !source.is_empty()), None),
+                }
+            }
+        };
+
+        Some(BenchmarkSeries {
+            key: key.to_string(),
+            source: source.map(str::to_string),
+            percentile,
+        })
+    }
+}
+
+impl Display for BenchmarkSeries {
+    /// A readable description of the benchmark, e.g. "Similar Experiences (median)"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let percentile = self.percentile.map(|percentile| match percentile {
+            50 => "median".to_string(),
+            percentile => {
+                let suffix = match (percentile % 10, percentile % 100) {
+                    (_, 11..=13) => "th",
+                    (1, _) => "st",
+                    (2, _) => "nd",
+                    (3, _) => "rd",
+                    _ => "th",
+                };
+                format!("{}{} percentile", percentile, suffix)
+            }
+        });
+
+        match (&self.source, percentile) {
+            (Some(source), Some(percentile)) => write!(f, "{} ({})", source, percentile),
+            (Some(source), None) => write!(f, "{}", source),
+            (None, Some(percentile)) => write!(f, "{}", percentile),
+            (None, None) => write!(f, "Benchmark"),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use crate::data::DataPoint;
+use chrono::{DateTime, Utc};
+
+/// The days in a week, the period over which player counts usually rise and fall
+const WEEK: usize = 7;
+
+/// The fewest days between two changepoints, so that a week's regular ups and downs are never
+/// taken for a shift on their own
+pub const MIN_SEGMENT_DAYS: usize = WEEK;
+
+/// A date on which the average level of a series shifts and stays shifted
+#[derive(Clone, PartialEq, Debug)]
+pub struct Changepoint {
+    /// The first date at the new level
+    pub date: DateTime<Utc>,
+    /// The average of the series between the previous changepoint and this one
+    pub before: f64,
+    /// The average of the series between this changepoint and the next one
+    pub after: f64,
+}
+
+impl Changepoint {
+    /// The shift as a percentage of the level before it, or None if that level was zero
+    pub fn change_percent(&self) -> Option<f64> {
+        (self.before != 0.0).then(|| (self.after - self.before) / self.before.abs() * 100.0)
+    }
+}
+
+/// Running sums of the values and their squares, so that the cost of any segment can be found in
+/// constant time
+struct Sums {
+    values: Vec<f64>,
+    squares: Vec<f64>,
+}
+
+impl Sums {
+    fn new(values: &[f64]) -> Self {
+        let mut sums = Sums {
+            values: vec![0.0],
+            squares: vec![0.0],
+        };
+        for value in values {
+            sums.values.push(sums.values.last().unwrap() + value);
+            sums.squares
+                .push(sums.squares.last().unwrap() + value * value);
+        }
+        sums
+    }
+
+    /// The sum of squared deviations from the mean of the values in `start..end`
+    fn cost(&self, start: usize, end: usize) -> f64 {
+        let count = (end - start) as f64;
+        let sum = self.values[end] - self.values[start];
+        (self.squares[end] - self.squares[start]) - sum * sum / count
+    }
+
+    fn mean(&self, start: usize, end: usize) -> f64 {
+        (self.values[end] - self.values[start]) / (end - start) as f64
+    }
+}
+
+fn median(values: &mut [f64]) -> f64 {
+    values.sort_by(f64::total_cmp);
+    let middle = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[middle - 1] + values[middle]) / 2.0
+    } else {
+        values[middle]
+    }
+}
+
+/// Removes the usual difference of each weekday from the rest of its week, so that weekend peaks
+/// are neither taken for shifts nor counted as noise. The series must span at least a week.
+fn remove_weekdays(values: &[f64]) -> Vec<f64> {
+    let half = WEEK / 2;
+    let mut deviations: Vec<Vec<f64>> = vec![Vec::new(); WEEK];
+    for (index, window) in values.windows(WEEK).enumerate() {
+        let middle = index + half;
+        let mean = window.iter().sum::<f64>() / WEEK as f64;
+        deviations[middle % WEEK].push(values[middle] - mean);
+    }
+    let effects: Vec<f64> = deviations
+        .iter_mut()
+        .map(|deviations| median(deviations))
+        .collect();
+
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, value)| value - effects[index % WEEK])
+        .collect()
+}
+
+/// Estimates the variance of the noise in a series from the differences between neighbouring
+/// values, which a shift in level barely affects, unlike the variance of the values themselves
+fn noise_variance(values: &[f64]) -> f64 {
+    let mut differences: Vec<f64> = values.windows(2).map(|pair| pair[1] - pair[0]).collect();
+    let center = median(&mut differences);
+    let mut deviations: Vec<f64> = differences
+        .iter()
+        .map(|difference| (difference - center).abs())
+        .collect();
+    // The median absolute deviation, scaled to match a standard deviation for normal noise, and
+    // halved as each difference carries the noise of two values
+    let deviation = median(&mut deviations) * 1.4826;
+    deviation * deviation / 2.0
+}
+
+/// Splits `start..end` where doing so lowers the cost by more than the penalty, then splits each
+/// side the same way
+fn split(
+    sums: &Sums,
+    start: usize,
+    end: usize,
+    min_size: usize,
+    penalty: f64,
+    splits: &mut Vec<usize>,
+) {
+    if end - start < min_size * 2 {
+        return;
+    }
+
+    let cost = sums.cost(start, end);
+    let Some((gain, index)) = (start + min_size..=end - min_size)
+        .map(|index| {
+            (
+                cost - sums.cost(start, index) - sums.cost(index, end),
+                index,
+            )
+        })
+        .max_by(|(a, _), (b, _)| a.total_cmp(b))
+    else {
+        return;
+    };
+
+    if gain > penalty {
+        splits.push(index);
+        split(sums, start, index, min_size, penalty, splits);
+        split(sums, index, end, min_size, penalty, splits);
+    }
+}
+
+/// Finds the dates on which the series' average level shifts, by binary segmentation. A shift is
+/// only accepted if it explains more of the series than noise of the estimated size would, with a
+/// penalty that grows with the length of the series.
+pub fn find_changepoints(points: &[(DateTime<Utc>, DataPoint)]) -> Vec<Changepoint> {
+    let mut points: Vec<(DateTime<Utc>, f64)> = points
+        .iter()
+        .map(|(date, point)| (*date, (*point).into()))
+        .collect();
+    points.sort_by_key(|(date, _)| *date);
+    if points.len() < MIN_SEGMENT_DAYS * 2 {
+        return Vec::new();
+    }
+
+    let values: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
+    let adjusted = remove_weekdays(&values);
+    let penalty = 2.0 * noise_variance(&adjusted) * (values.len() as f64).ln();
+
+    let mut splits = Vec::new();
+    split(
+        &Sums::new(&adjusted),
+        0,
+        values.len(),
+        MIN_SEGMENT_DAYS,
+        penalty,
+        &mut splits,
+    );
+    splits.sort_unstable();
+
+    // The levels are reported from the values as they are, not with weekdays removed
+    let sums = Sums::new(&values);
+    let bounds: Vec<usize> = std::iter::once(0)
+        .chain(splits.iter().copied())
+        .chain(std::iter::once(values.len()))
+        .collect();
+    bounds
+        .windows(3)
+        .map(|bounds| Changepoint {
+            date: points[bounds[1]].0,
+            before: sums.mean(bounds[0], bounds[1]),
+            after: sums.mean(bounds[1], bounds[2]),
+        })
+        .collect()
+}
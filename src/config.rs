@@ -0,0 +1,210 @@
+use crate::alert::AlertRule;
+use crate::parse::AnalyticsData;
+use crate::style::{SeriesRule, StyleOverrides};
+use log::info;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The config file looked for in the working directory when `--config` is not given
+pub const DEFAULT_CONFIG_FILE: &str = "rasorite.toml";
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Overrides for the built-in presets, keyed by preset name
+    #[serde(default)]
+    pub presets: HashMap<String, StyleOverrides>,
+
+    /// Styles for series matching a pattern, applied in order to every chart
+    #[serde(default)]
+    pub series: Vec<SeriesRule>,
+
+    /// The charts shown by `rasorite serve`
+    #[serde(default)]
+    pub charts: Vec<ChartConfig>,
+
+    /// Where `--publish-sheets` publishes parsed series to
+    pub sheets: Option<SheetsConfig>,
+
+    /// The database `--sync-notion` upserts daily rows into
+    pub notion: Option<NotionConfig>,
+
+    /// The KPIs for which a fall is good news, by name, e.g. "Sessions". Their delta badges are
+    /// colored green when they fall and red when they rise
+    #[serde(default)]
+    pub lower_is_better: Vec<String>,
+
+    /// Settings for each experience, keyed by the name given to `--profile`
+    #[serde(default)]
+    pub profiles: HashMap<String, ProfileConfig>,
+
+    /// Conditions checked against each export once it is plotted, alerting when one is met
+    #[serde(default)]
+    pub rules: Vec<AlertRule>,
+
+    /// Where alerts are sent when a rule fires
+    #[serde(default)]
+    pub notify: NotifyConfig,
+}
+
+impl Config {
+    /// The names given to experiences by their profiles, keyed by universe ID
+    pub fn experience_names(&self) -> HashMap<u64, String> {
+        self.profiles
+            .values()
+            .filter_map(|profile| Some((profile.universe_id?, profile.name.clone()?)))
+            .collect()
+    }
+
+    /// Looks up the profile with the given name, if one was chosen
+    pub fn profile(&self, name: Option<&str>) -> Result<Option<&ProfileConfig>, ConfigError> {
+        name.map(|name| {
+            self.profiles
+                .get(name)
+                .ok_or_else(|| ConfigError::UnknownProfile(name.to_string()))
+        })
+        .transpose()
+    }
+}
+
+/// Settings for one experience, so that studios with several games don't need to repeat them as
+/// flags
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ProfileConfig {
+    /// The experience's universe ID. Exports of other experiences are rejected
+    pub universe_id: Option<u64>,
+    /// The name shown in chart titles instead of the universe ID
+    pub name: Option<String>,
+    /// The KPIs usually plotted for the experience, e.g. "Daily Active Users". Other KPIs are
+    /// skipped by `rasorite watch-dir`
+    #[serde(default)]
+    pub kpis: Vec<String>,
+    /// The directory charts are written to, when not given as an absolute path
+    pub out_dir: Option<PathBuf>,
+}
+
+impl ProfileConfig {
+    /// Checks that the export is of the profile's experience
+    pub fn check(&self, profile: &str, data: &AnalyticsData) -> Result<(), ConfigError> {
+        match self.universe_id {
+            Some(universe_id) if universe_id != data.universe_id => Err(
+                ConfigError::WrongUniverse(profile.to_string(), universe_id, data.universe_id),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the export's KPI is one of the profile's KPIs. Every KPI is if none are listed.
+    pub fn includes_kpi(&self, data: &AnalyticsData) -> bool {
+        let kpi = data.kpi_type.to_string();
+        self.kpis.is_empty() || self.kpis.iter().any(|k| k.eq_ignore_ascii_case(&kpi))
+    }
+
+    /// Where a chart is written: inside the profile's output directory, unless the path is absolute
+    pub fn out_path(&self, out_file: &Path) -> PathBuf {
+        match &self.out_dir {
+            Some(out_dir) if out_file.is_relative() => out_dir.join(out_file),
+            _ => out_file.to_path_buf(),
+        }
+    }
+}
+
+/// A Google Sheet that parsed series are published to using a service account
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SheetsConfig {
+    /// The service account key file downloaded from the Google Cloud console
+    pub credentials: PathBuf,
+    /// The ID of the spreadsheet, as found in its URL
+    pub spreadsheet_id: String,
+    /// The tab the series are written to. Defaults to "Rasorite"
+    #[serde(default = "default_sheet")]
+    pub sheet: String,
+}
+
+/// A Notion database that daily KPI rows are upserted into using an internal integration
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct NotionConfig {
+    /// The integration's secret token
+    pub token: String,
+    /// The ID of the database, as found in its URL
+    pub database_id: String,
+}
+
+/// Where alerts are sent when a rule fires. Any number of them can be set.
+#[derive(Deserialize, Clone, Default, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// A Discord webhook URL. The chart is attached to the message
+    pub discord: Option<String>,
+    /// A Slack incoming webhook URL. Slack's webhooks cannot take files, so the chart's path is
+    /// given instead
+    pub slack: Option<String>,
+    /// A URL a JSON payload of the alerts and the chart is posted to
+    pub webhook: Option<String>,
+    /// Exits with 6 once the chart is written if a rule fired, for runs from scripts and schedulers.
+    /// Has no effect on `rasorite watch-dir`, which keeps running
+    #[serde(default)]
+    pub exit_code: bool,
+}
+
+fn default_sheet() -> String {
+    "Rasorite".to_string()
+}
+
+/// A chart rendered by `rasorite serve`, made from an export and the flags to plot it with
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct ChartConfig {
+    pub name: String,
+    pub input: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("The config file \"{0}\" could not be read!")]
+    UnreadableFile(PathBuf),
+
+    #[error("The config file \"{0}\" is invalid: {1}")]
+    InvalidFile(PathBuf, toml::de::Error),
+
+    #[error("The profile \"{0}\" is not defined in the config file!")]
+    UnknownProfile(String),
+
+    #[error(
+        "The export is for Experience ID {2}, but the profile \"{0}\" is for Experience ID {1}!"
+    )]
+    WrongUniverse(String, u64, u64),
+}
+
+/// The config file that will be used: the given one, or the default config file if it exists
+pub fn config_path(file: Option<&Path>) -> Option<PathBuf> {
+    match file {
+        Some(file) => Some(file.to_path_buf()),
+        None if Path::new(DEFAULT_CONFIG_FILE).is_file() => {
+            Some(PathBuf::from(DEFAULT_CONFIG_FILE))
+        }
+        None => None,
+    }
+}
+
+/// Loads the given config file, or the default config file if it exists. Having no config file at
+/// all is not an error.
+pub fn load_config(file: Option<&Path>) -> Result<Config, ConfigError> {
+    let Some(file) = config_path(file) else {
+        return Ok(Config::default());
+    };
+
+    info!("Loading config file {}...", file.display());
+
+    let contents =
+        std::fs::read_to_string(&file).map_err(|_| ConfigError::UnreadableFile(file.clone()))?;
+
+    toml::from_str(&contents).map_err(|e| ConfigError::InvalidFile(file, e))
+}
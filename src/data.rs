@@ -1,4 +1,5 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::ValueEnum;
 use fixed::types::I32F32;
 use plotters::coord::ranged1d::{KeyPointHint, NoDefaultFormatting, ValueFormatter};
 use plotters::data::float::FloatPrettyPrinter;
@@ -21,7 +22,16 @@ pub enum DataParsingError {
     CannotParse,
 }
 
-#[derive(EnumString, Display, Clone, Debug)]
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DataPointArithmeticError {
+    #[error("A calculation overflowed the range of values a data point can hold!")]
+    Overflow,
+
+    #[error("Attempted to perform data point arithmetic on different data point types!")]
+    MismatchedTypes,
+}
+
+#[derive(EnumString, Display, Clone, PartialEq, Eq, Debug)]
 pub enum KpiType {
     #[strum(to_string = "Daily Active Users")]
     DailyActiveUsers,
@@ -40,6 +50,250 @@ pub enum KpiType {
 
     #[strum(to_string = "Paying Users")]
     PayingUsers,
+
+    /// Daily active users as a percentage of monthly active users, derived from two exports
+    #[strum(to_string = "Stickiness")]
+    Stickiness,
+
+    /// Average revenue per daily active user, derived from two exports
+    #[strum(to_string = "ARPDAU")]
+    Arpdau,
+
+    /// Paying users as a percentage of daily active users, derived from two exports
+    #[strum(to_string = "Conversion Rate")]
+    ConversionRate,
+}
+
+impl KpiType {
+    /// Whether the KPI's values are percentages
+    pub fn is_percentage(&self) -> bool {
+        matches!(self, KpiType::Stickiness | KpiType::ConversionRate)
+    }
+}
+
+/// The unit playtime values are written in. Roblox exports playtime in hours.
+#[derive(ValueEnum, Display, Clone, Copy, PartialEq, Eq, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub enum PlaytimeUnit {
+    Minutes,
+    Hours,
+    Days,
+}
+
+impl PlaytimeUnit {
+    /// How many of the unit make up an hour
+    pub fn per_hour(self) -> f64 {
+        match self {
+            PlaytimeUnit::Minutes => 60.0,
+            PlaytimeUnit::Hours => 1.0,
+            PlaytimeUnit::Days => 1.0 / 24.0,
+        }
+    }
+}
+
+/// Formats a value for labels: whole numbers for large values, two decimal places for small ones
+pub fn format_number(value: f64) -> String {
+    if value.abs() >= 100.0 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.2}", value)
+    }
+}
+
+/// The suffixes large values are abbreviated with, from largest to smallest
+const ABBREVIATIONS: [(f64, &str); 4] = [(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "k")];
+
+/// Abbreviates values of a thousand or more with a suffix and up to two decimal places, e.g.
+/// 12400 as "12.4k" and 1200000 as "1.2M". Smaller values are returned as None.
+pub fn abbreviate_number(value: f64) -> Option<String> {
+    // Pick the suffix by the rounded value, so that 999999 becomes "1M" rather than "1000k"
+    let (scale, suffix) = ABBREVIATIONS
+        .iter()
+        .find(|(scale, _)| (value.abs() / scale * 100.0).round() >= 100.0)?;
+    let number = format!("{:.2}", value / scale);
+    let number = number.trim_end_matches('0').trim_end_matches('.');
+    Some(format!("{}{}", number, suffix))
+}
+
+/// What the values on the y-axis are, which decides how its labels are formatted
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AxisValues {
+    /// Whole numbers such as user counts, labelled without decimal places
+    Counts,
+    /// Fractional values such as normalized data, labelled with up to two decimal places
+    Ratios,
+    /// Percentages of a reference series, labelled like ratios with a percent sign
+    Percentages,
+}
+
+impl AxisValues {
+    /// Counts if every point is a whole number, otherwise ratios
+    pub fn of<'a>(points: impl IntoIterator<Item = &'a DataPoint>) -> Self {
+        if points
+            .into_iter()
+            .all(|point| matches!(point, DataPoint::Integer(_) | DataPoint::Zero))
+        {
+            AxisValues::Counts
+        } else {
+            AxisValues::Ratios
+        }
+    }
+
+    /// Formats a y-axis label, abbreviating large values so that they fit beside the axis
+    pub fn format(self, value: &DataPoint) -> String {
+        let value = f64::from(*value);
+        match self {
+            AxisValues::Counts | AxisValues::Ratios if value.abs() >= 1000.0 => {
+                abbreviate_number(value).unwrap_or_else(|| format!("{:.0}", value))
+            }
+            AxisValues::Counts => format!("{:.0}", value),
+            AxisValues::Ratios => FloatPrettyPrinter {
+                allow_scientific: false,
+                min_decimal: 0,
+                max_decimal: 2,
+            }
+            .print(value),
+            AxisValues::Percentages => format!("{}%", AxisValues::Ratios.format(&value.into())),
+        }
+    }
+}
+
+impl DataPoint {
+    /// Converts a value into a fractional data point, failing if it is too large to be held
+    pub fn checked_from_f64(value: f64) -> Result<DataPoint, DataPointArithmeticError> {
+        if value == 0f64 {
+            return Ok(DataPoint::Zero);
+        }
+        I32F32::checked_from_num(value)
+            .map(DataPoint::Float)
+            .ok_or(DataPointArithmeticError::Overflow)
+    }
+
+    /// Applies the matching operation to two data points of the same type. Zero takes on the type
+    /// of the other point.
+    fn checked_op(
+        self,
+        rhs: DataPoint,
+        float: fn(I32F32, I32F32) -> Option<I32F32>,
+        integer: fn(u64, u64) -> Option<u64>,
+    ) -> Result<DataPoint, DataPointArithmeticError> {
+        let result = match (self, rhs) {
+            (DataPoint::Float(lhs), DataPoint::Float(rhs)) => float(lhs, rhs).map(DataPoint::Float),
+            (DataPoint::Float(lhs), DataPoint::Zero) => {
+                float(lhs, I32F32::ZERO).map(DataPoint::Float)
+            }
+            (DataPoint::Zero, DataPoint::Float(rhs)) => {
+                float(I32F32::ZERO, rhs).map(DataPoint::Float)
+            }
+            (DataPoint::Integer(lhs), DataPoint::Integer(rhs)) => {
+                integer(lhs, rhs).map(DataPoint::Integer)
+            }
+            (DataPoint::Integer(lhs), DataPoint::Zero) => integer(lhs, 0).map(DataPoint::Integer),
+            (DataPoint::Zero, DataPoint::Integer(rhs)) => integer(0, rhs).map(DataPoint::Integer),
+            (DataPoint::Zero, DataPoint::Zero) => integer(0, 0).map(DataPoint::Integer),
+            _ => return Err(DataPointArithmeticError::MismatchedTypes),
+        };
+
+        // Results are kept in the same form as parsed points, where zero is always its own variant
+        match result {
+            Some(DataPoint::Integer(0)) => Ok(DataPoint::Zero),
+            Some(DataPoint::Float(value)) if value == I32F32::ZERO => Ok(DataPoint::Zero),
+            Some(point) => Ok(point),
+            None => Err(DataPointArithmeticError::Overflow),
+        }
+    }
+
+    /// Adds two data points, failing if the result overflows
+    pub fn checked_add(self, rhs: DataPoint) -> Result<DataPoint, DataPointArithmeticError> {
+        self.checked_op(rhs, I32F32::checked_add, u64::checked_add)
+    }
+
+    /// Subtracts a data point, failing if the result overflows or, for whole numbers, goes below zero
+    pub fn checked_sub(self, rhs: DataPoint) -> Result<DataPoint, DataPointArithmeticError> {
+        self.checked_op(rhs, I32F32::checked_sub, u64::checked_sub)
+    }
+
+    /// Multiplies two data points, failing if the result overflows
+    pub fn checked_mul(self, rhs: DataPoint) -> Result<DataPoint, DataPointArithmeticError> {
+        self.checked_op(rhs, I32F32::checked_mul, u64::checked_mul)
+    }
+
+    /// Adds two data points, clamping the result to the largest value a data point can hold
+    pub fn saturating_add(self, rhs: DataPoint) -> Result<DataPoint, DataPointArithmeticError> {
+        self.checked_op(
+            rhs,
+            |lhs, rhs| Some(lhs.saturating_add(rhs)),
+            |lhs, rhs| Some(lhs.saturating_add(rhs)),
+        )
+    }
+
+    /// Subtracts a data point, clamping the result to the smallest value a data point can hold
+    pub fn saturating_sub(self, rhs: DataPoint) -> Result<DataPoint, DataPointArithmeticError> {
+        self.checked_op(
+            rhs,
+            |lhs, rhs| Some(lhs.saturating_sub(rhs)),
+            |lhs, rhs| Some(lhs.saturating_sub(rhs)),
+        )
+    }
+
+    /// Multiplies two data points, clamping the result to the largest value a data point can hold
+    pub fn saturating_mul(self, rhs: DataPoint) -> Result<DataPoint, DataPointArithmeticError> {
+        self.checked_op(
+            rhs,
+            |lhs, rhs| Some(lhs.saturating_mul(rhs)),
+            |lhs, rhs| Some(lhs.saturating_mul(rhs)),
+        )
+    }
+
+    /// Rounds the point to the nearest whole number
+    pub fn round(self) -> DataPoint {
+        let value = f64::from(self).round();
+        if value <= 0f64 {
+            DataPoint::Zero
+        } else {
+            DataPoint::Integer(value as u64)
+        }
+    }
+}
+
+/// The largest whole number every smaller one of which an f64 holds exactly
+const MAX_EXACT_INTEGER: f64 = (1u64 << f64::MANTISSA_DIGITS) as f64;
+
+/// The characters spreadsheets group the digits of large numbers with
+const GROUPING_SEPARATORS: [char; 6] = [',', '.', ' ', '\'', '\u{a0}', '\u{202f}'];
+
+impl DataPoint {
+    /// Parses a value written with the given decimal separator, ignoring the separators digits are
+    /// grouped with, e.g. "1.234,5" with a comma as the decimal separator
+    pub fn parse_localized(s: &str, decimal_separator: char) -> Result<Self, DataParsingError> {
+        s.trim()
+            .chars()
+            .filter(|c| *c == decimal_separator || !GROUPING_SEPARATORS.contains(c))
+            .map(|c| if c == decimal_separator { '.' } else { c })
+            .collect::<String>()
+            .parse()
+    }
+}
+
+/// The value without the commas its thousands are grouped with, if it is grouped in groups of
+/// three the way English spreadsheets write it, e.g. "12,345,678" or "1,234.5"
+fn ungrouped(s: &str) -> Option<String> {
+    let (whole, fraction) = match s.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (s, None),
+    };
+    let mut groups = whole.split(',');
+    let first = groups.next()?;
+    let rest: Vec<&str> = groups.collect();
+
+    let digits = |group: &str| group.chars().all(|c| c.is_ascii_digit());
+    let grouped = !rest.is_empty()
+        && (1..=3).contains(&first.len())
+        && digits(first)
+        && rest.iter().all(|group| group.len() == 3 && digits(group))
+        && fraction.is_none_or(|fraction| !fraction.is_empty() && digits(fraction));
+
+    grouped.then(|| s.replace(',', ""))
 }
 
 impl FromStr for DataPoint {
@@ -49,11 +303,25 @@ impl FromStr for DataPoint {
         // Zero has to be a special case as it can appear when the data points aren't integers
         if s == "0" {
             Ok(DataPoint::Zero)
+        } else if let Some(ungrouped) = ungrouped(s) {
+            // Spreadsheets group the thousands of large counts, which are read as written
+            ungrouped.parse()
         } else if s.matches(char::is_numeric).collect::<String>() == s {
             // If the string does not contain a decimal point, then we can assume it is an integer
             Ok(DataPoint::Integer(
                 s.parse().map_err(|_| DataParsingError::CannotParse)?,
             ))
+        } else if s.contains(['e', 'E']) {
+            // Spreadsheets write large values in scientific notation, so whole values written
+            // that way are read as the counts they were
+            let value: f64 = s.parse().map_err(|_| DataParsingError::CannotParse)?;
+            if value == 0f64 {
+                Ok(DataPoint::Zero)
+            } else if value > 0f64 && value.fract() == 0f64 && value < MAX_EXACT_INTEGER {
+                Ok(DataPoint::Integer(value as u64))
+            } else {
+                DataPoint::checked_from_f64(value).map_err(|_| DataParsingError::CannotParse)
+            }
         } else {
             Ok(DataPoint::Float(
                 s.parse().map_err(|_| DataParsingError::CannotParse)?,
@@ -62,6 +330,20 @@ impl FromStr for DataPoint {
     }
 }
 
+/// Writes the data point so that it parses back into the same data point, as exports, reports and
+/// tooltips all show values this way. Decimals always have a decimal point, so that a whole decimal
+/// is not read back as an integer.
+impl std::fmt::Display for DataPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataPoint::Zero => write!(f, "0"),
+            DataPoint::Integer(value) => write!(f, "{}", value),
+            DataPoint::Float(value) if value.frac() == 0 => write!(f, "{}.0", value),
+            DataPoint::Float(value) => write!(f, "{}", value),
+        }
+    }
+}
+
 impl From<I32F32> for DataPoint {
     fn from(value: I32F32) -> Self {
         DataPoint::Float(value)
@@ -111,27 +393,8 @@ impl Mul for DataPoint {
             return DataPoint::Zero;
         }
 
-        match self {
-            DataPoint::Float(value_lhs) => {
-                let DataPoint::Float(value_rhs) = rhs else {
-                    panic!(
-                        "Attempted to perform data point arithmetic on different data point types!"
-                    )
-                };
-
-                DataPoint::Float(value_lhs * value_rhs)
-            }
-            DataPoint::Integer(value_lhs) => {
-                let DataPoint::Integer(value_rhs) = rhs else {
-                    panic!(
-                        "Attempted to perform data point arithmetic on different data point types!"
-                    )
-                };
-
-                DataPoint::Integer(value_lhs * value_rhs)
-            }
-            _ => unreachable!(),
-        }
+        // Checked so that overflows are caught in release builds too, rather than wrapping
+        self.checked_mul(rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -163,25 +426,7 @@ impl Sub for DataPoint {
             return self;
         }
 
-        match self {
-            DataPoint::Float(value_lhs) => {
-                let DataPoint::Float(value_rhs) = rhs else {
-                    panic!(
-                        "Attempted to perform data point arithmetic on different data point types!"
-                    )
-                };
-                DataPoint::Float(value_lhs - value_rhs)
-            }
-            DataPoint::Integer(value_lhs) => {
-                let DataPoint::Integer(value_rhs) = rhs else {
-                    panic!(
-                        "Attempted to perform data point arithmetic on different data point types!"
-                    )
-                };
-                DataPoint::Integer(value_lhs - value_rhs)
-            }
-            _ => unreachable!(),
-        }
+        self.checked_sub(rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -203,25 +448,7 @@ impl Add for DataPoint {
             return self;
         }
 
-        match self {
-            DataPoint::Float(value_lhs) => {
-                let DataPoint::Float(value_rhs) = rhs else {
-                    panic!(
-                        "Attempted to perform data point arithmetic on different data point types!"
-                    )
-                };
-                DataPoint::Float(value_lhs + value_rhs)
-            }
-            DataPoint::Integer(value_lhs) => {
-                let DataPoint::Integer(value_rhs) = rhs else {
-                    panic!(
-                        "Attempted to perform data point arithmetic on different data point types!"
-                    )
-                };
-                DataPoint::Integer(value_lhs + value_rhs)
-            }
-            _ => unreachable!(),
-        }
+        self.checked_add(rhs).unwrap_or_else(|e| panic!("{}", e))
     }
 }
 
@@ -231,8 +458,149 @@ impl AddAssign for DataPoint {
     }
 }
 
+/// An inclusive range of days, written as `2024-03-10..2024-03-12` or as a single day
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct DateRange {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+#[derive(Debug, Error)]
+pub enum DateRangeParsingError {
+    #[error("Dates must be written as YYYY-MM-DD, or as YYYY-MM-DD..YYYY-MM-DD for a range!")]
+    CannotParse,
+
+    #[error("The range ends on {1}, before it starts on {0}!")]
+    Backwards(NaiveDate, NaiveDate),
+}
+
+impl DateRange {
+    pub fn contains(&self, date: &DateTime<Utc>) -> bool {
+        (self.start..=self.end).contains(&date.date_naive())
+    }
+}
+
+impl FromStr for DateRange {
+    type Err = DateRangeParsingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |date: &str| {
+            NaiveDate::parse_from_str(date.trim(), "%F")
+                .map_err(|_| DateRangeParsingError::CannotParse)
+        };
+        let (start, end) = match s.split_once("..") {
+            Some((start, end)) => (parse(start)?, parse(end)?),
+            None => (parse(s)?, parse(s)?),
+        };
+        if end < start {
+            return Err(DateRangeParsingError::Backwards(start, end));
+        }
+        Ok(DateRange { start, end })
+    }
+}
+
+/// Margins added above and below the data on the y-axis, as percentages of the data's span
+#[derive(Clone, Copy, Debug)]
+pub struct Padding {
+    pub top: f64,
+    pub bottom: f64,
+}
+
+impl Default for Padding {
+    fn default() -> Self {
+        Padding {
+            top: 10f64,
+            bottom: 10f64,
+        }
+    }
+}
+
 pub struct RangedDataPoint(DataPoint, DataPoint);
 
+impl RangedDataPoint {
+    /// A range between two data points, as found by [`get_data_range`] or given explicitly
+    pub fn new(start: DataPoint, end: DataPoint) -> Self {
+        RangedDataPoint(start, end)
+    }
+
+    /// Converts a value into a data point of the same type as the range bounds so that comparisons
+    /// between them stay meaningful. Integer ranges use the given rounding function.
+    fn coerce(&self, value: f64, round: fn(f64) -> f64) -> DataPoint {
+        match self.1 {
+            DataPoint::Integer(_) if round(value) <= 0f64 => DataPoint::Zero,
+            DataPoint::Integer(_) => DataPoint::Integer(round(value) as u64),
+            _ => DataPoint::from(value),
+        }
+    }
+
+    /// Converts a key point back into a data point. Whole key points of integer ranges stay
+    /// integers, as counts can be too large for a fixed-point float.
+    fn key_point(&self, value: f64) -> DataPoint {
+        match self.1.max(self.0) {
+            DataPoint::Integer(_) if value > 0f64 && value.fract() == 0f64 => {
+                DataPoint::Integer(value as u64)
+            }
+            _ => DataPoint::from(value),
+        }
+    }
+
+    /// Widens the range so that the given value is visible with the same margin used by
+    /// [`get_data_range`]
+    pub fn including(self, value: f64, padding: Padding) -> Self {
+        let (start, end): (f64, f64) = (self.0.into(), self.1.into());
+
+        if value < start {
+            let start = value - (end - value) * padding.bottom / 100f64;
+            let start = if value >= 0f64 {
+                start.max(0f64)
+            } else {
+                start
+            };
+            RangedDataPoint(self.coerce(start, f64::floor), self.1)
+        } else if value > end {
+            RangedDataPoint(
+                self.0,
+                self.coerce(value + (value - start) * padding.top / 100f64, f64::ceil),
+            )
+        } else {
+            self
+        }
+    }
+
+    /// Widens a range with no width, such as that of a flat series or a single point, by 10% of its
+    /// value on either side, or by 1 if the value is zero, so that there is room to draw it. Ranges
+    /// of positive values are not widened below zero.
+    pub fn widened(self) -> Self {
+        let (start, end): (f64, f64) = (self.0.into(), self.1.into());
+        if start < end {
+            return self;
+        }
+
+        let margin = if start == 0f64 {
+            1f64
+        } else {
+            start.abs() / 10f64
+        };
+        let widened_start = if start >= 0f64 {
+            (start - margin).max(0f64)
+        } else {
+            start - margin
+        };
+        RangedDataPoint(
+            self.coerce(widened_start, f64::floor),
+            self.coerce(end + margin, f64::ceil),
+        )
+    }
+
+    /// Replaces either bound of the range with an explicit value
+    pub fn with_bounds(self, start: Option<f64>, end: Option<f64>) -> Self {
+        RangedDataPoint(
+            start.map_or(self.0, |start| self.coerce(start, f64::floor)),
+            end.map_or(self.1, |end| self.coerce(end, f64::ceil)),
+        )
+    }
+}
+
 impl Ranged for RangedDataPoint {
     type FormatOption = NoDefaultFormatting;
     type ValueType = DataPoint;
@@ -281,7 +649,7 @@ impl Ranged for RangedDataPoint {
         assert!(!(range.0.is_nan() || range.1.is_nan()));
 
         if (range.0 - range.1).abs() < f64::EPSILON {
-            return vec![DataPoint::from(range.0)];
+            return vec![self.key_point(range.0)];
         }
 
         let mut scale = 10f64.powf((range.1 - range.0).log(10.0).floor());
@@ -351,7 +719,7 @@ impl Ranged for RangedDataPoint {
             if new_left_relative < 0.0 {
                 left_relative += value_granularity;
             }
-            ret.push((left_relative + left_base).into());
+            ret.push(self.key_point(left_relative + left_base));
             left_relative += scale;
         }
         ret
@@ -380,33 +748,50 @@ impl ValueFormatter<DataPoint> for RangedDataPoint {
 #[allow(clippy::ptr_arg)]
 pub fn get_data_range(
     data: &Vec<(DateTime<Utc>, DataPoint)>,
+    padding: Padding,
 ) -> (Range<DateTime<Utc>>, RangedDataPoint) {
-    let mut value_range = data
-        .iter()
-        .min_by(|(_, point1), (_, point2)| point1.cmp(point2))
-        .expect("Failed to obtain least data point!")
-        .1
-        ..data
-            .iter()
-            .max_by(|(_, point1), (_, point2)| point1.cmp(point2))
-            .expect("Failed to obtain greatest data point!")
-            .1;
-
-    // add 10% boundary to make sure data points have margin
-    let value_range_len = value_range.end - value_range.start;
-    value_range.start -= (value_range_len / 10).min(value_range.start);
-    value_range.end += value_range_len / 10;
-
-    return (
-        data.iter()
-            .min_by(|(date1, _), (date2, _)| date1.cmp(date2))
-            .expect("Failed to obtain earliest date!")
-            .0
-            ..data
-                .iter()
-                .max_by(|(date1, _), (date2, _)| date1.cmp(date2))
-                .expect("Failed to obtain latest date!")
-                .0,
-        RangedDataPoint(value_range.start, value_range.end),
-    );
+    get_data_range_multi([data.as_slice()], padding)
+}
+
+/// The date range and padded value range spanning every point of every series, found in a single
+/// pass over the borrowed series rather than by collecting their points together first
+pub fn get_data_range_multi<'a>(
+    series: impl IntoIterator<Item = &'a [(DateTime<Utc>, DataPoint)]>,
+    padding: Padding,
+) -> (Range<DateTime<Utc>>, RangedDataPoint) {
+    let (dates, unpadded) = series
+        .into_iter()
+        .flatten()
+        .fold(
+            None::<(Range<DateTime<Utc>>, RangedDataPoint)>,
+            |range, (date, point)| {
+                Some(match range {
+                    None => (*date..*date, RangedDataPoint(*point, *point)),
+                    Some((dates, values)) => (
+                        dates.start.min(*date)..dates.end.max(*date),
+                        RangedDataPoint(values.0.min(*point), values.1.max(*point)),
+                    ),
+                })
+            },
+        )
+        .expect("Failed to obtain the range of the data!");
+
+    // add a margin around the data so that data points don't touch the edges of the chart
+    let (start, end): (f64, f64) = (unpadded.0.into(), unpadded.1.into());
+    let value_range_len = end - start;
+    let padded_start = start - value_range_len * padding.bottom / 100f64;
+    let padded_start = if start >= 0f64 {
+        padded_start.max(0f64)
+    } else {
+        padded_start
+    };
+    let padded_end = end + value_range_len * padding.top / 100f64;
+
+    (
+        dates,
+        RangedDataPoint(
+            unpadded.coerce(padded_start, f64::floor),
+            unpadded.coerce(padded_end, f64::ceil),
+        ),
+    )
 }
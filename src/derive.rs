@@ -0,0 +1,157 @@
+use crate::data::{DataPoint, DataPointArithmeticError, KpiType};
+use crate::parse::{AnalyticsData, MismatchError};
+use crate::warning::{self, Warning};
+use chrono::{DateTime, Utc};
+use clap::ValueEnum;
+use log::info;
+use std::collections::{BTreeMap, HashMap};
+use strum::Display;
+use thiserror::Error;
+
+/// A metric calculated by dividing one KPI by another, for engagement figures the Creator
+/// Dashboard does not provide
+#[derive(ValueEnum, Display, Clone, Copy, PartialEq, Eq, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub enum Derivation {
+    /// Daily active users as a percentage of monthly active users
+    Stickiness,
+    /// Robux earned per daily active user
+    Arpdau,
+    /// Paying users as a percentage of daily active users
+    Conversion,
+}
+
+impl Derivation {
+    /// The KPIs divided, as the numerator and the denominator
+    pub fn inputs(self) -> (KpiType, KpiType) {
+        match self {
+            Derivation::Stickiness => (KpiType::DailyActiveUsers, KpiType::MonthlyActiveUsers),
+            Derivation::Arpdau => (KpiType::DailyRevenue, KpiType::DailyActiveUsers),
+            Derivation::Conversion => (KpiType::PayingUsers, KpiType::DailyActiveUsers),
+        }
+    }
+
+    /// The KPI of the derived series
+    pub fn kpi(self) -> KpiType {
+        match self {
+            Derivation::Stickiness => KpiType::Stickiness,
+            Derivation::Arpdau => KpiType::Arpdau,
+            Derivation::Conversion => KpiType::ConversionRate,
+        }
+    }
+
+    /// What each ratio is multiplied by, such as 100 for percentages
+    fn scale(self) -> f64 {
+        match self {
+            Derivation::Stickiness | Derivation::Conversion => 100.0,
+            Derivation::Arpdau => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum DerivationError {
+    #[error("Deriving {0} needs one {1} export and one {2} export, but {3} and {4} were given!")]
+    MismatchedInputs(Derivation, KpiType, KpiType, KpiType, KpiType),
+
+    #[error(transparent)]
+    Mismatch(#[from] MismatchError),
+
+    #[error("The exports have no series in common to derive {0} from!")]
+    NoCommonSeries(Derivation),
+
+    #[error(transparent)]
+    Arithmetic(#[from] DataPointArithmeticError),
+}
+
+/// Divides the numerator's points by the denominator's on each date they share. Days on which the
+/// denominator is zero have no ratio and are left out.
+fn divide(
+    numerator: &[(DateTime<Utc>, DataPoint)],
+    denominator: &[(DateTime<Utc>, DataPoint)],
+    scale: f64,
+) -> Result<Vec<(DateTime<Utc>, DataPoint)>, DataPointArithmeticError> {
+    let denominator: HashMap<DateTime<Utc>, f64> = denominator
+        .iter()
+        .map(|(date, point)| (*date, f64::from(*point)))
+        .collect();
+
+    // Sorted by date, as the exports list each day once per series in no particular order
+    let ratios: BTreeMap<DateTime<Utc>, f64> = numerator
+        .iter()
+        .filter_map(|(date, point)| {
+            let divisor = denominator.get(date).filter(|divisor| **divisor != 0.0)?;
+            Some((*date, f64::from(*point) / divisor * scale))
+        })
+        .collect();
+    ratios
+        .into_iter()
+        .map(|(date, ratio)| Ok((date, DataPoint::checked_from_f64(ratio)?)))
+        .collect()
+}
+
+/// Derives a metric from two exports of the same experience and granularity, given in either
+/// order. Every series found in both, such as the total, breakdowns and benchmarks, is divided on
+/// the dates the two have in common. With `allow_mismatch`, exports that do not match are only
+/// warned about.
+pub fn derive(
+    derivation: Derivation,
+    first: AnalyticsData,
+    second: AnalyticsData,
+    allow_mismatch: bool,
+) -> Result<AnalyticsData, DerivationError> {
+    let (numerator_kpi, denominator_kpi) = derivation.inputs();
+    let (numerator, denominator) =
+        if first.kpi_type == numerator_kpi && second.kpi_type == denominator_kpi {
+            (first, second)
+        } else if second.kpi_type == numerator_kpi && first.kpi_type == denominator_kpi {
+            (second, first)
+        } else {
+            return Err(DerivationError::MismatchedInputs(
+                derivation,
+                numerator_kpi,
+                denominator_kpi,
+                first.kpi_type,
+                second.kpi_type,
+            ));
+        };
+
+    numerator.check_combinable(&denominator, true, allow_mismatch)?;
+
+    info!(
+        "Deriving {} from {} and {}...",
+        derivation, numerator.kpi_type, denominator.kpi_type
+    );
+
+    let mut data = HashMap::new();
+    for (name, points) in &numerator.data {
+        let Some(divisors) = denominator.data.get(name) else {
+            warning::emit(Warning::SeriesMissing(
+                name.to_string(),
+                denominator.kpi_type.clone(),
+            ));
+            continue;
+        };
+        let ratios = divide(points, divisors, derivation.scale())?;
+        if ratios.len() < points.len() {
+            warning::emit(Warning::NothingToDivideBy(
+                points.len() - ratios.len(),
+                name.to_string(),
+                denominator.kpi_type.clone(),
+            ));
+        }
+        if !ratios.is_empty() {
+            data.insert(name.clone(), ratios);
+        }
+    }
+    if data.is_empty() {
+        return Err(DerivationError::NoCommonSeries(derivation));
+    }
+
+    Ok(AnalyticsData {
+        kpi_type: derivation.kpi(),
+        universe_id: numerator.universe_id,
+        data,
+        playtime_unit: None,
+    })
+}
@@ -0,0 +1,233 @@
+use crate::digest::{format_percent, format_value};
+use crate::parse::{parse_analytics_file, AnalyticsData, AnalyticsParseError, MismatchError};
+use chrono::{DateTime, Utc};
+use clap::Args;
+use log::info;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::PathBuf;
+use thiserror::Error;
+
+#[derive(Args)]
+pub struct DiffOptions {
+    /// The earlier export
+    old: PathBuf,
+
+    /// The later export, of the same experience and KPI
+    new: PathBuf,
+
+    #[arg(long, value_name = "PERCENT", default_value_t = 0.0)]
+    /// How far a value can move, as a percentage of its earlier value, before it is reported as changed
+    tolerance: f64,
+
+    #[arg(long)]
+    /// Compares the exports even if they are of different experiences or granularities, warning instead of failing
+    allow_mismatch: bool,
+
+    #[arg(long)]
+    /// Compares the exports even if they are of different KPIs, warning instead of failing
+    mix_kpis: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum DiffError {
+    #[error(transparent)]
+    Parse(#[from] AnalyticsParseError),
+
+    #[error("The tolerance cannot be negative!")]
+    InvalidTolerance,
+
+    #[error(transparent)]
+    Mismatch(#[from] MismatchError),
+}
+
+/// Which of the two exports something was found in
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Old,
+    New,
+}
+
+/// A day on which the two exports disagree
+#[derive(Clone, PartialEq, Debug)]
+pub enum Discrepancy {
+    /// Only one of the exports has a value for the day
+    Missing {
+        date: DateTime<Utc>,
+        side: Side,
+        value: f64,
+    },
+    /// Both exports have a value for the day, but they differ by more than the tolerance
+    Changed {
+        date: DateTime<Utc>,
+        old: f64,
+        new: f64,
+    },
+}
+
+impl Discrepancy {
+    pub fn date(&self) -> DateTime<Utc> {
+        match self {
+            Discrepancy::Missing { date, .. } | Discrepancy::Changed { date, .. } => *date,
+        }
+    }
+}
+
+/// The differences between one series in the two exports
+#[derive(Clone, Debug)]
+pub struct SeriesDiff {
+    pub name: String,
+    /// The export the series is in, if it is missing from the other one entirely
+    pub only_in: Option<Side>,
+    /// The days on which the series differs, in date order
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+fn values(data: &AnalyticsData, name: &str) -> Option<BTreeMap<DateTime<Utc>, f64>> {
+    data.data.get(name).map(|points| {
+        points
+            .iter()
+            .map(|(date, point)| (*date, (*point).into()))
+            .collect()
+    })
+}
+
+/// Compares every series of the two exports day by day, returning the series that differ. Values
+/// are only reported as changed if they moved by more than `tolerance` percent of the old value.
+pub fn diff(old: &AnalyticsData, new: &AnalyticsData, tolerance: f64) -> Vec<SeriesDiff> {
+    let names: BTreeSet<&str> = old
+        .data
+        .keys()
+        .chain(new.data.keys())
+        .map(|name| &**name)
+        .collect();
+
+    names
+        .into_iter()
+        .filter_map(|name| {
+            let (old_values, new_values) = match (values(old, name), values(new, name)) {
+                (Some(old_values), Some(new_values)) => (old_values, new_values),
+                (old_values, _) => {
+                    return Some(SeriesDiff {
+                        name: name.to_string(),
+                        only_in: Some(if old_values.is_some() {
+                            Side::Old
+                        } else {
+                            Side::New
+                        }),
+                        discrepancies: Vec::new(),
+                    })
+                }
+            };
+
+            let dates: BTreeSet<&DateTime<Utc>> =
+                old_values.keys().chain(new_values.keys()).collect();
+            let discrepancies: Vec<Discrepancy> = dates
+                .into_iter()
+                .filter_map(
+                    |&date| match (old_values.get(&date), new_values.get(&date)) {
+                        (Some(&old), Some(&new)) => ((new - old).abs()
+                            > old.abs() * tolerance / 100.0)
+                            .then_some(Discrepancy::Changed { date, old, new }),
+                        (Some(&value), None) => Some(Discrepancy::Missing {
+                            date,
+                            side: Side::Old,
+                            value,
+                        }),
+                        (None, Some(&value)) => Some(Discrepancy::Missing {
+                            date,
+                            side: Side::New,
+                            value,
+                        }),
+                        (None, None) => None,
+                    },
+                )
+                .collect();
+
+            (!discrepancies.is_empty()).then(|| SeriesDiff {
+                name: name.to_string(),
+                only_in: None,
+                discrepancies,
+            })
+        })
+        .collect()
+}
+
+/// The differences as plain text, naming the exports as given
+pub fn render_text(diffs: &[SeriesDiff], old_name: &str, new_name: &str) -> String {
+    let side_name = |side: Side| match side {
+        Side::Old => old_name,
+        Side::New => new_name,
+    };
+
+    let mut text = String::new();
+    for series in diffs {
+        text.push_str(&format!("{}\n", series.name));
+        if let Some(side) = series.only_in {
+            text.push_str(&format!("  Only in {}\n", side_name(side)));
+        }
+        for discrepancy in &series.discrepancies {
+            let date = discrepancy.date().format("%F");
+            match discrepancy {
+                Discrepancy::Missing { side, value, .. } => text.push_str(&format!(
+                    "  {}  only in {}: {}\n",
+                    date,
+                    side_name(*side),
+                    format_value(Some(*value))
+                )),
+                Discrepancy::Changed { old, new, .. } => text.push_str(&format!(
+                    "  {}  {} -> {}  ({})\n",
+                    date,
+                    format_value(Some(*old)),
+                    format_value(Some(*new)),
+                    format_percent((*old != 0.0).then(|| (new - old) / old * 100.0))
+                )),
+            }
+        }
+        text.push('\n');
+    }
+
+    let count: usize = diffs
+        .iter()
+        .map(|series| series.discrepancies.len() + usize::from(series.only_in.is_some()))
+        .sum();
+    match count {
+        0 => text.push_str("No discrepancies found\n"),
+        1 => text.push_str("1 discrepancy found\n"),
+        count => text.push_str(&format!("{} discrepancies found\n", count)),
+    }
+
+    text
+}
+
+impl DiffOptions {
+    /// Prints the differences between the exports. Returns whether there were any.
+    pub fn run(&self) -> Result<bool, DiffError> {
+        if self.tolerance < 0.0 || self.tolerance.is_nan() {
+            return Err(DiffError::InvalidTolerance);
+        }
+
+        let old = parse_analytics_file(&self.old)?;
+        let new = parse_analytics_file(&self.new)?;
+
+        old.check_same_kpi(&new, self.mix_kpis)?;
+        old.check_combinable(&new, true, self.allow_mismatch)?;
+
+        info!(
+            "Comparing {} with {}...",
+            self.old.display(),
+            self.new.display()
+        );
+
+        let diffs = diff(&old, &new, self.tolerance);
+        print!(
+            "{}",
+            render_text(
+                &diffs,
+                &self.old.display().to_string(),
+                &self.new.display().to_string()
+            )
+        );
+
+        Ok(!diffs.is_empty())
+    }
+}
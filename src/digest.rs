@@ -0,0 +1,427 @@
+use crate::data::format_number;
+use crate::output::{check_output, check_overwrite, write_atomically, OutputError};
+use crate::parse::{parse_analytics_file, AnalyticsData, AnalyticsParseError};
+use crate::style::{Style, Theme};
+use chrono::{DateTime, Duration, Utc};
+use clap::{Args, ValueEnum};
+use log::info;
+use plotters::backend::{BitMapBackend, DrawingBackend};
+use plotters::coord::Shift;
+use plotters::drawing::{DrawingArea, IntoDrawingArea};
+use plotters::element::Text;
+use plotters::style::full_palette::{GREEN_700, RED_700};
+use plotters::style::{FontStyle, IntoFont, RGBColor};
+use plotters_svg::SVGBackend;
+use std::fs;
+use std::path::{Path, PathBuf};
+use strum::Display;
+use thiserror::Error;
+
+#[derive(ValueEnum, Display, Clone, Copy, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub enum DigestFormat {
+    /// Aligned plain text, for pasting into chat
+    Text,
+    /// A Markdown table
+    Markdown,
+    /// A PNG image of the table
+    Png,
+    /// An SVG image of the table
+    Svg,
+}
+
+#[derive(Args)]
+pub struct DigestOptions {
+    #[arg(required = true)]
+    /// The Roblox Analytics exports to summarize, one per KPI
+    files: Vec<PathBuf>,
+
+    #[arg(long, conflicts_with = "days")]
+    /// Compares the last 7 days with the 7 days before them. This is the default
+    weekly: bool,
+
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Compares the last number of days with the same number of days before them
+    days: Option<u32>,
+
+    #[arg(long, default_value_t = DigestFormat::Text)]
+    /// The format the digest is written in
+    format: DigestFormat,
+
+    #[arg(short, long)]
+    /// The file to write the digest to. Text and Markdown digests are printed if this is omitted
+    out: Option<PathBuf>,
+
+    #[arg(short, long)]
+    /// Overwrites the output file if it already exists
+    force: bool,
+
+    #[arg(long, value_enum, default_value_t = Theme::Light)]
+    /// The color theme of image digests
+    theme: Theme,
+}
+
+#[derive(Debug, Error)]
+pub enum DigestError {
+    #[error(transparent)]
+    Parse(#[from] AnalyticsParseError),
+
+    #[error(transparent)]
+    Output(#[from] OutputError),
+
+    #[error("Image digests must be written to a file! Pass one with --out.")]
+    MissingOutput,
+
+    #[error("The digest file \"{0}\" could not be written!")]
+    UnwritableFile(PathBuf),
+}
+
+/// A series' average daily value over the current and previous periods
+#[derive(Clone, Debug)]
+pub struct SeriesDelta {
+    pub name: String,
+    pub current: Option<f64>,
+    pub previous: Option<f64>,
+}
+
+impl SeriesDelta {
+    pub fn change(&self) -> Option<f64> {
+        Some(self.current? - self.previous?)
+    }
+
+    /// The change relative to the previous period, or None if it was zero or missing
+    pub fn change_percent(&self) -> Option<f64> {
+        let previous = self.previous.filter(|previous| *previous != 0.0)?;
+        Some(self.change()? / previous * 100.0)
+    }
+}
+
+/// The comparison of the latest period of one export with the period before it
+#[derive(Clone, Debug)]
+pub struct KpiDigest {
+    pub kpi: String,
+    pub universe_id: u64,
+    /// The first and last days of the current period
+    pub current: (DateTime<Utc>, DateTime<Utc>),
+    /// The first and last days of the previous period
+    pub previous: (DateTime<Utc>, DateTime<Utc>),
+    pub series: Vec<SeriesDelta>,
+}
+
+fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (count, total) = values.fold((0, 0.0), |(count, total), value| (count + 1, total + value));
+    (count > 0).then_some(total / count as f64)
+}
+
+impl KpiDigest {
+    /// Compares the last `days` days of the export, ending on its latest date, with the `days` days
+    /// before them. Returns None if the export has no data.
+    pub fn new(data: &AnalyticsData, days: u32) -> Option<Self> {
+        let end = data.data.values().flatten().map(|(date, _)| *date).max()?;
+        let period = Duration::days(days as i64);
+        let current_start = end - period + Duration::days(1);
+        let previous_start = current_start - period;
+
+        let mut series: Vec<SeriesDelta> = data
+            .data
+            .iter()
+            .map(|(name, points)| {
+                let between = |start: DateTime<Utc>, end: DateTime<Utc>| {
+                    average(
+                        points
+                            .iter()
+                            .filter(|(date, _)| *date >= start && *date < end)
+                            .map(|(_, point)| (*point).into()),
+                    )
+                };
+
+                SeriesDelta {
+                    name: name.to_string(),
+                    current: between(current_start, end + Duration::days(1)),
+                    previous: between(previous_start, current_start),
+                }
+            })
+            .collect();
+        series.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Some(KpiDigest {
+            kpi: data.kpi_type.to_string(),
+            universe_id: data.universe_id,
+            current: (current_start, end),
+            previous: (previous_start, current_start - Duration::days(1)),
+            series,
+        })
+    }
+}
+
+pub(crate) fn format_value(value: Option<f64>) -> String {
+    value.map(format_number).unwrap_or_else(|| "-".to_string())
+}
+
+fn format_change(change: Option<f64>) -> String {
+    match change {
+        Some(change) => format!(
+            "{}{}",
+            if change >= 0.0 { "+" } else { "" },
+            format_value(Some(change))
+        ),
+        None => "-".to_string(),
+    }
+}
+
+pub(crate) fn format_percent(percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{:+.1}%", percent),
+        None => "-".to_string(),
+    }
+}
+
+fn format_range((start, end): (DateTime<Utc>, DateTime<Utc>)) -> String {
+    format!("{} to {}", start.format("%F"), end.format("%F"))
+}
+
+/// The digest as aligned plain text
+pub fn render_text(digests: &[KpiDigest]) -> String {
+    let mut text = String::new();
+
+    for digest in digests {
+        text.push_str(&format!(
+            "{} for Experience ID {}\n{} vs {}, average per day\n",
+            digest.kpi,
+            digest.universe_id,
+            format_range(digest.current),
+            format_range(digest.previous)
+        ));
+
+        let width = digest
+            .series
+            .iter()
+            .map(|series| series.name.len())
+            .max()
+            .unwrap_or_default();
+        for series in &digest.series {
+            text.push_str(&format!(
+                "  {:<width$}  {:>10}  {:>10}  {:>10}  {:>8}\n",
+                series.name,
+                format_value(series.current),
+                format_value(series.previous),
+                format_change(series.change()),
+                format_percent(series.change_percent()),
+                width = width
+            ));
+        }
+        text.push('\n');
+    }
+
+    text
+}
+
+/// The digest as one Markdown table per KPI
+pub fn render_markdown(digests: &[KpiDigest]) -> String {
+    let mut markdown = String::new();
+
+    for digest in digests {
+        markdown.push_str(&format!(
+            "### {} for Experience ID {}\n\n_{} vs {}, average per day_\n\n",
+            digest.kpi,
+            digest.universe_id,
+            format_range(digest.current),
+            format_range(digest.previous)
+        ));
+        markdown.push_str("| Series | Current | Previous | Change | Change % |\n");
+        markdown.push_str("|---|---:|---:|---:|---:|\n");
+        for series in &digest.series {
+            markdown.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                series.name.replace('|', "\\|"),
+                format_value(series.current),
+                format_value(series.previous),
+                format_change(series.change()),
+                format_percent(series.change_percent())
+            ));
+        }
+        markdown.push('\n');
+    }
+
+    markdown
+}
+
+const ROW_HEIGHT: i32 = 40;
+const HEADING_HEIGHT: i32 = 90;
+const BLOCK_GAP: i32 = 20;
+const IMAGE_WIDTH: u32 = 1000;
+
+fn image_height(digests: &[KpiDigest]) -> u32 {
+    digests
+        .iter()
+        .map(|digest| HEADING_HEIGHT + ROW_HEIGHT * (digest.series.len() as i32 + 1) + BLOCK_GAP)
+        .sum::<i32>() as u32
+        + 20
+}
+
+fn draw_digest<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    digests: &[KpiDigest],
+    style: &Style,
+) {
+    let font = style.font_family();
+    let theme = style.theme;
+    let text = |content: String, (x, y): (i32, i32), size: f64, color: RGBColor, bold: bool| {
+        let font_style = if bold {
+            FontStyle::Bold
+        } else {
+            FontStyle::Normal
+        };
+        area.draw(&Text::new(
+            content,
+            (x, y),
+            (font, style.font_size(size), font_style)
+                .into_font()
+                .color(&color),
+        ))
+        .expect("Failed to draw digest text!");
+    };
+
+    area.fill(&theme.background())
+        .expect("Failed to fill drawing area!");
+
+    let columns = [
+        (20, "Series"),
+        (440, "Current"),
+        (580, "Previous"),
+        (720, "Change"),
+        (860, "Change %"),
+    ];
+    let mut y = 20;
+    for digest in digests {
+        text(
+            format!("{} for Experience ID {}", digest.kpi, digest.universe_id),
+            (20, y),
+            30.0,
+            theme.foreground(),
+            true,
+        );
+        text(
+            format!(
+                "{} vs {}, average per day",
+                format_range(digest.current),
+                format_range(digest.previous)
+            ),
+            (20, y + 42),
+            18.0,
+            theme.muted(),
+            false,
+        );
+        y += HEADING_HEIGHT;
+
+        for (x, heading) in columns {
+            text(heading.to_string(), (x, y), 20.0, theme.muted(), true);
+        }
+        y += ROW_HEIGHT;
+
+        for series in &digest.series {
+            let color = match series.change() {
+                Some(change) if change > 0.0 => GREEN_700,
+                Some(change) if change < 0.0 => RED_700,
+                _ => theme.foreground(),
+            };
+            let cells = [
+                (series.name.clone(), theme.foreground()),
+                (format_value(series.current), theme.foreground()),
+                (format_value(series.previous), theme.foreground()),
+                (format_change(series.change()), color),
+                (format_percent(series.change_percent()), color),
+            ];
+            for ((x, _), (content, color)) in columns.iter().zip(cells) {
+                text(content, (*x, y), 20.0, color, false);
+            }
+            y += ROW_HEIGHT;
+        }
+        y += BLOCK_GAP;
+    }
+}
+
+/// Draws the digest as a table into an SVG file, or a bitmap file for any other format
+pub fn render_image(
+    digests: &[KpiDigest],
+    file: &Path,
+    format: DigestFormat,
+    theme: Theme,
+) -> Result<(), DigestError> {
+    let style = Style {
+        theme,
+        ..Style::default()
+    };
+    let size = (IMAGE_WIDTH, image_height(digests));
+    let unwritable = || DigestError::UnwritableFile(file.to_path_buf());
+
+    write_atomically(
+        file,
+        |temporary| {
+            if let DigestFormat::Svg = format {
+                let area = SVGBackend::new(temporary, size).into_drawing_area();
+                draw_digest(&area, digests, &style);
+                area.present().map_err(|_| unwritable())
+            } else {
+                let area = BitMapBackend::new(temporary, size).into_drawing_area();
+                draw_digest(&area, digests, &style);
+                area.present().map_err(|_| unwritable())
+            }
+        },
+        unwritable,
+    )
+}
+
+impl DigestOptions {
+    pub fn run(&self) -> Result<(), DigestError> {
+        let days = self.days.unwrap_or(7);
+
+        // Checked before reading the exports, so that an unusable output fails fast
+        if let Some(out) = &self.out {
+            if let DigestFormat::Png | DigestFormat::Svg = self.format {
+                check_output(out)?;
+            }
+            if !self.force {
+                check_overwrite([out.as_path()])?;
+            }
+        }
+
+        let mut digests = Vec::new();
+        for file in &self.files {
+            let data = parse_analytics_file(file)?;
+            digests.extend(KpiDigest::new(&data, days));
+        }
+
+        info!(
+            "Comparing the last {} days of {} exports...",
+            days,
+            digests.len()
+        );
+
+        let contents = match self.format {
+            DigestFormat::Text => render_text(&digests),
+            DigestFormat::Markdown => render_markdown(&digests),
+            DigestFormat::Png | DigestFormat::Svg => {
+                let out = self.out.as_deref().ok_or(DigestError::MissingOutput)?;
+                render_image(&digests, out, self.format, self.theme)?;
+                info!("Wrote digest to {}", out.display());
+                return Ok(());
+            }
+        };
+
+        match &self.out {
+            Some(out) => {
+                let unwritable = || DigestError::UnwritableFile(out.clone());
+                write_atomically(
+                    out,
+                    |temporary| fs::write(temporary, contents).map_err(|_| unwritable()),
+                    unwritable,
+                )?;
+                info!("Wrote digest to {}", out.display());
+            }
+            None => print!("{}", contents),
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,89 @@
+use std::process::ExitCode;
+
+/// What the process exits with, so that scripts wrapping Rasorite can tell an unreadable export
+/// from a chart that could not be drawn. Invalid command lines exit with 2, as clap reports them.
+///
+/// Network errors have no code of their own: Rasorite only goes online to publish and notify once
+/// the chart is written, so they set the [`Convenience::Publish`] and [`Convenience::Notify`] bits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Exit {
+    Success,
+    /// Anything not covered below, such as an invalid config file or an output that already exists
+    Failure,
+    /// An export could not be read, or could not be combined with another
+    Parse,
+    /// The chart, or a file written with it such as an export, report or manifest, could not be drawn
+    /// or written
+    Plot,
+    /// A check found what it looks for, such as `diff` finding days on which two exports disagree
+    Alert,
+}
+
+impl Exit {
+    pub fn code(self) -> u8 {
+        match self {
+            Exit::Success => 0,
+            Exit::Failure => 1,
+            Exit::Parse => 3,
+            Exit::Plot => 4,
+            Exit::Alert => 6,
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit.code())
+    }
+}
+
+/// Something done for convenience once the chart is written. Its failure does not fail the run,
+/// but adds its own bit to the exit code.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Convenience {
+    /// Opening the chart in a viewer
+    Open,
+    /// Publishing the data to Google Sheets, Notion, a webhook or the GitHub step summary
+    Publish,
+    /// Checking the rules in the config file, and sending alerts for those that fired
+    Notify,
+}
+
+impl Convenience {
+    fn bit(self) -> u8 {
+        match self {
+            Convenience::Open => 8,
+            Convenience::Publish => 16,
+            Convenience::Notify => 32,
+        }
+    }
+}
+
+/// The outcome of a run whose chart was written, with the conveniences that failed after it
+#[derive(Default, Debug)]
+pub struct Written {
+    failed: u8,
+    /// Whether a rule fired with `exit_code` set, which makes the status an alert
+    alerted: bool,
+}
+
+impl Written {
+    pub fn fail(&mut self, convenience: Convenience) {
+        self.failed |= convenience.bit();
+    }
+
+    pub fn alert(&mut self) {
+        self.alerted = true;
+    }
+}
+
+impl From<Written> for ExitCode {
+    fn from(written: Written) -> Self {
+        let status = if written.alerted {
+            Exit::Alert
+        } else {
+            Exit::Success
+        };
+        ExitCode::from(status.code() | written.failed)
+    }
+}
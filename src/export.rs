@@ -0,0 +1,363 @@
+use crate::data::DataPoint;
+use crate::holiday::Holiday;
+use crate::output::write_atomically;
+use crate::parse::AnalyticsData;
+use crate::stats::series_stats;
+use crate::warning::{self, Warning};
+use arrow_array::{ArrayRef, Decimal128Array, RecordBatch, StringArray, TimestampMillisecondArray};
+use arrow_schema::{ArrowError, DataType, Field, Schema, TimeUnit};
+use chrono::{DateTime, Datelike, Utc};
+use clap::ValueEnum;
+use log::info;
+use parquet::arrow::ArrowWriter;
+use parquet::errors::ParquetError;
+use rust_xlsxwriter::{ExcelDateTime, Format, Image, Workbook, XlsxError};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use strum::Display;
+use thiserror::Error;
+
+/// The number of decimal places values are stored with. Fractional values are parsed as 32.32 fixed
+/// point numbers, so ten places represent them without loss of meaningful precision.
+const DECIMAL_SCALE: i8 = 10;
+const DECIMAL_PRECISION: u8 = 38;
+
+/// File formats the parsed data can be written in alongside the chart
+#[derive(ValueEnum, Display, Clone, Copy, Debug)]
+#[strum(serialize_all = "kebab-case")]
+pub enum ExportFormat {
+    /// A columnar Apache Parquet file with one row per series per date
+    Parquet,
+    /// A CSV file with one row per date and one column per series
+    WideCsv,
+    /// A CSV file with one row per date and one column per breakdown series, such as each platform
+    /// or country, leaving out the total and benchmarks
+    BreakdownCsv,
+    /// An Excel workbook with the data, summary statistics and the chart
+    Xlsx,
+}
+
+#[derive(Debug, Error)]
+pub enum ExportError {
+    #[error("The export file \"{0}\" could not be written!")]
+    UnwritableFile(PathBuf),
+
+    #[error("The export has no breakdown series to write! Export it with a \"View by\" breakdown, such as platform or country.")]
+    BreakdownMissing,
+
+    #[error("Unable to build the exported data: {0}")]
+    Arrow(#[from] ArrowError),
+
+    #[error("Unable to write the Parquet file: {0}")]
+    Parquet(#[from] ParquetError),
+
+    #[error("Unable to write the CSV file: {0}")]
+    Csv(#[from] csv::Error),
+
+    #[error("Unable to write the Excel workbook: {0}")]
+    Xlsx(#[from] XlsxError),
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            // Kept distinct from plain .csv so that an export can never overwrite its input
+            ExportFormat::WideCsv => "wide.csv",
+            ExportFormat::BreakdownCsv => "breakdowns.csv",
+            ExportFormat::Xlsx => "xlsx",
+        }
+    }
+}
+
+/// The path data is exported to, e.g. `plot.parquet` for `plot.png`
+pub fn export_path(out_file: &Path, format: ExportFormat) -> PathBuf {
+    out_file.with_extension(format.extension())
+}
+
+/// Every data point as `(series, date, value)`, ordered by series name and then by date
+fn sorted_points(data: &AnalyticsData) -> Vec<(&str, DateTime<Utc>, DataPoint)> {
+    let mut points: Vec<_> = data
+        .data
+        .iter()
+        .flat_map(|(name, points)| {
+            points
+                .iter()
+                .map(move |(date, point)| (&**name, *date, *point))
+        })
+        .collect();
+    points.sort_by(|a, b| a.0.cmp(b.0).then(a.1.cmp(&b.1)));
+    points
+}
+
+/// A data point as an unscaled decimal with [`DECIMAL_SCALE`] decimal places
+fn to_decimal(point: DataPoint) -> i128 {
+    let scale = 10i128.pow(DECIMAL_SCALE as u32);
+    match point {
+        DataPoint::Zero => 0,
+        DataPoint::Integer(value) => value as i128 * scale,
+        // Rounds to the nearest decimal rather than truncating the binary fraction
+        DataPoint::Float(value) => {
+            (value.to_bits() as i128 * scale + (1 << 31)).div_euclid(1 << 32)
+        }
+    }
+}
+
+fn export_parquet(data: &AnalyticsData, file: &Path) -> Result<(), ExportError> {
+    let points = sorted_points(data);
+
+    let schema = Schema::new(vec![
+        Field::new("series", DataType::Utf8, false),
+        Field::new(
+            "date",
+            DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new(
+            "value",
+            DataType::Decimal128(DECIMAL_PRECISION, DECIMAL_SCALE),
+            false,
+        ),
+    ])
+    .with_metadata(HashMap::from([
+        ("universe_id".to_string(), data.universe_id.to_string()),
+        ("kpi".to_string(), data.kpi_type.to_string()),
+    ]));
+
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(
+            points.iter().map(|(name, _, _)| *name),
+        )),
+        Arc::new(
+            TimestampMillisecondArray::from_iter_values(
+                points.iter().map(|(_, date, _)| date.timestamp_millis()),
+            )
+            .with_timezone("UTC"),
+        ),
+        Arc::new(
+            Decimal128Array::from_iter_values(
+                points.iter().map(|(_, _, point)| to_decimal(*point)),
+            )
+            .with_precision_and_scale(DECIMAL_PRECISION, DECIMAL_SCALE)?,
+        ),
+    ];
+    let batch = RecordBatch::try_new(Arc::new(schema), columns)?;
+
+    let output = File::create(file).map_err(|_| ExportError::UnwritableFile(file.to_path_buf()))?;
+    let mut writer = ArrowWriter::try_new(output, batch.schema(), None)?;
+    writer.write(&batch)?;
+    writer.close()?;
+
+    Ok(())
+}
+
+/// The value of each series on each date, with series in the same order as their names
+type WideRows = BTreeMap<DateTime<Utc>, Vec<Option<DataPoint>>>;
+
+/// The value of each of the named series on every date they have data for
+fn wide_rows(data: &AnalyticsData, series: &[&str]) -> WideRows {
+    let mut rows = WideRows::new();
+    for (index, name) in series.iter().enumerate() {
+        for (date, point) in &data.data[*name] {
+            rows.entry(*date)
+                .or_insert_with(|| vec![None; series.len()])[index] = Some(*point);
+        }
+    }
+
+    rows
+}
+
+/// Every series name in order
+fn all_series(data: &AnalyticsData) -> Vec<&str> {
+    let mut series: Vec<&str> = data.data.keys().map(|name| &**name).collect();
+    series.sort();
+    series
+}
+
+/// Writes the named series as a CSV file with one column each
+fn export_wide_csv(data: &AnalyticsData, series: &[&str], file: &Path) -> Result<(), ExportError> {
+    let rows = wide_rows(data, series);
+
+    let mut writer = csv::Writer::from_path(file)
+        .map_err(|_| ExportError::UnwritableFile(file.to_path_buf()))?;
+    writer.write_record(std::iter::once("date").chain(series.iter().copied()))?;
+    for (date, values) in rows {
+        writer.write_record(
+            std::iter::once(date.format("%F").to_string()).chain(
+                values
+                    .into_iter()
+                    .map(|value| value.map(|value| value.to_string()).unwrap_or_default()),
+            ),
+        )?;
+    }
+    writer
+        .flush()
+        .map_err(|_| ExportError::UnwritableFile(file.to_path_buf()))?;
+
+    Ok(())
+}
+
+fn excel_date(date: DateTime<Utc>) -> Result<ExcelDateTime, XlsxError> {
+    ExcelDateTime::from_ymd(date.year() as u16, date.month() as u8, date.day() as u8)
+}
+
+fn export_xlsx(
+    data: &AnalyticsData,
+    file: &Path,
+    chart: &Path,
+    holidays: &[Holiday],
+) -> Result<(), ExportError> {
+    let header = Format::new().set_bold();
+    let date = Format::new().set_num_format("yyyy-mm-dd");
+    let percent = Format::new().set_num_format("0.0%");
+    let number = Format::new().set_num_format("#,##0.##");
+
+    let mut workbook = Workbook::new();
+
+    let series = all_series(data);
+    let rows = wide_rows(data, &series);
+    let sheet = workbook.add_worksheet().set_name("Data")?;
+    sheet.write_with_format(0, 0, "Date", &header)?;
+    sheet.set_column_width(0, 12)?;
+    for (column, name) in series.iter().enumerate() {
+        sheet.write_with_format(0, column as u16 + 1, *name, &header)?;
+        sheet.set_column_width(column as u16 + 1, name.len().max(10) as f64)?;
+    }
+    for (row, (day, values)) in rows.into_iter().enumerate() {
+        let row = row as u32 + 1;
+        sheet.write_datetime_with_format(row, 0, excel_date(day)?, &date)?;
+        for (column, value) in values.into_iter().enumerate() {
+            if let Some(value) = value {
+                sheet.write_number_with_format(row, column as u16 + 1, value, &number)?;
+            }
+        }
+    }
+
+    let sheet = workbook.add_worksheet().set_name("Stats")?;
+    let mut headings = vec![
+        "Series",
+        "Points",
+        "First Date",
+        "Last Date",
+        "First",
+        "Last",
+        "Change",
+        "Change %",
+        "Min",
+        "Max",
+        "Mean",
+        "Median",
+        "Std Dev",
+        "Total",
+    ];
+    if data.playtime_unit.is_some() {
+        headings.push("Person-Years");
+    }
+    // The holiday comparison follows the other columns, wherever they end
+    let holiday_column = headings.len() as u16;
+    if !holidays.is_empty() {
+        headings.extend(["Holiday Mean", "Other Mean", "Holiday Change %"]);
+    }
+    for (column, heading) in headings.iter().enumerate() {
+        sheet.write_with_format(0, column as u16, *heading, &header)?;
+        sheet.set_column_width(column as u16, 12)?;
+    }
+    for (row, stats) in series_stats(data, holidays).iter().enumerate() {
+        let row = row as u32 + 1;
+        sheet.set_column_width(0, stats.name.len().max(12) as f64)?;
+        sheet.write(row, 0, stats.name.as_str())?;
+        sheet.write(row, 1, stats.points as u32)?;
+        sheet.write_datetime_with_format(row, 2, excel_date(stats.first_date)?, &date)?;
+        sheet.write_datetime_with_format(row, 3, excel_date(stats.last_date)?, &date)?;
+        for (column, value) in [(4, stats.first), (5, stats.last), (6, stats.change())] {
+            sheet.write_number_with_format(row, column, value, &number)?;
+        }
+        if let Some(change) = stats.change_percent() {
+            sheet.write_number_with_format(row, 7, change / 100.0, &percent)?;
+        }
+        for (column, value) in [
+            (8, stats.min),
+            (9, stats.max),
+            (10, stats.mean),
+            (11, stats.median),
+            (12, stats.std_dev),
+            (13, stats.total),
+        ] {
+            sheet.write_number_with_format(row, column, value, &number)?;
+        }
+        if let Some(person_years) = stats.person_years {
+            sheet.write_number_with_format(row, 14, person_years, &number)?;
+        }
+        for (column, value) in [
+            (holiday_column, stats.holiday_mean),
+            (holiday_column + 1, stats.non_holiday_mean),
+        ] {
+            if let Some(value) = value {
+                sheet.write_number_with_format(row, column, value, &number)?;
+            }
+        }
+        if let Some(change) = stats.holiday_change_percent() {
+            sheet.write_number_with_format(row, holiday_column + 2, change / 100.0, &percent)?;
+        }
+    }
+
+    let sheet = workbook.add_worksheet().set_name("Chart")?;
+    sheet.write_with_format(
+        0,
+        0,
+        format!("{} for Experience ID {}", data.kpi_type, data.universe_id),
+        &header,
+    )?;
+    let is_png = chart
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"));
+    if is_png {
+        let image = Image::new(chart)?
+            .set_scale_width(0.6)
+            .set_scale_height(0.6);
+        sheet.insert_image(2, 0, &image)?;
+    } else {
+        warning::emit(Warning::WorkbookChartNotPng);
+        sheet.write(2, 0, "Render the chart as a PNG to embed it here.")?;
+    }
+
+    workbook.save(file).map_err(|e| match e {
+        XlsxError::IoError(_) => ExportError::UnwritableFile(file.to_path_buf()),
+        e => ExportError::Xlsx(e),
+    })?;
+
+    Ok(())
+}
+
+/// Writes the parsed data to the given file in the given format. Formats that include the chart
+/// read it from `chart`, so it must be rendered first, and formats with summary statistics compare
+/// the given holidays with other days.
+pub fn export_data(
+    data: &AnalyticsData,
+    format: ExportFormat,
+    file: &Path,
+    chart: &Path,
+    holidays: &[Holiday],
+) -> Result<(), ExportError> {
+    info!("Exporting data as {}...", format);
+
+    write_atomically(
+        file,
+        |temporary| match format {
+            ExportFormat::Parquet => export_parquet(data, temporary),
+            ExportFormat::WideCsv => export_wide_csv(data, &all_series(data), temporary),
+            ExportFormat::BreakdownCsv => match data.breakdowns() {
+                breakdowns if breakdowns.is_empty() => Err(ExportError::BreakdownMissing),
+                breakdowns => export_wide_csv(data, &breakdowns, temporary),
+            },
+            ExportFormat::Xlsx => export_xlsx(data, temporary, chart, holidays),
+        },
+        || ExportError::UnwritableFile(file.to_path_buf()),
+    )?;
+
+    info!("Exported data to {}", file.display());
+
+    Ok(())
+}
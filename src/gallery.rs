@@ -0,0 +1,149 @@
+use crate::metadata::{escape_xml, read_metadata};
+use crate::output::write_atomically;
+use log::info;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The name of the gallery page written into the output directory
+pub const GALLERY_FILE: &str = "index.html";
+
+#[derive(Debug, Error)]
+pub enum GalleryError {
+    #[error("The directory \"{0}\" could not be read!")]
+    UnreadableDirectory(PathBuf),
+
+    #[error("The gallery \"{0}\" could not be written!")]
+    UnwritableFile(PathBuf),
+}
+
+/// A chart found in the output directory
+struct Chart {
+    name: String,
+    /// The last date in the chart's data, for ordering charts newest first
+    date_end: Option<String>,
+}
+
+/// Charts grouped by universe ID and then KPI. Charts without metadata are grouped under neither.
+type Groups = BTreeMap<Option<u64>, BTreeMap<Option<String>, Vec<Chart>>>;
+
+/// Finds the charts in a directory and groups them by the universe ID and KPI embedded in them
+fn find_charts(dir: &Path) -> Result<Groups, GalleryError> {
+    let entries =
+        fs::read_dir(dir).map_err(|_| GalleryError::UnreadableDirectory(dir.to_path_buf()))?;
+
+    let mut groups = Groups::new();
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        let Some(name) = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+        // Hidden files include charts that are still being written
+        let is_chart = path.extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("png") || extension.eq_ignore_ascii_case("svg")
+        });
+        if name.starts_with('.') || !is_chart || !path.is_file() {
+            continue;
+        }
+
+        let mut metadata = read_metadata(&path).unwrap_or_default();
+        groups
+            .entry(
+                metadata
+                    .get("universe_id")
+                    .and_then(|universe_id| universe_id.parse().ok()),
+            )
+            .or_default()
+            .entry(metadata.remove("kpi"))
+            .or_default()
+            .push(Chart {
+                name,
+                date_end: metadata.remove("date_end"),
+            });
+    }
+
+    for charts in groups.values_mut().flat_map(|kpis| kpis.values_mut()) {
+        charts.sort_by(|a, b| (&b.date_end, &b.name).cmp(&(&a.date_end, &a.name)));
+    }
+
+    Ok(groups)
+}
+
+/// Makes a file name safe to use as a relative link
+fn href(name: &str) -> String {
+    name.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('#', "%23")
+        .replace('?', "%3F")
+}
+
+/// Builds the gallery page, naming experiences after `names` where given instead of their ID
+fn page(groups: &Groups, names: &HashMap<u64, String>) -> String {
+    let mut page = String::from(concat!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+        "<title>Rasorite</title>\n<style>\n",
+        "body { font-family: sans-serif; margin: 2em; background: #f4f4f4; }\n",
+        "section { background: white; padding: 1em; margin-bottom: 2em; }\n",
+        ".charts { display: flex; flex-wrap: wrap; gap: 1em; }\n",
+        "figure { margin: 0; width: 320px; }\n",
+        "img { width: 100%; border: 1px solid #ddd; }\n",
+        "figcaption { font-size: small; color: #666; overflow-wrap: anywhere; }\n",
+        "</style>\n</head>\n<body>\n<h1>Rasorite</h1>\n",
+    ));
+
+    for (universe_id, kpis) in groups {
+        let heading = match universe_id {
+            Some(universe_id) => match names.get(universe_id) {
+                Some(name) => name.clone(),
+                None => format!("Experience ID {}", universe_id),
+            },
+            None => "Other Charts".to_string(),
+        };
+        page.push_str(&format!("<section>\n<h2>{}</h2>\n", escape_xml(&heading)));
+
+        for (kpi, charts) in kpis {
+            if let Some(kpi) = kpi {
+                page.push_str(&format!("<h3>{}</h3>\n", escape_xml(kpi)));
+            }
+            page.push_str("<div class=\"charts\">\n");
+            for chart in charts {
+                page.push_str(&format!(
+                    "<figure>\n<a href=\"{0}\"><img src=\"{0}\" alt=\"{1}\" loading=\"lazy\"></a>\n<figcaption>{1}</figcaption>\n</figure>\n",
+                    escape_xml(&href(&chart.name)),
+                    escape_xml(&chart.name)
+                ));
+            }
+            page.push_str("</div>\n");
+        }
+
+        page.push_str("</section>\n");
+    }
+
+    page.push_str("</body>\n</html>\n");
+    page
+}
+
+/// Writes an `index.html` thumbnail gallery of every chart in the directory, grouped by experience
+/// and KPI, replacing any gallery written before. Returns the path of the gallery.
+pub fn write_gallery(dir: &Path, names: &HashMap<u64, String>) -> Result<PathBuf, GalleryError> {
+    let groups = find_charts(dir)?;
+    let file = dir.join(GALLERY_FILE);
+
+    write_atomically(
+        &file,
+        |temporary| {
+            fs::write(temporary, page(&groups, names))
+                .map_err(|_| GalleryError::UnwritableFile(file.clone()))
+        },
+        || GalleryError::UnwritableFile(file.clone()),
+    )?;
+
+    info!("Wrote gallery to {}", file.display());
+    Ok(file)
+}
@@ -0,0 +1,235 @@
+use crate::parse::AnalyticsData;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// A day on which players' habits change, such as a public holiday
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Holiday {
+    pub date: NaiveDate,
+    pub name: String,
+}
+
+/// The regions with a built-in holiday calendar
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Region {
+    /// United States federal holidays
+    Us,
+    /// Bank holidays in England and Wales
+    Uk,
+    /// Brazilian national holidays
+    Br,
+}
+
+/// Where holidays are taken from: a built-in calendar or a file
+#[derive(Clone, Debug)]
+pub enum HolidaySource {
+    Region(Region),
+    File(PathBuf),
+}
+
+impl FromStr for HolidaySource {
+    type Err = Infallible;
+
+    /// Reads a region code such as `us`, or else a path to a file of holidays
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "us" => HolidaySource::Region(Region::Us),
+            "uk" => HolidaySource::Region(Region::Uk),
+            "br" => HolidaySource::Region(Region::Br),
+            _ => HolidaySource::File(PathBuf::from(s)),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum HolidayError {
+    #[error("The holiday calendar \"{0}\" could not be read! Give a CSV file of dates and names, or one of the built-in calendars: us, uk or br.")]
+    UnreadableFile(PathBuf),
+
+    #[error("The holiday calendar \"{0}\" has an invalid line \"{1}\"! Each line must be a date written as YYYY-MM-DD followed by a name.")]
+    InvalidLine(PathBuf, String),
+}
+
+/// Easter Sunday in the Gregorian calendar, by the anonymous Gregorian algorithm
+fn easter(year: i32) -> NaiveDate {
+    let a = year % 19;
+    let b = year / 100;
+    let c = year % 100;
+    let d = b / 4;
+    let e = b % 4;
+    let f = (b + 8) / 25;
+    let g = (b - f + 1) / 3;
+    let h = (19 * a + b - d - g + 15) % 30;
+    let i = c / 4;
+    let k = c % 4;
+    let l = (32 + 2 * e + 2 * i - h - k) % 7;
+    let m = (a + 11 * h + 22 * l) / 451;
+    let month = (h + l - 7 * m + 114) / 31;
+    let day = (h + l - 7 * m + 114) % 31 + 1;
+    NaiveDate::from_ymd_opt(year, month as u32, day as u32).expect("Easter is always a valid date!")
+}
+
+fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).expect("Fixed holidays are always valid dates!")
+}
+
+/// The nth given weekday of the month, e.g. the third Monday of January
+fn nth_weekday(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n)
+        .expect("Every month has at least four of each weekday!")
+}
+
+/// The last given weekday of the month, e.g. the last Monday of May
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday(year, month, weekday, 4))
+}
+
+/// The holidays of a built-in calendar in a single year. Holidays are given on the day itself rather
+/// than on any weekday they are observed on, as that is when players' habits change.
+fn region_holidays(region: Region, year: i32) -> Vec<(NaiveDate, &'static str)> {
+    let easter = easter(year);
+    let mut holidays = match region {
+        Region::Us => vec![
+            (date(year, 1, 1), "New Year's Day"),
+            (
+                nth_weekday(year, 1, Weekday::Mon, 3),
+                "Martin Luther King Jr. Day",
+            ),
+            (nth_weekday(year, 2, Weekday::Mon, 3), "Presidents' Day"),
+            (last_weekday(year, 5, Weekday::Mon), "Memorial Day"),
+            (date(year, 7, 4), "Independence Day"),
+            (nth_weekday(year, 9, Weekday::Mon, 1), "Labor Day"),
+            (nth_weekday(year, 10, Weekday::Mon, 2), "Columbus Day"),
+            (date(year, 11, 11), "Veterans Day"),
+            (nth_weekday(year, 11, Weekday::Thu, 4), "Thanksgiving"),
+            (date(year, 12, 25), "Christmas Day"),
+        ],
+        Region::Uk => vec![
+            (date(year, 1, 1), "New Year's Day"),
+            (easter - Duration::days(2), "Good Friday"),
+            (easter + Duration::days(1), "Easter Monday"),
+            (
+                nth_weekday(year, 5, Weekday::Mon, 1),
+                "Early May Bank Holiday",
+            ),
+            (last_weekday(year, 5, Weekday::Mon), "Spring Bank Holiday"),
+            (last_weekday(year, 8, Weekday::Mon), "Summer Bank Holiday"),
+            (date(year, 12, 25), "Christmas Day"),
+            (date(year, 12, 26), "Boxing Day"),
+        ],
+        Region::Br => vec![
+            (date(year, 1, 1), "New Year's Day"),
+            (easter - Duration::days(48), "Carnival"),
+            (easter - Duration::days(47), "Carnival"),
+            (easter - Duration::days(2), "Good Friday"),
+            (date(year, 4, 21), "Tiradentes"),
+            (date(year, 5, 1), "Labour Day"),
+            (easter + Duration::days(60), "Corpus Christi"),
+            (date(year, 9, 7), "Independence Day"),
+            (date(year, 10, 12), "Our Lady of Aparecida"),
+            (date(year, 11, 2), "All Souls' Day"),
+            (date(year, 11, 15), "Republic Day"),
+            (date(year, 12, 25), "Christmas Day"),
+        ],
+    };
+
+    // Holidays only observed since a given year
+    match region {
+        Region::Us if year >= 2021 => holidays.push((date(year, 6, 19), "Juneteenth")),
+        Region::Br if year >= 2024 => {
+            holidays.push((date(year, 11, 20), "Black Consciousness Day"))
+        }
+        _ => {}
+    }
+    holidays
+}
+
+/// Reads a CSV file of holidays, with a date written as YYYY-MM-DD and a name on each line. A
+/// header line and lines starting with `#` are skipped.
+fn read_holidays(file: &Path) -> Result<Vec<Holiday>, HolidayError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .comment(Some(b'#'))
+        .trim(csv::Trim::All)
+        .from_path(file)
+        .map_err(|_| HolidayError::UnreadableFile(file.to_path_buf()))?;
+
+    let mut holidays = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(|_| HolidayError::UnreadableFile(file.to_path_buf()))?;
+        let invalid = || {
+            HolidayError::InvalidLine(
+                file.to_path_buf(),
+                record.iter().collect::<Vec<_>>().join(","),
+            )
+        };
+
+        let Some(day) = record.get(0).filter(|day| !day.is_empty()) else {
+            continue;
+        };
+        if index == 0 && day.eq_ignore_ascii_case("date") {
+            continue;
+        }
+        let date = NaiveDate::parse_from_str(day, "%F").map_err(|_| invalid())?;
+        let name = record
+            .get(1)
+            .filter(|name| !name.is_empty())
+            .ok_or_else(invalid)?;
+        holidays.push(Holiday {
+            date,
+            name: name.to_string(),
+        });
+    }
+    Ok(holidays)
+}
+
+/// The holidays from every source between two days inclusive, in date order
+pub fn holidays_between(
+    sources: &[HolidaySource],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Result<Vec<Holiday>, HolidayError> {
+    let mut holidays = Vec::new();
+    for source in sources {
+        match source {
+            HolidaySource::Region(region) => {
+                for year in start.year()..=end.year() {
+                    holidays.extend(region_holidays(*region, year).into_iter().map(
+                        |(date, name)| Holiday {
+                            date,
+                            name: name.to_string(),
+                        },
+                    ));
+                }
+            }
+            HolidaySource::File(file) => holidays.extend(read_holidays(file)?),
+        }
+    }
+
+    holidays.retain(|holiday| (start..=end).contains(&holiday.date));
+    holidays.sort_by(|a, b| (a.date, &a.name).cmp(&(b.date, &b.name)));
+    // The same holiday from more than one calendar is only shown once
+    holidays.dedup();
+    Ok(holidays)
+}
+
+/// The holidays from every source within the dates of the export
+pub fn holidays_for(
+    sources: &[HolidaySource],
+    data: &AnalyticsData,
+) -> Result<Vec<Holiday>, HolidayError> {
+    let dates = data
+        .data
+        .values()
+        .flatten()
+        .map(|(date, _)| date.date_naive());
+    match (dates.clone().min(), dates.max()) {
+        (Some(start), Some(end)) if !sources.is_empty() => holidays_between(sources, start, end),
+        _ => Ok(Vec::new()),
+    }
+}
@@ -0,0 +1,32 @@
+//! The parsing and plotting core of Rasorite, shared by the command line tool and the WebAssembly
+//! build
+
+pub mod alert;
+pub mod benchmark;
+pub mod changepoint;
+pub mod config;
+pub mod data;
+pub mod derive;
+pub mod diff;
+pub mod digest;
+pub mod export;
+pub mod gallery;
+pub mod holiday;
+pub mod locale;
+pub mod manifest;
+pub mod metadata;
+pub mod output;
+pub mod parse;
+pub mod plot;
+pub mod portfolio;
+pub mod redact;
+pub mod report;
+pub mod scorecard;
+pub mod stats;
+pub mod style;
+pub mod summary;
+pub mod timings;
+pub mod transform;
+pub mod warning;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -0,0 +1,278 @@
+use crate::benchmark::BenchmarkSeries;
+use crate::data::{KpiType, PlaytimeUnit};
+use chrono::{DateTime, Datelike, Utc};
+use clap::ValueEnum;
+use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+use intl_pluralrules::{PluralCategory, PluralRuleType, PluralRules};
+use log::warn;
+use std::str::FromStr;
+use unic_langid::LanguageIdentifier;
+
+/// The languages chart text can be written in
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Lang {
+    #[default]
+    En,
+    Es,
+    #[value(name = "pt-BR")]
+    PtBr,
+    Fr,
+    De,
+}
+
+impl Lang {
+    fn id(self) -> LanguageIdentifier {
+        match self {
+            Lang::En => "en",
+            Lang::Es => "es",
+            Lang::PtBr => "pt-BR",
+            Lang::Fr => "fr",
+            Lang::De => "de",
+        }
+        .parse()
+        .expect("Built-in language identifiers are valid!")
+    }
+
+    /// The Fluent messages for the language, compiled into the binary so that it needs no files
+    /// beside it
+    fn resource(self) -> &'static str {
+        match self {
+            Lang::En => include_str!("../locales/en.ftl"),
+            Lang::Es => include_str!("../locales/es.ftl"),
+            Lang::PtBr => include_str!("../locales/pt-BR.ftl"),
+            Lang::Fr => include_str!("../locales/fr.ftl"),
+            Lang::De => include_str!("../locales/de.ftl"),
+        }
+    }
+
+    fn bundle(self) -> FluentBundle<FluentResource> {
+        let resource = FluentResource::try_new(self.resource().to_string())
+            .expect("Failed to parse built-in translations!");
+        let mut bundle = FluentBundle::new(vec![self.id()]);
+        // Isolation marks help browsers lay out mixed-direction text, but plotters draws them as
+        // boxes
+        bundle.set_use_isolating(false);
+        bundle
+            .add_resource(resource)
+            .expect("Failed to load built-in translations!");
+        bundle
+    }
+}
+
+/// The order in which the day, month and year of a date are written
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DateOrder {
+    DayMonthYear,
+    MonthDayYear,
+    YearMonthDay,
+}
+
+/// The regional conventions a chart is laid out with, given as a language tag such as `en-GB` or
+/// `ar-SA`. Unlike [`Lang`], which picks the language of the chart's text, a locale picks how dates
+/// are written and which way the chart reads.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Locale(LanguageIdentifier);
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .map(Locale)
+            .map_err(|_| "expected a language tag such as en-GB".to_string())
+    }
+}
+
+impl Locale {
+    fn language(&self) -> &str {
+        self.0.language.as_str()
+    }
+
+    fn region(&self) -> Option<&str> {
+        self.0.region.as_ref().map(|region| region.as_str())
+    }
+
+    /// Whether the locale's language is written from right to left
+    pub fn is_right_to_left(&self) -> bool {
+        matches!(
+            self.language(),
+            "ar" | "he" | "iw" | "fa" | "ur" | "yi" | "ps" | "dv" | "ckb" | "sd" | "ug"
+        )
+    }
+
+    /// The order dates are written in, going by the region where it differs between the regions
+    /// a language is spoken in
+    pub fn date_order(&self) -> DateOrder {
+        match (self.language(), self.region()) {
+            (_, Some("US" | "PH" | "FM" | "MH" | "PW" | "AS" | "GU" | "MP" | "VI")) => {
+                DateOrder::MonthDayYear
+            }
+            (_, Some("CA" | "CN" | "JP" | "KR" | "TW" | "HU" | "LT")) => DateOrder::YearMonthDay,
+            ("zh" | "ja" | "ko" | "hu" | "lt" | "sv", _) => DateOrder::YearMonthDay,
+            // English without a region follows American conventions
+            ("en", None) => DateOrder::MonthDayYear,
+            _ => DateOrder::DayMonthYear,
+        }
+    }
+
+    /// The character between the day, month and year of a date
+    fn date_separator(&self) -> char {
+        match (self.date_order(), self.language()) {
+            (DateOrder::YearMonthDay, "zh" | "ja") => '/',
+            (DateOrder::YearMonthDay, _) => '-',
+            (DateOrder::MonthDayYear, _) => '/',
+            (
+                DateOrder::DayMonthYear,
+                "de" | "ru" | "pl" | "cs" | "sk" | "fi" | "nb" | "nn" | "no" | "da" | "tr" | "uk"
+                | "ro" | "hr" | "sl" | "sr" | "bg" | "et" | "lv" | "is" | "az" | "kk" | "be",
+            ) => '.',
+            (DateOrder::DayMonthYear, "nl") => '-',
+            (DateOrder::DayMonthYear, _) => '/',
+        }
+    }
+
+    /// The character between the whole and fractional parts of a number, going by the region where
+    /// it differs between the regions a language is spoken in
+    pub fn decimal_separator(&self) -> char {
+        match (self.language(), self.region()) {
+            (_, Some("US" | "MX" | "CH" | "PR" | "GT" | "DO" | "PA" | "PE" | "PH")) => '.',
+            (
+                "de" | "fr" | "es" | "pt" | "it" | "nl" | "ru" | "pl" | "cs" | "sk" | "tr" | "uk"
+                | "id" | "da" | "sv" | "nb" | "nn" | "no" | "fi" | "ro" | "hu" | "el" | "vi" | "hr"
+                | "sl" | "sr" | "bg" | "et" | "lv" | "lt" | "ca" | "az" | "kk" | "be",
+                _,
+            ) => ',',
+            _ => '.',
+        }
+    }
+
+    /// A date written in numbers in the locale's order, e.g. "23/02/2024" for `en-GB`
+    pub fn numeric_date(&self, date: &DateTime<Utc>) -> String {
+        let separator = self.date_separator();
+        let format = match self.date_order() {
+            DateOrder::DayMonthYear => format!("%d{0}%m{0}%Y", separator),
+            DateOrder::MonthDayYear => format!("%m{0}%d{0}%Y", separator),
+            DateOrder::YearMonthDay => format!("%Y{0}%m{0}%d", separator),
+        };
+        date.format(&format).to_string()
+    }
+}
+
+/// Writes chart text in a chosen language
+pub struct Localizer {
+    /// The chosen language, followed by English for any messages it is missing
+    bundles: Vec<FluentBundle<FluentResource>>,
+    ordinals: Option<PluralRules>,
+}
+
+impl Localizer {
+    pub fn new(lang: Lang) -> Self {
+        let mut bundles = vec![lang.bundle()];
+        if lang != Lang::En {
+            bundles.push(Lang::En.bundle());
+        }
+
+        Localizer {
+            bundles,
+            ordinals: PluralRules::create(lang.id(), PluralRuleType::ORDINAL).ok(),
+        }
+    }
+
+    /// Formats the message with the given ID and arguments
+    pub fn message(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let args: FluentArgs = args
+            .iter()
+            .map(|(key, value)| (*key, value.clone()))
+            .collect();
+
+        for bundle in &self.bundles {
+            let Some(pattern) = bundle.get_message(id).and_then(|message| message.value()) else {
+                continue;
+            };
+
+            let mut errors = Vec::new();
+            let text = bundle.format_pattern(pattern, Some(&args), &mut errors);
+            for error in errors {
+                warn!("Failed to format message \"{}\": {}", id, error);
+            }
+            return text.into_owned();
+        }
+
+        warn!("The message \"{}\" has no translation!", id);
+        id.to_string()
+    }
+
+    /// The name of a KPI
+    pub fn kpi(&self, kpi: &KpiType) -> String {
+        self.message(
+            match kpi {
+                KpiType::DailyActiveUsers => "kpi-daily-active-users",
+                KpiType::MonthlyActiveUsers => "kpi-monthly-active-users",
+                KpiType::Visits => "kpi-sessions",
+                KpiType::TotalPlayTimeHours => "kpi-playtime",
+                KpiType::DailyRevenue => "kpi-daily-revenue",
+                KpiType::PayingUsers => "kpi-paying-users",
+                KpiType::Stickiness => "kpi-stickiness",
+                KpiType::Arpdau => "kpi-arpdau",
+                KpiType::ConversionRate => "kpi-conversion-rate",
+            },
+            &[],
+        )
+    }
+
+    /// The name of a unit of playtime, e.g. "days"
+    pub fn playtime_unit(&self, unit: PlaytimeUnit) -> String {
+        self.message("unit", &[("unit", unit.to_string().into())])
+    }
+
+    /// A readable description of a benchmark, e.g. "Similar Experiences (median)"
+    pub fn benchmark(&self, benchmark: &BenchmarkSeries) -> String {
+        let percentile = benchmark.percentile.map(|percentile| {
+            let ordinal = match self
+                .ordinals
+                .as_ref()
+                .and_then(|rules| rules.select(percentile).ok())
+            {
+                Some(PluralCategory::ZERO) => "zero",
+                Some(PluralCategory::ONE) => "one",
+                Some(PluralCategory::TWO) => "two",
+                Some(PluralCategory::FEW) => "few",
+                Some(PluralCategory::MANY) => "many",
+                _ => "other",
+            };
+            self.message(
+                "percentile",
+                &[
+                    ("percentile", percentile.into()),
+                    ("ordinal", ordinal.into()),
+                ],
+            )
+        });
+
+        match (&benchmark.source, percentile) {
+            (Some(source), Some(percentile)) => self.message(
+                "benchmark-with-percentile",
+                &[
+                    ("source", source.as_str().into()),
+                    ("percentile", percentile.into()),
+                ],
+            ),
+            (Some(source), None) => source.clone(),
+            (None, Some(percentile)) => percentile,
+            (None, None) => self.message("benchmark-unnamed", &[]),
+        }
+    }
+
+    /// A date written out with the name of its month, e.g. "Feb 23, 2024"
+    pub fn date(&self, date: &DateTime<Utc>) -> String {
+        let month = self.message("month", &[("month", date.month().into())]);
+        self.message(
+            "axis-date",
+            &[
+                ("day", date.day().to_string().into()),
+                ("month", month.into()),
+                ("year", date.year().to_string().into()),
+            ],
+        )
+    }
+}
@@ -1,28 +1,67 @@
-use crate::parse::parse_analytics_file;
-use crate::plot::plot_data;
-use clap::Parser;
+use crate::alert::check_rules;
+use crate::config::{config_path, load_config};
+use crate::derive::{derive, Derivation};
+use crate::diff::{DiffError, DiffOptions};
+use crate::digest::{DigestError, DigestOptions};
+use crate::exit::{Convenience, Exit, Written};
+use crate::export::{export_data, export_path, ExportFormat};
+use crate::holiday::holidays_for;
+use crate::manifest::{manifest_path, DataSummary, Manifest};
+use crate::notify::{emit_alerts, notify, post_summary};
+use crate::output::{check_output, check_overwrite};
+use crate::parse::{parse_localized_analytics_file, read_analytics_file};
+use crate::plot::{plot_data, PlotOptions};
+use crate::portfolio::{PortfolioError, PortfolioOptions};
+use crate::redact::{redact_panics, RedactingLogger};
+use crate::report::{render_report, ReportContext, ReportError};
+use crate::scorecard::{ScorecardError, ScorecardOptions};
+use crate::serve::ServeOptions;
+use crate::summary::{render_summary, write_github_summary};
+use crate::tui::TuiOptions;
+use crate::warning::Warning;
+use crate::watch::WatchOptions;
+use clap::{Parser, Subcommand};
 use clap_verbosity_flag::WarnLevel;
-use log::error;
-use std::path::PathBuf;
+use log::{error, info, warn};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-mod data;
-mod parse;
-mod plot;
+use rasorite::{
+    alert, benchmark, config, data, derive, diff, digest, export, gallery, holiday, manifest,
+    metadata, output, parse, plot, portfolio, redact, report, scorecard, stats, summary, timings,
+    warning,
+};
+
+mod exit;
+mod notify;
+mod notion;
+#[cfg(feature = "preview")]
+mod preview;
+mod serve;
+mod sheets;
+mod tui;
+mod watch;
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(
+    version,
+    about,
+    long_about = None,
+    args_conflicts_with_subcommands = true,
+    args_override_self = true,
+    subcommand_negates_reqs = true
+)]
 struct Cli {
-    #[arg(short, long)]
-    /// Plots the analytics series normalized against the benchmark series instead of plotting both the benchmark series and the analytics series
-    normalize: bool,
+    #[command(subcommand)]
+    command: Option<Command>,
 
-    #[arg(short, long)]
+    #[arg(short, long, required = true)]
     /// The CSV file exported from Roblox Analytics
-    in_file: PathBuf,
+    in_file: Option<PathBuf>,
 
+    #[arg(required = true)]
     /// The file to export the graph to. Must be an image file type, can be either bitmap or vector
-    out_file: PathBuf,
+    out_file: Option<PathBuf>,
 
     #[command(flatten)]
     verbose: clap_verbosity_flag::Verbosity<WarnLevel>,
@@ -30,33 +69,530 @@ struct Cli {
     #[arg(short, long)]
     /// Does not try to open the output file after it is created
     silent: bool,
+
+    #[arg(short, long)]
+    /// Overwrites the output files if they already exist
+    force: bool,
+
+    #[command(flatten)]
+    plot: PlotOptions,
+
+    #[arg(long, requires = "with")]
+    /// Plots a metric derived from the input and the export given with --with, such as stickiness (daily ÷ monthly active users)
+    derive: Option<Derivation>,
+
+    #[arg(long, value_name = "FILE", requires = "derive")]
+    /// The second export a --derive metric is calculated from. The two can be given in either order
+    with: Option<PathBuf>,
+
+    #[arg(long, value_name = "FORMAT")]
+    /// Also writes the parsed data next to the chart in the given format. Can be given multiple times
+    export: Vec<ExportFormat>,
+
+    #[arg(long, requires = "report")]
+    /// A minijinja template rendered with the series statistics and chart path, e.g. for a weekly report
+    template: Option<PathBuf>,
+
+    #[arg(long, requires = "template")]
+    /// The file to write the rendered template to
+    report: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Upserts the parsed series into the Google Sheet configured in the config file
+    publish_sheets: bool,
+
+    #[arg(long)]
+    /// Upserts a row for every data point into the Notion database configured in the config file
+    sync_notion: bool,
+
+    #[arg(long, value_name = "URL")]
+    /// Posts a JSON summary of the run to the URL once the chart is written: the experience, KPI, latest figures, warnings and the chart in base64
+    webhook: Option<String>,
+
+    #[arg(long, value_name = "URL", requires = "webhook")]
+    /// Sends this link to the chart to the --webhook instead of its contents, for charts uploaded elsewhere
+    webhook_chart_url: Option<String>,
+
+    #[arg(long)]
+    /// Appends a Markdown table of the latest figures of each series and the paths of the chart and its exports to $GITHUB_STEP_SUMMARY, for runs in GitHub Actions
+    github_summary: bool,
+
+    #[arg(long)]
+    /// Embeds the input export into SVG charts, so that the chart alone is enough to re-render it or check its numbers later. Pass the chart as the input to read it back
+    embed_data: bool,
+
+    #[arg(long)]
+    /// Writes a .manifest.json file next to the chart recording the inputs, options and data used to render it
+    manifest: bool,
+
+    #[arg(long)]
+    /// Prints how long each stage of parsing, transforming and rendering took once the chart is written
+    timings: bool,
+
+    #[cfg(feature = "preview")]
+    #[arg(long)]
+    /// Shows the chart in a window and re-renders it as flags are entered at a prompt. The chart is written once the window is closed
+    preview: bool,
+
+    #[arg(skip)]
+    /// Set when replaying a manifest, so that its alerts are not sent to the notifiers again
+    replaying: bool,
 }
 
-fn main() -> ExitCode {
-    let cli = Cli::parse();
+#[derive(Subcommand)]
+enum Command {
+    /// Regenerates a chart from a manifest written with --manifest
+    Replay {
+        /// The manifest file to replay
+        manifest: PathBuf,
 
-    pretty_env_logger::formatted_builder()
-        .filter_level(cli.verbose.log_level_filter())
-        .init();
+        #[arg(long)]
+        /// Replays the manifest even if its input files have changed
+        ignore_changes: bool,
+    },
+
+    /// Watches a directory for new Roblox Analytics exports and plots each one as it appears
+    WatchDir(Box<WatchOptions>),
+
+    /// Browses one or more exports interactively in the terminal
+    Tui(Box<TuiOptions>),
+
+    /// Hosts a local web page showing the charts listed in the config file, re-rendering them as their inputs change
+    Serve(ServeOptions),
 
-    let analytics = parse_analytics_file(&cli.in_file);
+    /// Summarizes the last week of one or more exports against the week before it
+    Digest(DigestOptions),
+
+    /// Draws one or more exports as a grid of cards with their latest value, change and a sparkline
+    Scorecard(ScorecardOptions),
+
+    /// Draws several experiences' exports of the same KPI as a grid of small charts, to compare them at a glance
+    Portfolio(PortfolioOptions),
+
+    /// Reports the days on which two exports of the same KPI disagree, such as when Roblox revises its figures. Exits with 6 if they do
+    Diff(DiffOptions),
+}
 
-    if let Err(e) = analytics {
+/// Renders a chart from the given options
+fn render(cli: &Cli, args: Vec<String>) -> ExitCode {
+    let (Some(in_file), Some(out_file)) = (&cli.in_file, &cli.out_file) else {
+        unreachable!("The input and output files are required when no subcommand is given");
+    };
+
+    #[cfg(feature = "preview")]
+    if cli.preview {
+        return match preview::preview(cli, args) {
+            Ok(args) => {
+                let args: Vec<String> = args.into_iter().filter(|arg| arg != "--preview").collect();
+                let cli =
+                    Cli::parse_from(std::iter::once("rasorite".to_string()).chain(args.clone()));
+                render(&cli, args)
+            }
+            Err(e) => {
+                error!("{}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let config = match load_config(cli.plot.config.as_deref()) {
+        Ok(config) => config,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut plot = cli.plot.clone();
+    let profile = match plot.apply_profile(&config) {
+        Ok(profile) => profile,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let style = plot.resolve_style(&config);
+    let out_file = &profile.map_or_else(|| out_file.clone(), |profile| profile.out_path(out_file));
+
+    if let Err(e) = check_output(out_file) {
         error!("{}", e);
         return ExitCode::FAILURE;
     }
 
-    if let Err(e) = plot_data(analytics.unwrap(), &cli) {
+    if !cli.force {
+        let mut outputs = vec![out_file.clone()];
+        outputs.extend(
+            cli.export
+                .iter()
+                .map(|format| export_path(out_file, *format)),
+        );
+        outputs.extend(cli.manifest.then(|| manifest_path(out_file)));
+        outputs.extend(cli.report.clone());
+
+        if let Err(e) = check_overwrite(outputs.iter().map(PathBuf::as_path)) {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if cli.timings {
+        timings::enable();
+    }
+
+    let mut analytics = match timings::time("parse", || {
+        parse_localized_analytics_file(in_file, plot.number_locale.as_ref())
+    }) {
+        Ok(analytics) => analytics,
+        Err(e) => {
+            error!("{}", e);
+            return Exit::Parse.into();
+        }
+    };
+
+    if let (Some(derivation), Some(with)) = (cli.derive, &cli.with) {
+        let other = match timings::time("parse", || {
+            parse_localized_analytics_file(with, plot.number_locale.as_ref())
+        }) {
+            Ok(other) => other,
+            Err(e) => {
+                error!("{}", e);
+                return Exit::Parse.into();
+            }
+        };
+        let derived = timings::time("derive", || {
+            derive(derivation, analytics, other, plot.allow_mismatch)
+        });
+        analytics = match derived {
+            Ok(derived) => derived,
+            Err(e) => {
+                error!("{}", e);
+                return Exit::Parse.into();
+            }
+        };
+    }
+
+    if let (Some(profile), Some(name)) = (profile, &plot.profile) {
+        if let Err(e) = profile.check(name, &analytics) {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+        if !profile.includes_kpi(&analytics) {
+            warning::emit(Warning::ProfileKpi(
+                analytics.kpi_type.clone(),
+                name.clone(),
+            ));
+        }
+    }
+
+    // Convert up front so that the exports, reports and syncs agree with the chart
+    if let Some(unit) = plot.unit {
+        if analytics.playtime_unit.is_none() {
+            warning::emit(Warning::UnitIgnored);
+        } else if let Err(e) = analytics.convert_playtime(unit) {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    analytics.exclude_dates(&plot.exclude_dates);
+    let holidays = match holidays_for(&plot.holidays, &analytics) {
+        Ok(holidays) => holidays,
+        Err(e) => {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let summary = cli.manifest.then(|| DataSummary::new(&analytics));
+
+    if cli.embed_data {
+        let contents = match read_analytics_file(in_file) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("{}", e);
+                return Exit::Parse.into();
+            }
+        };
+        // A chart read back as input still names the export it was made from
+        let name = in_file
+            .with_extension("csv")
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        plot.embedded_data = Some((name, contents));
+    }
+
+    if let Err(e) = plot_data(analytics.clone(), out_file, &plot, &style) {
         error!("{}", e);
-        return ExitCode::FAILURE;
+        return Exit::Plot.into();
     };
 
+    // The chart is written by now, so anything that fails from here on without failing the run
+    // adds its own bit to the exit code instead
+    let mut written = Written::default();
+
+    // Checked straight away, so that the alerts are among the warnings in the report, manifest,
+    // webhook and step summary
+    let alerts = check_rules(&config.rules, &analytics).unwrap_or_else(|e| {
+        warning::emit(Warning::AlertCheckFailed(e.to_string()));
+        written.fail(Convenience::Notify);
+        Vec::new()
+    });
+    emit_alerts(&alerts);
+
+    let mut exports = Vec::new();
+    for format in &cli.export {
+        let file = export_path(out_file, *format);
+        let exported = timings::time("export", || {
+            export_data(&analytics, *format, &file, out_file, &holidays)
+        });
+        if let Err(e) = exported {
+            error!("{}", e);
+            return Exit::Plot.into();
+        }
+        exports.push(file);
+    }
+
+    if let (Some(template), Some(report)) = (&cli.template, &cli.report) {
+        let context = ReportContext::new(
+            &analytics,
+            out_file,
+            exports.clone(),
+            &holidays,
+            warning::collected(),
+        );
+        if let Err(e) = render_report(template, report, &context) {
+            error!("{}", e);
+            return match e {
+                ReportError::UnwritableReport(_) => Exit::Plot.into(),
+                _ => ExitCode::FAILURE,
+            };
+        }
+    }
+
+    if let Some(summary) = summary {
+        let config_file = config_path(cli.plot.config.as_deref());
+        let mut inputs = vec![in_file.as_path()];
+        inputs.extend(cli.with.as_deref());
+        inputs.extend(config_file.as_deref());
+
+        let file = manifest_path(out_file);
+        if let Err(e) = Manifest::new(args, &inputs, summary, &warning::collected())
+            .and_then(|manifest| manifest.save(&file))
+        {
+            error!("{}", e);
+            return Exit::Plot.into();
+        }
+
+        info!("Wrote manifest to {}", file.display());
+    }
+
+    // The chart and its files are written by now, so anything done after is only a convenience:
+    // its failure is warned about and adds its own bit to the exit code
+
+    if cli.publish_sheets {
+        if let Err(e) = sheets::publish(&analytics, config.sheets.as_ref()) {
+            warning::emit(Warning::PublishFailed("Google Sheets", e.to_string()));
+            written.fail(Convenience::Publish);
+        }
+    }
+
+    if cli.sync_notion {
+        if let Err(e) = notion::sync(&analytics, config.notion.as_ref()) {
+            warning::emit(Warning::PublishFailed("Notion", e.to_string()));
+            written.fail(Convenience::Publish);
+        }
+    }
+
+    if let Some(url) = &cli.webhook {
+        if let Err(e) = post_summary(
+            url,
+            &analytics,
+            out_file,
+            cli.webhook_chart_url.as_deref(),
+            &warning::collected(),
+        ) {
+            warning::emit(Warning::PublishFailed("the webhook", e.to_string()));
+            written.fail(Convenience::Publish);
+        }
+    }
+
+    if cli.github_summary {
+        let summary = render_summary(&analytics, out_file, &exports, &warning::collected());
+        if let Err(e) = write_github_summary(&summary) {
+            warning::emit(Warning::PublishFailed(
+                "the GitHub step summary",
+                e.to_string(),
+            ));
+            written.fail(Convenience::Publish);
+        }
+    }
+
+    if !alerts.is_empty() && !cli.replaying {
+        for (notifier, e) in notify(&alerts, out_file, &config.notify) {
+            warning::emit(Warning::NotifyFailed(notifier, e.to_string()));
+            written.fail(Convenience::Notify);
+        }
+        if config.notify.exit_code {
+            written.alert();
+        }
+    }
+
+    if cli.timings {
+        eprint!("{}", timings::report());
+    }
+
     if !cli.silent {
-        if let Err(e) = opener::open(cli.out_file) {
+        if let Err(e) = opener::open(out_file) {
+            warning::emit(Warning::OpenFailed(e.to_string()));
+            written.fail(Convenience::Open);
+        };
+    }
+
+    written.into()
+}
+
+fn replay(manifest_file: &Path, ignore_changes: bool) -> ExitCode {
+    let manifest = match Manifest::load(manifest_file) {
+        Ok(manifest) => manifest,
+        Err(e) => {
             error!("{}", e);
             return ExitCode::FAILURE;
-        };
+        }
+    };
+
+    if let Err(e) = manifest.verify_inputs() {
+        if ignore_changes {
+            warn!("{}", e);
+        } else {
+            error!("{}", e);
+            return ExitCode::FAILURE;
+        }
     }
 
-    ExitCode::SUCCESS
+    let mut cli = match Cli::try_parse_from(
+        std::iter::once("rasorite".to_string()).chain(manifest.args.iter().cloned()),
+    ) {
+        Ok(cli) => cli,
+        Err(e) => {
+            error!(
+                "The manifest's arguments are not valid for this version of Rasorite: {}",
+                e
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    // The manifest being replayed is left untouched, and the chart it describes is regenerated
+    cli.manifest = false;
+    cli.force = true;
+    // Only the chart and its files are regenerated: the run was already published, summarized and
+    // notified about when it was recorded, and there may be no one around to look at the chart
+    cli.publish_sheets = false;
+    cli.sync_notion = false;
+    cli.webhook = None;
+    cli.webhook_chart_url = None;
+    cli.github_summary = false;
+    cli.silent = true;
+    cli.replaying = true;
+
+    if let Err(e) = std::env::set_current_dir(&manifest.working_directory) {
+        error!(
+            "Unable to enter the manifest's working directory {}: {}",
+            manifest.working_directory.display(),
+            e
+        );
+        return ExitCode::FAILURE;
+    }
+
+    info!(
+        "Replaying {} from {}",
+        manifest_file.display(),
+        manifest.generated
+    );
+
+    render(&cli, manifest.args)
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    // Credentials are scrubbed from logs and panics, which are often pasted into bug reports
+    let logger = pretty_env_logger::formatted_builder()
+        .filter_level(cli.verbose.log_level_filter())
+        .build();
+    log::set_max_level(logger.filter());
+    log::set_boxed_logger(Box::new(RedactingLogger(logger))).expect("Failed to set up logging!");
+    redact_panics();
+
+    match &cli.command {
+        Some(Command::Replay {
+            manifest,
+            ignore_changes,
+        }) => warning::collect(|| replay(manifest, *ignore_changes)).0,
+        Some(Command::WatchDir(options)) => match options.watch() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Some(Command::Tui(options)) => match options.run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Some(Command::Serve(options)) => match options.serve() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", e);
+                ExitCode::FAILURE
+            }
+        },
+        Some(Command::Digest(options)) => match options.run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", e);
+                match e {
+                    DigestError::Parse(_) => Exit::Parse.into(),
+                    DigestError::UnwritableFile(_) => Exit::Plot.into(),
+                    _ => ExitCode::FAILURE,
+                }
+            }
+        },
+        Some(Command::Scorecard(options)) => match options.run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", e);
+                match e {
+                    ScorecardError::Parse(_) => Exit::Parse.into(),
+                    ScorecardError::UnwritableFile(_) => Exit::Plot.into(),
+                    _ => ExitCode::FAILURE,
+                }
+            }
+        },
+        Some(Command::Portfolio(options)) => match options.run() {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                error!("{}", e);
+                match e {
+                    PortfolioError::Parse(_) | PortfolioError::Mismatch(..) => Exit::Parse.into(),
+                    PortfolioError::UnwritableFile(_) => Exit::Plot.into(),
+                    _ => ExitCode::FAILURE,
+                }
+            }
+        },
+        Some(Command::Diff(options)) => match options.run() {
+            Ok(false) => ExitCode::SUCCESS,
+            Ok(true) => Exit::Alert.into(),
+            Err(e) => {
+                error!("{}", e);
+                match e {
+                    DiffError::Parse(_) | DiffError::Mismatch(_) => Exit::Parse.into(),
+                    DiffError::InvalidTolerance => ExitCode::FAILURE,
+                }
+            }
+        },
+        None => warning::collect(|| render(&cli, std::env::args().skip(1).collect())).0,
+    }
 }
@@ -0,0 +1,191 @@
+use crate::data::DataPoint;
+use crate::output::write_atomically;
+use crate::parse::AnalyticsData;
+use crate::redact::redact;
+use crate::warning::{Warning, WarningRecord};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// A record of everything needed to regenerate a chart: the exact command line, the working
+/// directory it was run from, and hashes of every input file
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Manifest {
+    pub version: String,
+    pub generated: DateTime<Utc>,
+    pub working_directory: PathBuf,
+    /// The command line arguments the chart was rendered with, excluding the program name and any
+    /// credentials
+    pub args: Vec<String>,
+    pub inputs: Vec<InputFile>,
+    pub data: DataSummary,
+    /// Everything warned about while the chart was rendered
+    #[serde(default)]
+    pub warnings: Vec<WarningRecord>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct InputFile {
+    pub path: PathBuf,
+    pub sha256: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DataSummary {
+    pub universe_id: u64,
+    pub kpi: String,
+    pub series: Vec<SeriesSummary>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SeriesSummary {
+    pub name: String,
+    pub points: usize,
+    pub first_date: DateTime<Utc>,
+    pub last_date: DateTime<Utc>,
+    pub min: f64,
+    pub max: f64,
+}
+
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("The input file \"{0}\" could not be read!")]
+    UnreadableInput(PathBuf),
+
+    #[error("The manifest \"{0}\" could not be written!")]
+    UnwritableManifest(PathBuf),
+
+    #[error("The manifest \"{0}\" could not be read!")]
+    UnreadableManifest(PathBuf),
+
+    #[error("The manifest \"{0}\" is invalid: {1}")]
+    InvalidManifest(PathBuf, serde_json::Error),
+
+    #[error("The input file \"{0}\" has changed since the chart was rendered!")]
+    InputChanged(PathBuf),
+}
+
+fn hash_file(file: &Path) -> Result<String, ManifestError> {
+    let contents =
+        fs::read(file).map_err(|_| ManifestError::UnreadableInput(file.to_path_buf()))?;
+
+    Ok(Sha256::digest(contents)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect())
+}
+
+impl DataSummary {
+    pub fn new(data: &AnalyticsData) -> Self {
+        let mut series: Vec<SeriesSummary> = data
+            .data
+            .iter()
+            .filter(|(_, points)| !points.is_empty())
+            .map(|(name, points)| {
+                let values = points
+                    .iter()
+                    .map(|(_, point)| <DataPoint as Into<f64>>::into(*point));
+                SeriesSummary {
+                    name: name.to_string(),
+                    points: points.len(),
+                    first_date: points.iter().map(|(date, _)| *date).min().unwrap(),
+                    last_date: points.iter().map(|(date, _)| *date).max().unwrap(),
+                    min: values.clone().fold(f64::INFINITY, f64::min),
+                    max: values.fold(f64::NEG_INFINITY, f64::max),
+                }
+            })
+            .collect();
+        series.sort_by(|a, b| a.name.cmp(&b.name));
+
+        DataSummary {
+            universe_id: data.universe_id,
+            kpi: data.kpi_type.to_string(),
+            series,
+        }
+    }
+}
+
+/// Redacts the arguments the way logs are, and leaves out the --webhook URL whatever it looks like,
+/// as anyone holding it can post to it. Replays never post to the webhook, so nothing is lost.
+fn redact_args(args: Vec<String>) -> Vec<String> {
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut webhook = false;
+    for arg in args {
+        redacted.push(if webhook {
+            "[REDACTED]".to_string()
+        } else if arg.starts_with("--webhook=") {
+            "--webhook=[REDACTED]".to_string()
+        } else {
+            redact(&arg).into_owned()
+        });
+        webhook = arg == "--webhook";
+    }
+    redacted
+}
+
+impl Manifest {
+    pub fn new(
+        args: Vec<String>,
+        inputs: &[&Path],
+        data: DataSummary,
+        warnings: &[Warning],
+    ) -> Result<Self, ManifestError> {
+        Ok(Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            generated: Utc::now(),
+            working_directory: std::env::current_dir().unwrap_or_default(),
+            args: redact_args(args),
+            inputs: inputs
+                .iter()
+                .map(|path| {
+                    Ok(InputFile {
+                        path: path.to_path_buf(),
+                        sha256: hash_file(path)?,
+                    })
+                })
+                .collect::<Result<_, ManifestError>>()?,
+            data,
+            warnings: warnings.iter().map(WarningRecord::from).collect(),
+        })
+    }
+
+    pub fn load(file: &Path) -> Result<Self, ManifestError> {
+        let contents = fs::read_to_string(file)
+            .map_err(|_| ManifestError::UnreadableManifest(file.to_path_buf()))?;
+
+        serde_json::from_str(&contents)
+            .map_err(|e| ManifestError::InvalidManifest(file.to_path_buf(), e))
+    }
+
+    pub fn save(&self, file: &Path) -> Result<(), ManifestError> {
+        let contents = serde_json::to_string_pretty(self).expect("Failed to serialize manifest!");
+
+        let unwritable = || ManifestError::UnwritableManifest(file.to_path_buf());
+        write_atomically(
+            file,
+            |temporary| fs::write(temporary, contents).map_err(|_| unwritable()),
+            unwritable,
+        )
+    }
+
+    /// Checks that every input file still has the contents it had when the chart was rendered.
+    /// Relative paths are resolved against the manifest's working directory.
+    pub fn verify_inputs(&self) -> Result<(), ManifestError> {
+        for input in &self.inputs {
+            let path = self.working_directory.join(&input.path);
+            if hash_file(&path)? != input.sha256 {
+                return Err(ManifestError::InputChanged(input.path.clone()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The manifest path for a chart, e.g. `plot.manifest.json` for `plot.png`
+pub fn manifest_path(out_file: &Path) -> PathBuf {
+    out_file.with_extension("manifest.json")
+}
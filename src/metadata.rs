@@ -0,0 +1,298 @@
+use base64::prelude::{Engine, BASE64_STANDARD};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Machine-readable information embedded into rendered charts so that they remain
+/// self-describing once separated from the data they were made from
+#[derive(Clone, Debug)]
+pub struct ChartMetadata {
+    pub universe_id: u64,
+    pub kpi: String,
+    pub date_range: (DateTime<Utc>, DateTime<Utc>),
+    /// The transformations applied to the data before plotting, in order
+    pub transforms: Vec<String>,
+    /// When the chart was rendered, left out of deterministic renders
+    pub generated: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Error)]
+pub enum MetadataError {
+    #[error("Unable to read back the rendered chart to embed metadata!")]
+    UnreadableOutput,
+
+    #[error("Unable to write metadata into the rendered chart!")]
+    UnwritableOutput,
+
+    #[error("The rendered chart is not a valid PNG file!")]
+    InvalidPng,
+}
+
+impl ChartMetadata {
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let mut entries = vec![
+            ("universe_id", self.universe_id.to_string()),
+            ("kpi", self.kpi.clone()),
+            (
+                "date_start",
+                self.date_range.0.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+            (
+                "date_end",
+                self.date_range.1.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ),
+            ("transforms", self.transforms.join(",")),
+            ("version", env!("CARGO_PKG_VERSION").to_string()),
+        ];
+        if let Some(generated) = self.generated {
+            entries.push((
+                "generated",
+                generated.to_rfc3339_opts(SecondsFormat::Secs, true),
+            ));
+        }
+
+        entries
+    }
+}
+
+pub fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn embed_svg_metadata(contents: &str, metadata: &ChartMetadata) -> Option<String> {
+    let svg_start = contents.find("<svg")?;
+    let open_tag_end = svg_start + contents[svg_start..].find('>')? + 1;
+
+    let mut element = String::from(
+        "\n<metadata>\n<rasorite:chart xmlns:rasorite=\"https://github.com/chemiclast/rasorite\">\n",
+    );
+    for (key, value) in metadata.entries() {
+        element.push_str(&format!(
+            "<rasorite:{key}>{}</rasorite:{key}>\n",
+            escape_xml(&value)
+        ));
+    }
+    element.push_str("</rasorite:chart>\n</metadata>");
+
+    let mut result = contents.to_string();
+    result.insert_str(open_tag_end, &element);
+    Some(result)
+}
+
+fn unescape_xml(value: &str) -> String {
+    value
+        .replace("&quot;", "\"")
+        .replace("&gt;", ">")
+        .replace("&lt;", "<")
+        .replace("&amp;", "&")
+}
+
+fn read_svg_metadata(contents: &str) -> Option<HashMap<String, String>> {
+    let chart_start = contents.find("<rasorite:chart")?;
+    let chart_end = chart_start + contents[chart_start..].find("</rasorite:chart>")?;
+    let mut rest = &contents[chart_start..chart_end];
+    // Skips the opening tag of the chart element itself
+    rest = &rest[rest.find('>')? + 1..];
+
+    let mut entries = HashMap::new();
+    while let Some(start) = rest.find("<rasorite:") {
+        rest = &rest[start + "<rasorite:".len()..];
+        let key_end = rest.find('>')?;
+        let key = &rest[..key_end];
+        let closing = format!("</rasorite:{}>", key);
+        let value_end = rest.find(&closing)?;
+        entries.insert(key.to_string(), unescape_xml(&rest[key_end + 1..value_end]));
+        rest = &rest[value_end + closing.len()..];
+    }
+
+    Some(entries)
+}
+
+fn embed_svg_data(contents: &str, name: &str, data: &[u8]) -> Option<String> {
+    let svg_start = contents.find("<svg")?;
+    let open_tag_end = svg_start + contents[svg_start..].find('>')? + 1;
+
+    let element = format!(
+        "\n<metadata>\n<rasorite:data xmlns:rasorite=\"https://github.com/chemiclast/rasorite\" encoding=\"base64\" filename=\"{}\">\n{}\n</rasorite:data>\n</metadata>",
+        escape_xml(name),
+        BASE64_STANDARD.encode(data)
+    );
+
+    let mut result = contents.to_string();
+    result.insert_str(open_tag_end, &element);
+    Some(result)
+}
+
+/// Takes the export embedded into an SVG chart with `--embed-data` back out of it
+pub fn extract_svg_data(contents: &str) -> Option<Vec<u8>> {
+    let element_start = contents.find("<rasorite:data")?;
+    let data_start = element_start + contents[element_start..].find('>')? + 1;
+    let data_end = data_start + contents[data_start..].find("</rasorite:data>")?;
+    let encoded: String = contents[data_start..data_end]
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect();
+
+    BASE64_STANDARD.decode(encoded).ok()
+}
+
+fn png_chunk(kind: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut chunk = Vec::with_capacity(data.len() + 12);
+    chunk.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    chunk.extend_from_slice(kind);
+    chunk.extend_from_slice(data);
+
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+    chunk.extend_from_slice(&hasher.finalize().to_be_bytes());
+
+    chunk
+}
+
+/// Builds a tEXt chunk, or an iTXt chunk if the value cannot be represented in Latin-1
+fn png_text_chunk(key: &str, value: &str) -> Vec<u8> {
+    let key = format!("rasorite:{}", key);
+
+    if value.chars().all(|c| (c as u32) < 256) {
+        let mut data: Vec<u8> = key.bytes().collect();
+        data.push(0);
+        data.extend(value.chars().map(|c| c as u8));
+        png_chunk(b"tEXt", &data)
+    } else {
+        // keyword, null, no compression, no language tag, no translated keyword, UTF-8 text
+        let mut data: Vec<u8> = key.bytes().collect();
+        data.extend_from_slice(&[0, 0, 0, 0, 0]);
+        data.extend_from_slice(value.as_bytes());
+        png_chunk(b"iTXt", &data)
+    }
+}
+
+fn embed_png_metadata(contents: &[u8], metadata: &ChartMetadata) -> Option<Vec<u8>> {
+    if !contents.starts_with(&PNG_SIGNATURE) || contents.get(12..16)? != b"IHDR" {
+        return None;
+    }
+
+    // The IHDR chunk must come first, so the text chunks are inserted straight after it
+    let ihdr_length = u32::from_be_bytes(contents.get(8..12)?.try_into().ok()?) as usize;
+    let ihdr_end = 8 + 12 + ihdr_length;
+    if contents.len() < ihdr_end {
+        return None;
+    }
+
+    let mut result = contents[..ihdr_end].to_vec();
+    for (key, value) in metadata.entries() {
+        result.extend(png_text_chunk(key, &value));
+    }
+    result.extend_from_slice(&contents[ihdr_end..]);
+    Some(result)
+}
+
+fn read_png_metadata(contents: &[u8]) -> Option<HashMap<String, String>> {
+    if !contents.starts_with(&PNG_SIGNATURE) {
+        return None;
+    }
+
+    let mut entries = HashMap::new();
+    let mut offset = PNG_SIGNATURE.len();
+    while let (Some(length), Some(kind)) = (
+        contents.get(offset..offset + 4),
+        contents.get(offset + 4..offset + 8),
+    ) {
+        let length = u32::from_be_bytes(length.try_into().ok()?) as usize;
+        let data = contents.get(offset + 8..offset + 8 + length)?;
+        offset += length + 12;
+
+        let (keyword, value) = match kind {
+            b"tEXt" => {
+                let split = data.iter().position(|&byte| byte == 0)?;
+                // tEXt values are Latin-1, whose code points match the first 256 of Unicode
+                (
+                    &data[..split],
+                    data[split + 1..].iter().map(|&byte| byte as char).collect(),
+                )
+            }
+            // Only the uncompressed iTXt chunks written by embed_png_metadata are understood
+            b"iTXt" => {
+                let split = data.iter().position(|&byte| byte == 0)?;
+                let text = data.get(split + 5..)?;
+                (&data[..split], String::from_utf8_lossy(text).into_owned())
+            }
+            b"IDAT" | b"IEND" => break,
+            _ => continue,
+        };
+        if let Some(key) = keyword.strip_prefix(b"rasorite:") {
+            entries.insert(String::from_utf8_lossy(key).into_owned(), value);
+        }
+    }
+
+    Some(entries)
+}
+
+/// Reads back the metadata embedded into the chart at the given path, keyed by the names in
+/// [`ChartMetadata::entries`]. Returns nothing if the file cannot be read or holds no metadata.
+pub fn read_metadata(file: &Path) -> Option<HashMap<String, String>> {
+    let entries = match file
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase())
+        .as_deref()
+    {
+        Some("svg") => read_svg_metadata(&fs::read_to_string(file).ok()?)?,
+        Some("png") => read_png_metadata(&fs::read(file).ok()?)?,
+        _ => return None,
+    };
+
+    (!entries.is_empty()).then_some(entries)
+}
+
+/// Embeds the metadata into the chart at the given path. Returns false if the file format does not
+/// support metadata.
+pub fn embed_metadata(file: &Path, metadata: &ChartMetadata) -> Result<bool, MetadataError> {
+    match file
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase())
+        .as_deref()
+    {
+        Some("svg") => {
+            let contents = fs::read_to_string(file).map_err(|_| MetadataError::UnreadableOutput)?;
+            let contents =
+                embed_svg_metadata(&contents, metadata).ok_or(MetadataError::UnreadableOutput)?;
+            fs::write(file, contents).map_err(|_| MetadataError::UnwritableOutput)?;
+            Ok(true)
+        }
+        Some("png") => {
+            let contents = fs::read(file).map_err(|_| MetadataError::UnreadableOutput)?;
+            let contents =
+                embed_png_metadata(&contents, metadata).ok_or(MetadataError::InvalidPng)?;
+            fs::write(file, contents).map_err(|_| MetadataError::UnwritableOutput)?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+/// Embeds the export a chart was made from into the chart at the given path, under the given file
+/// name. Returns false if the file format cannot hold it.
+pub fn embed_data(file: &Path, name: &str, data: &[u8]) -> Result<bool, MetadataError> {
+    if !file
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+    {
+        return Ok(false);
+    }
+
+    let contents = fs::read_to_string(file).map_err(|_| MetadataError::UnreadableOutput)?;
+    let contents = embed_svg_data(&contents, name, data).ok_or(MetadataError::UnreadableOutput)?;
+    fs::write(file, contents).map_err(|_| MetadataError::UnwritableOutput)?;
+    Ok(true)
+}
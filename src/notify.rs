@@ -0,0 +1,258 @@
+use crate::alert::Alert;
+use crate::config::NotifyConfig;
+use crate::data::DataPointArithmeticError;
+use crate::parse::AnalyticsData;
+use crate::stats::LatestStats;
+use crate::warning::{self, Warning};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use log::info;
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+/// Separates the parts of the multipart body the chart is uploaded to Discord in
+const BOUNDARY: &str = "rasorite-alert-boundary";
+
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    #[error("The chart \"{0}\" could not be read to attach it!")]
+    UnreadableChart(String),
+
+    #[error(transparent)]
+    Arithmetic(#[from] DataPointArithmeticError),
+
+    #[error("The request failed: {0}")]
+    Request(String),
+}
+
+impl From<ureq::Error> for NotifyError {
+    fn from(value: ureq::Error) -> Self {
+        match value {
+            ureq::Error::Status(code, response) => NotifyError::Request(format!(
+                "{} {}",
+                code,
+                response.into_string().unwrap_or_default()
+            )),
+            // The URL is left out, as a webhook's URL is all it takes to post to it
+            ureq::Error::Transport(transport) => NotifyError::Request(match transport.message() {
+                Some(message) => format!("{}: {}", transport.kind(), message),
+                None => transport.kind().to_string(),
+            }),
+        }
+    }
+}
+
+/// The alerts as lines of chat text, headed by the experience they are for
+fn text(alerts: &[Alert]) -> String {
+    alerts
+        .iter()
+        .map(|alert| {
+            format!(
+                "⚠️ {} for Experience ID {}: {}",
+                alert.rule, alert.universe_id, alert.message
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn content_type(chart: &Path) -> &'static str {
+    match chart.extension().and_then(|extension| extension.to_str()) {
+        Some("svg") => "image/svg+xml",
+        Some("jpg" | "jpeg") => "image/jpeg",
+        Some("bmp") => "image/bmp",
+        _ => "image/png",
+    }
+}
+
+/// Posts the alerts to a Discord webhook, with the chart attached
+fn discord(url: &str, alerts: &[Alert], chart: &Path, contents: &[u8]) -> Result<(), NotifyError> {
+    let name = chart
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "chart.png".to_string());
+    let payload = json!({ "content": text(alerts) });
+
+    let mut body = format!(
+        "--{0}\r\nContent-Disposition: form-data; name=\"payload_json\"\r\nContent-Type: application/json\r\n\r\n{1}\r\n--{0}\r\nContent-Disposition: form-data; name=\"files[0]\"; filename=\"{2}\"\r\nContent-Type: {3}\r\n\r\n",
+        BOUNDARY,
+        payload,
+        name.replace('"', ""),
+        content_type(chart)
+    )
+    .into_bytes();
+    body.extend_from_slice(contents);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", BOUNDARY).as_bytes());
+
+    ureq::post(url)
+        .set(
+            "Content-Type",
+            &format!("multipart/form-data; boundary={}", BOUNDARY),
+        )
+        .send_bytes(&body)?;
+    Ok(())
+}
+
+/// Posts the alerts to a Slack incoming webhook, naming the chart as it cannot be attached
+fn slack(url: &str, alerts: &[Alert], chart: &Path) -> Result<(), NotifyError> {
+    ureq::post(url).send_json(json!({
+        "text": format!("{}\nChart: {}", text(alerts), chart.display()),
+    }))?;
+    Ok(())
+}
+
+/// The chart as sent to webhooks: its path, and either a link to it or its contents in base64
+#[derive(Serialize)]
+struct ChartPayload {
+    path: String,
+    content_type: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    base64: Option<String>,
+}
+
+impl ChartPayload {
+    fn new(chart: &Path, contents: &[u8]) -> Self {
+        ChartPayload {
+            path: chart.display().to_string(),
+            content_type: content_type(chart),
+            url: None,
+            base64: Some(STANDARD.encode(contents)),
+        }
+    }
+
+    fn linked(chart: &Path, url: &str) -> Self {
+        ChartPayload {
+            path: chart.display().to_string(),
+            content_type: content_type(chart),
+            url: Some(url.to_string()),
+            base64: None,
+        }
+    }
+}
+
+/// Posts the alerts and the chart, encoded in base64, to a webhook as JSON
+fn webhook(url: &str, alerts: &[Alert], chart: &Path, contents: &[u8]) -> Result<(), NotifyError> {
+    ureq::post(url).send_json(json!({
+        "alerts": alerts,
+        "chart": ChartPayload::new(chart, contents),
+    }))?;
+    Ok(())
+}
+
+/// What `--webhook` is posted once a chart is written
+#[derive(Serialize)]
+struct RunPayload<'a> {
+    universe_id: u64,
+    kpi: String,
+    /// The first and last days of the export
+    date_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// The latest figures of the export's "Total" series
+    latest: Option<LatestStats>,
+    /// Everything warned about while the chart was made, each with a `kind` and a `message`
+    warnings: &'a [Warning],
+    chart: ChartPayload,
+}
+
+/// Posts a summary of a run to a webhook as JSON: the experience, KPI and latest figures of the
+/// export, the warnings raised and the chart, linked to at `chart_url` if given or else encoded in
+/// base64
+pub fn post_summary(
+    url: &str,
+    data: &AnalyticsData,
+    chart: &Path,
+    chart_url: Option<&str>,
+    warnings: &[Warning],
+) -> Result<(), NotifyError> {
+    let chart = match chart_url {
+        Some(chart_url) => ChartPayload::linked(chart, chart_url),
+        None => {
+            let contents = fs::read(chart)
+                .map_err(|_| NotifyError::UnreadableChart(chart.display().to_string()))?;
+            ChartPayload::new(chart, &contents)
+        }
+    };
+
+    // The total is summed for exports viewed by a breakdown, as it is when they are charted
+    let mut totals = data.clone();
+    totals.add_computed_total()?;
+    let latest = totals
+        .data
+        .iter()
+        .filter(|(name, _)| name.starts_with("Total"))
+        .min_by(|(a, _), (b, _)| a.cmp(b))
+        .and_then(|(_, points)| LatestStats::new(points));
+    let dates = data.data.values().flatten().map(|(date, _)| *date);
+
+    info!("Posting the run to the webhook...");
+    ureq::post(url).send_json(RunPayload {
+        universe_id: data.universe_id,
+        kpi: data.kpi_type.to_string(),
+        date_range: dates.clone().min().zip(dates.max()),
+        latest,
+        warnings,
+        chart,
+    })?;
+    Ok(())
+}
+
+/// Warns about each alert that fired, so that it is logged and collected with the chart's other
+/// warnings
+pub fn emit_alerts(alerts: &[Alert]) {
+    for alert in alerts {
+        warning::emit(Warning::AlertFired(
+            alert.rule.clone(),
+            alert.universe_id,
+            alert.message.clone(),
+        ));
+    }
+}
+
+/// Sends the alerts to every notifier configured, returning the name and error of each that
+/// failed, so that one failing does not keep the others from being told
+pub fn notify(
+    alerts: &[Alert],
+    chart: &Path,
+    config: &NotifyConfig,
+) -> Vec<(&'static str, NotifyError)> {
+    if alerts.is_empty() {
+        return Vec::new();
+    }
+
+    // Read once for every notifier that attaches it
+    let contents = fs::read(chart).ok();
+    let attachment = || {
+        contents
+            .as_deref()
+            .ok_or_else(|| NotifyError::UnreadableChart(chart.display().to_string()))
+    };
+
+    let mut failures = Vec::new();
+    if let Some(url) = &config.discord {
+        failures.extend(send("Discord", || {
+            discord(url, alerts, chart, attachment()?)
+        }));
+    }
+    if let Some(url) = &config.slack {
+        failures.extend(send("Slack", || slack(url, alerts, chart)));
+    }
+    if let Some(url) = &config.webhook {
+        failures.extend(send("the webhook", || {
+            webhook(url, alerts, chart, attachment()?)
+        }));
+    }
+    failures
+}
+
+fn send(
+    name: &'static str,
+    request: impl FnOnce() -> Result<(), NotifyError>,
+) -> Option<(&'static str, NotifyError)> {
+    info!("Sending alerts to {}...", name);
+    request().err().map(|e| (name, e))
+}
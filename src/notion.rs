@@ -0,0 +1,219 @@
+use crate::config::NotionConfig;
+use crate::parse::AnalyticsData;
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::thread;
+use std::time::Duration;
+use thiserror::Error;
+
+const NOTION_API: &str = "https://api.notion.com/v1";
+const NOTION_VERSION: &str = "2022-06-28";
+
+/// Notion allows an average of three requests per second per integration
+const REQUEST_INTERVAL: Duration = Duration::from_millis(350);
+
+/// How many times a request is sent before giving up on being rate limited
+const MAX_ATTEMPTS: u32 = 5;
+
+/// The longest wait between attempts, however long Notion asks to wait
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Error)]
+pub enum NotionError {
+    #[error("Syncing to Notion requires a [notion] section in the config file!")]
+    MissingConfig,
+
+    #[error("The request to Notion failed: {0}")]
+    Request(String),
+
+    #[error("Notion is still rate limiting requests after {0} attempts! Try syncing again later.")]
+    RateLimited(u32),
+}
+
+impl From<ureq::Error> for NotionError {
+    fn from(value: ureq::Error) -> Self {
+        match value {
+            // Notion explains what went wrong in the body of error responses
+            ureq::Error::Status(code, response) => NotionError::Request(format!(
+                "{} {}",
+                code,
+                response.into_string().unwrap_or_default()
+            )),
+            // The URL is left out, as it names the database being synced
+            ureq::Error::Transport(transport) => NotionError::Request(match transport.message() {
+                Some(message) => format!("{}: {}", transport.kind(), message),
+                None => transport.kind().to_string(),
+            }),
+        }
+    }
+}
+
+impl From<std::io::Error> for NotionError {
+    fn from(value: std::io::Error) -> Self {
+        NotionError::Request(value.to_string())
+    }
+}
+
+struct Client<'a> {
+    config: &'a NotionConfig,
+}
+
+impl Client<'_> {
+    /// Sends a request, waiting and retrying when rate limited, up to [`MAX_ATTEMPTS`] times
+    fn send(&self, method: &str, path: &str, body: Value) -> Result<Value, NotionError> {
+        for attempt in 1..=MAX_ATTEMPTS {
+            let result = ureq::request(method, &format!("{}{}", NOTION_API, path))
+                .set("Authorization", &format!("Bearer {}", self.config.token))
+                .set("Notion-Version", NOTION_VERSION)
+                .send_json(&body);
+
+            match result {
+                Ok(response) => return Ok(response.into_json()?),
+                Err(ureq::Error::Status(429, _)) if attempt == MAX_ATTEMPTS => break,
+                Err(ureq::Error::Status(429, response)) => {
+                    let wait = response
+                        .header("Retry-After")
+                        .and_then(|value| value.parse().ok())
+                        .map_or(Duration::from_secs(1), Duration::from_secs)
+                        .min(MAX_RETRY_WAIT);
+                    warn!(
+                        "Rate limited by Notion, retrying in {} seconds...",
+                        wait.as_secs()
+                    );
+                    thread::sleep(wait);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Err(NotionError::RateLimited(MAX_ATTEMPTS))
+    }
+
+    /// Finds the pages already in the database for the export's experience and KPI, keyed by their
+    /// title, along with their current values
+    fn existing_rows(
+        &self,
+        data: &AnalyticsData,
+    ) -> Result<HashMap<String, (String, Option<f64>)>, NotionError> {
+        let mut rows = HashMap::new();
+        let mut cursor: Option<String> = None;
+
+        loop {
+            let mut query = json!({
+                "filter": {
+                    "and": [
+                        { "property": "Experience ID", "number": { "equals": data.universe_id } },
+                        { "property": "KPI", "select": { "equals": data.kpi_type.to_string() } },
+                    ]
+                },
+                "page_size": 100,
+            });
+            if let Some(cursor) = &cursor {
+                query["start_cursor"] = json!(cursor);
+            }
+
+            let response = self.send(
+                "POST",
+                &format!("/databases/{}/query", self.config.database_id),
+                query,
+            )?;
+
+            for page in response["results"].as_array().into_iter().flatten() {
+                let properties = &page["properties"];
+                let Some(key) = properties["Key"]["title"][0]["plain_text"].as_str() else {
+                    continue;
+                };
+                let Some(id) = page["id"].as_str() else {
+                    continue;
+                };
+                rows.insert(
+                    key.to_string(),
+                    (id.to_string(), properties["Value"]["number"].as_f64()),
+                );
+            }
+
+            match response["next_cursor"].as_str() {
+                Some(next) if response["has_more"].as_bool() == Some(true) => {
+                    cursor = Some(next.to_string())
+                }
+                _ => break,
+            }
+            thread::sleep(REQUEST_INTERVAL);
+        }
+
+        Ok(rows)
+    }
+}
+
+/// Upserts a row for every point of every series into the configured Notion database. Rows are
+/// identified by their Key title, so syncing the same export twice only updates changed values.
+pub fn sync(data: &AnalyticsData, config: Option<&NotionConfig>) -> Result<(), NotionError> {
+    let config = config.ok_or(NotionError::MissingConfig)?;
+    let client = Client { config };
+
+    info!("Reading existing rows from Notion...");
+
+    let existing = client.existing_rows(data)?;
+
+    let (mut created, mut updated, mut unchanged) = (0, 0, 0);
+    let mut series: Vec<_> = data.data.iter().collect();
+    series.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (name, points) in series {
+        for (date, point) in points {
+            let value: f64 = (*point).into();
+            let key = format!(
+                "{}:{}:{}:{}",
+                data.universe_id,
+                data.kpi_type,
+                name,
+                date.format("%F")
+            );
+
+            let properties = json!({
+                "Key": { "title": [{ "text": { "content": key } }] },
+                "Date": { "date": { "start": date.format("%F").to_string() } },
+                "Experience ID": { "number": data.universe_id },
+                "KPI": { "select": { "name": data.kpi_type.to_string() } },
+                "Series": { "rich_text": [{ "text": { "content": &**name } }] },
+                "Value": { "number": value },
+            });
+
+            match existing.get(&key) {
+                Some((_, Some(current))) if *current == value => {
+                    unchanged += 1;
+                    continue;
+                }
+                Some((id, _)) => {
+                    client.send(
+                        "PATCH",
+                        &format!("/pages/{}", id),
+                        json!({ "properties": properties }),
+                    )?;
+                    updated += 1;
+                }
+                None => {
+                    client.send(
+                        "POST",
+                        "/pages",
+                        json!({
+                            "parent": { "database_id": config.database_id },
+                            "properties": properties,
+                        }),
+                    )?;
+                    created += 1;
+                }
+            }
+
+            thread::sleep(REQUEST_INTERVAL);
+        }
+    }
+
+    info!(
+        "Synced to Notion: {} created, {} updated, {} unchanged",
+        created, updated, unchanged
+    );
+
+    Ok(())
+}
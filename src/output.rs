@@ -0,0 +1,85 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The file extensions charts can be rendered to
+pub const SUPPORTED_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "bmp", "svg"];
+
+#[derive(Debug, Error)]
+pub enum OutputError {
+    #[error("The output file has no extension! Use one of: {}.", SUPPORTED_EXTENSIONS.join(", "))]
+    MissingExtension,
+
+    #[error("Charts cannot be rendered as \"{0}\" files! Use one of: {}.", SUPPORTED_EXTENSIONS.join(", "))]
+    UnsupportedFormat(String),
+
+    #[error("The file \"{0}\" already exists! Pass --force to overwrite it.")]
+    Exists(PathBuf),
+}
+
+/// Checks that charts can be rendered to the given file's format, returning whether it is a vector
+/// format. Done before rendering so that an unusable path fails fast rather than after all the
+/// work of drawing the chart.
+pub fn check_output(out_file: &Path) -> Result<bool, OutputError> {
+    let extension = out_file
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .ok_or(OutputError::MissingExtension)?;
+
+    if !SUPPORTED_EXTENSIONS.contains(&extension.as_str()) {
+        return Err(OutputError::UnsupportedFormat(extension));
+    }
+
+    Ok(extension == "svg")
+}
+
+/// Refuses to go on if any of the files a run would write already exists, so that nothing is
+/// overwritten unless --force is passed
+pub fn check_overwrite<'a>(files: impl IntoIterator<Item = &'a Path>) -> Result<(), OutputError> {
+    match files.into_iter().find(|file| file.exists()) {
+        Some(existing) => Err(OutputError::Exists(existing.to_path_buf())),
+        None => Ok(()),
+    }
+}
+
+/// A hidden file next to the given one to write to before renaming it into place. The extension is
+/// kept, as some writers choose their format from it.
+fn temporary_path(file: &Path) -> PathBuf {
+    let stem = file
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name = match file.extension() {
+        Some(extension) => format!(
+            ".{}.{}.{}",
+            stem,
+            std::process::id(),
+            extension.to_string_lossy()
+        ),
+        None => format!(".{}.{}", stem, std::process::id()),
+    };
+
+    file.with_file_name(name)
+}
+
+/// Writes a file by having `write` create it at a temporary path, then renaming it over the real
+/// one, so that a crash or error part way through never leaves a truncated file behind for other
+/// tools to pick up
+pub fn write_atomically<T, E>(
+    file: &Path,
+    write: impl FnOnce(&Path) -> Result<T, E>,
+    rename_error: impl FnOnce() -> E,
+) -> Result<T, E> {
+    let temporary = temporary_path(file);
+
+    let result = write(&temporary).and_then(|value| {
+        fs::rename(&temporary, file)
+            .map(|_| value)
+            .map_err(|_| rename_error())
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+
+    result
+}
@@ -1,19 +1,268 @@
-use crate::data::DataPoint;
+use crate::benchmark::BenchmarkSeries;
 use crate::data::KpiType;
-use chrono::{DateTime, NaiveDateTime, Utc};
+use crate::data::{DataPoint, DataPointArithmeticError, DateRange, PlaytimeUnit};
+use crate::locale::Locale;
+use crate::metadata::extract_svg_data;
+use crate::warning::{self, Warning};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use csv::{StringRecord, StringRecordsIntoIter};
 use log::info;
-use std::collections::HashMap;
-use std::fs::File;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
+use strum::Display;
 use thiserror::Error;
 
-#[derive(Debug)]
+/// How far apart the dates of an export are
+#[derive(Display, Clone, Copy, PartialEq, Eq, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub enum Granularity {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+/// Why two exports should not be combined, such as when overlaying or deriving one from another
+#[derive(Debug, Error)]
+pub enum MismatchError {
+    #[error("The exports are of different experiences, {0} and {1}! Pass --allow-mismatch to combine them anyway.")]
+    DifferentExperiences(u64, u64),
+
+    #[error(
+        "The exports have {0} and {1} data points! Pass --allow-mismatch to combine them anyway."
+    )]
+    DifferentGranularities(Granularity, Granularity),
+
+    #[error(
+        "The exports are of different KPIs, {0} and {1}! Pass --mix-kpis to combine them anyway."
+    )]
+    DifferentKpis(KpiType, KpiType),
+}
+
+impl MismatchError {
+    /// What is raised in place of the error when mismatches are allowed
+    fn warning(&self) -> Warning {
+        match self {
+            MismatchError::DifferentExperiences(universe_id, other) => {
+                Warning::DifferentExperiences(*universe_id, *other)
+            }
+            MismatchError::DifferentGranularities(granularity, other) => {
+                Warning::DifferentGranularities(*granularity, *other)
+            }
+            MismatchError::DifferentKpis(kpi, other) => {
+                Warning::DifferentKpis(kpi.clone(), other.clone())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct AnalyticsData {
     pub kpi_type: KpiType,
     pub universe_id: u64,
-    pub data: HashMap<String, Vec<(DateTime<Utc>, DataPoint)>>,
+    /// Each series by name. Names are shared rather than owned, as breakdown-heavy exports have
+    /// many series whose names are handed around between parsing, transforms and plotting.
+    pub data: HashMap<Arc<str>, Vec<(DateTime<Utc>, DataPoint)>>,
+    /// The unit the values are in, for playtime exports
+    pub playtime_unit: Option<PlaytimeUnit>,
+}
+
+impl AnalyticsData {
+    /// A filename-friendly identifier for the export, e.g. `123456-daily-active-users`
+    pub fn slug(&self) -> String {
+        let kpi = self
+            .kpi_type
+            .to_string()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-");
+
+        format!("{}-{}", self.universe_id, kpi)
+    }
+
+    /// The benchmark series in the export, ordered by name
+    pub fn benchmarks(&self) -> Vec<BenchmarkSeries> {
+        let mut benchmarks: Vec<BenchmarkSeries> = self
+            .data
+            .keys()
+            .filter_map(|key| BenchmarkSeries::parse(key))
+            .collect();
+        benchmarks.sort_by(|a, b| a.key.cmp(&b.key));
+        benchmarks
+    }
+
+    /// The names of the breakdown series in the export, such as platforms or countries, ordered by
+    /// name. These are every series other than the total and the benchmarks.
+    pub fn breakdowns(&self) -> Vec<&str> {
+        let mut breakdowns: Vec<&str> = self
+            .data
+            .keys()
+            .map(|key| &**key)
+            .filter(|key| !key.starts_with("Total") && BenchmarkSeries::parse(key).is_none())
+            .collect();
+        breakdowns.sort();
+        breakdowns
+    }
+
+    /// How far apart the export's dates are, judged by the most common gap between consecutive
+    /// dates. Exports with fewer than two dates have no granularity.
+    pub fn granularity(&self) -> Option<Granularity> {
+        let dates: BTreeSet<NaiveDate> = self
+            .data
+            .values()
+            .flatten()
+            .map(|(date, _)| date.date_naive())
+            .collect();
+        let mut gaps: HashMap<i64, usize> = HashMap::new();
+        for (earlier, later) in dates.iter().zip(dates.iter().skip(1)) {
+            *gaps.entry((*later - *earlier).num_days()).or_default() += 1;
+        }
+
+        // Ties go to the shorter gap, so that a few missing days do not make an export weekly
+        let (gap, _) = gaps
+            .into_iter()
+            .max_by(|(a_gap, a_count), (b_gap, b_count)| {
+                a_count.cmp(b_count).then_with(|| b_gap.cmp(a_gap))
+            })?;
+        Some(match gap {
+            ..=1 => Granularity::Daily,
+            2..=10 => Granularity::Weekly,
+            _ => Granularity::Monthly,
+        })
+    }
+
+    /// Checks that another export can be combined with this one: that it has the same granularity
+    /// and, unless it is meant to be of another experience, the same universe ID. With
+    /// `allow_mismatch`, a mismatch is only warned about.
+    pub fn check_combinable(
+        &self,
+        other: &AnalyticsData,
+        same_experience: bool,
+        allow_mismatch: bool,
+    ) -> Result<(), MismatchError> {
+        let mut mismatches = Vec::new();
+        if same_experience && self.universe_id != other.universe_id {
+            mismatches.push(MismatchError::DifferentExperiences(
+                self.universe_id,
+                other.universe_id,
+            ));
+        }
+        if let (Some(granularity), Some(other)) = (self.granularity(), other.granularity()) {
+            if granularity != other {
+                mismatches.push(MismatchError::DifferentGranularities(granularity, other));
+            }
+        }
+
+        for mismatch in mismatches {
+            if !allow_mismatch {
+                return Err(mismatch);
+            }
+            warning::emit(mismatch.warning());
+        }
+        Ok(())
+    }
+
+    /// Checks that another export being compared with this one is of the same KPI, so that
+    /// sessions are not compared with daily active users by accident. With `force`, a mismatch is
+    /// only warned about.
+    pub fn check_same_kpi(&self, other: &AnalyticsData, force: bool) -> Result<(), MismatchError> {
+        if self.kpi_type == other.kpi_type {
+            return Ok(());
+        }
+
+        let mismatch = MismatchError::DifferentKpis(self.kpi_type.clone(), other.kpi_type.clone());
+        if !force {
+            return Err(mismatch);
+        }
+        warning::emit(mismatch.warning());
+        Ok(())
+    }
+
+    /// Adds a "Total" series summing the breakdown series on each date, for exports viewed by a
+    /// breakdown that come without one. Exports that already have a total or have no breakdowns
+    /// are left as they are. Returns whether a total was added.
+    pub fn add_computed_total(&mut self) -> Result<bool, DataPointArithmeticError> {
+        let breakdowns = self.breakdowns();
+        if breakdowns.is_empty() || self.data.keys().any(|key| key.starts_with("Total")) {
+            return Ok(false);
+        }
+
+        let mut totals: BTreeMap<DateTime<Utc>, DataPoint> = BTreeMap::new();
+        for (date, point) in breakdowns.iter().flat_map(|key| &self.data[*key]) {
+            let total = totals.entry(*date).or_insert(DataPoint::Zero);
+            // Series of whole numbers and of decimals can appear in the same export
+            *total = match total.checked_add(*point) {
+                Err(DataPointArithmeticError::MismatchedTypes) => {
+                    DataPoint::checked_from_f64(f64::from(*total) + f64::from(*point))?
+                }
+                result => result?,
+            };
+        }
+
+        warning::emit(Warning::ComputedTotal(breakdowns.len()));
+        self.data
+            .insert("Total".into(), totals.into_iter().collect());
+        Ok(true)
+    }
+
+    /// Removes every point on a day within the given ranges, such as an outage or a bot raid, from
+    /// every series. Returns the number of points removed.
+    pub fn exclude_dates(&mut self, ranges: &[DateRange]) -> usize {
+        let mut removed = 0;
+        for points in self.data.values_mut() {
+            let count = points.len();
+            points.retain(|(date, _)| !ranges.iter().any(|range| range.contains(date)));
+            removed += count - points.len();
+        }
+        self.data.retain(|_, points| !points.is_empty());
+
+        if removed > 0 {
+            info!("Excluded {} points within the excluded dates", removed);
+        }
+        removed
+    }
+
+    /// Converts playtime values into the given unit. Exports of other KPIs are left as they are.
+    /// Each series stays whole numbers if every converted value is whole, and otherwise becomes
+    /// decimals throughout.
+    pub fn convert_playtime(&mut self, unit: PlaytimeUnit) -> Result<(), DataPointArithmeticError> {
+        let Some(current) = self.playtime_unit else {
+            return Ok(());
+        };
+        if current == unit {
+            return Ok(());
+        }
+
+        let factor = unit.per_hour() / current.per_hour();
+        for points in self.data.values_mut() {
+            let values: Vec<f64> = points
+                .iter()
+                .map(|(_, point)| f64::from(*point) * factor)
+                .collect();
+            let whole = values
+                .iter()
+                .all(|value| value.fract() == 0.0 && (0.0..u64::MAX as f64).contains(value));
+
+            for ((_, point), value) in points.iter_mut().zip(values) {
+                *point = if value == 0.0 {
+                    DataPoint::Zero
+                } else if whole {
+                    DataPoint::Integer(value as u64)
+                } else {
+                    DataPoint::checked_from_f64(value)?
+                };
+            }
+        }
+
+        info!("Converted playtime from {} to {}", current, unit);
+        self.playtime_unit = Some(unit);
+        Ok(())
+    }
 }
 
 #[derive(Debug, Error)]
@@ -33,11 +282,16 @@ pub enum AnalyticsParseError {
     #[error("The provided file does not have a valid Experience ID line!")]
     InvalidHeader,
 
+    #[error("The chart \"{0}\" has no data embedded in it! Render it with --embed-data to embed its data.")]
+    NoEmbeddedData(PathBuf),
+
     #[error("Unable to determine KPI type! Make sure the header line for the data is present and correct!")]
     MissingKpiType,
 }
 
-fn get_universe_id(records: &mut StringRecordsIntoIter<File>) -> Result<u64, AnalyticsParseError> {
+fn get_universe_id<R: Read>(
+    records: &mut StringRecordsIntoIter<R>,
+) -> Result<u64, AnalyticsParseError> {
     let Some(Ok(first_line)) = records.next() else {
         return Err(AnalyticsParseError::EmptyFile);
     };
@@ -57,7 +311,9 @@ fn get_universe_id(records: &mut StringRecordsIntoIter<File>) -> Result<u64, Ana
 }
 
 /// Must be called after the first line (Experience ID) has been consumed
-fn get_kpi_type(records: &mut StringRecordsIntoIter<File>) -> Result<KpiType, AnalyticsParseError> {
+fn get_kpi_type<R: Read>(
+    records: &mut StringRecordsIntoIter<R>,
+) -> Result<KpiType, AnalyticsParseError> {
     let Some(Ok(first_line)) = records.next() else {
         return Err(AnalyticsParseError::MissingKpiType);
     };
@@ -75,14 +331,17 @@ fn get_kpi_type(records: &mut StringRecordsIntoIter<File>) -> Result<KpiType, An
         })
 }
 
-fn parse_record(
-    record: StringRecord,
-) -> Result<(String, (DateTime<Utc>, DataPoint)), AnalyticsParseError> {
+fn parse_record<'a>(
+    record: &'a StringRecord,
+    number_locale: Option<&Locale>,
+) -> Result<(&'a str, (DateTime<Utc>, DataPoint)), AnalyticsParseError> {
+    let value = record.get(2).ok_or(AnalyticsParseError::UnreadableFile)?;
+    let value = match number_locale {
+        Some(locale) => DataPoint::parse_localized(value, locale.decimal_separator()),
+        None => value.parse(),
+    };
     Ok((
-        record
-            .get(0)
-            .ok_or(AnalyticsParseError::UnreadableFile)?
-            .to_string(),
+        record.get(0).ok_or(AnalyticsParseError::UnreadableFile)?,
         (
             NaiveDateTime::parse_from_str(
                 record.get(1).ok_or(AnalyticsParseError::UnreadableFile)?,
@@ -90,25 +349,72 @@ fn parse_record(
             )
             .map_err(|_| AnalyticsParseError::UnreadableFile)?
             .and_utc(),
-            record
-                .get(2)
-                .ok_or(AnalyticsParseError::UnreadableFile)?
-                .parse()
-                .map_err(|_| AnalyticsParseError::UnreadableFile)?,
+            value.map_err(|_| AnalyticsParseError::UnreadableFile)?,
         ),
     ))
 }
 
-pub fn parse_analytics_file(file: &PathBuf) -> Result<AnalyticsData, AnalyticsParseError> {
-    let Ok(reader) = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .flexible(true)
-        .from_path(file)
-    else {
+pub fn parse_analytics_file(file: &Path) -> Result<AnalyticsData, AnalyticsParseError> {
+    parse_localized_analytics_file(file, None)
+}
+
+/// Parses the export at the given path, reading its values as written in the given locale, such
+/// as an export that has been through a spreadsheet and come out as "1.234,5"
+pub fn parse_localized_analytics_file(
+    file: &Path,
+    number_locale: Option<&Locale>,
+) -> Result<AnalyticsData, AnalyticsParseError> {
+    if is_svg(file) {
+        return parse_localized_analytics(read_analytics_file(file)?.as_slice(), number_locale);
+    }
+
+    // Streamed rather than read into memory first, as merged multi-year histories can run to
+    // hundreds of megabytes
+    let Ok(contents) = File::open(file) else {
+        return Err(AnalyticsParseError::UnreadableFile);
+    };
+    parse_localized_analytics(contents, number_locale)
+}
+
+fn is_svg(file: &Path) -> bool {
+    file.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("svg"))
+}
+
+/// Reads the export at the given path. SVG charts rendered with `--embed-data` are read as the
+/// export embedded in them, so that a chart can be re-rendered from itself.
+pub fn read_analytics_file(file: &Path) -> Result<Vec<u8>, AnalyticsParseError> {
+    let Ok(contents) = fs::read(file) else {
         return Err(AnalyticsParseError::UnreadableFile);
     };
 
-    let mut records = reader.into_records();
+    if is_svg(file) {
+        info!("Reading the data embedded in {}...", file.display());
+        return std::str::from_utf8(&contents)
+            .ok()
+            .and_then(extract_svg_data)
+            .ok_or_else(|| AnalyticsParseError::NoEmbeddedData(file.to_path_buf()));
+    }
+
+    Ok(contents)
+}
+
+/// Parses an export from any reader, such as the contents of a file dropped onto a web page
+pub fn parse_analytics<R: Read>(reader: R) -> Result<AnalyticsData, AnalyticsParseError> {
+    parse_localized_analytics(reader, None)
+}
+
+/// Parses an export from any reader, reading its values as written in the given locale. Without a
+/// locale, values must be written as the Creator Dashboard writes them.
+pub fn parse_localized_analytics<R: Read>(
+    reader: R,
+    number_locale: Option<&Locale>,
+) -> Result<AnalyticsData, AnalyticsParseError> {
+    let mut records = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader)
+        .into_records();
 
     info!("Finding Experience ID...");
 
@@ -122,18 +428,30 @@ pub fn parse_analytics_file(file: &PathBuf) -> Result<AnalyticsData, AnalyticsPa
 
     info!("Found KPI type {}", kpi_type);
 
-    let mut data: HashMap<String, Vec<(DateTime<Utc>, DataPoint)>> = HashMap::new();
+    let mut data: HashMap<Arc<str>, Vec<(DateTime<Utc>, DataPoint)>> = HashMap::new();
 
     info!("Collecting data records...");
 
-    for record in records {
-        let Ok(record) = record else { continue };
-        let result = parse_record(record);
-        if let Ok((name, result)) = result {
-            if let std::collections::hash_map::Entry::Vacant(e) = data.entry(name.clone()) {
-                e.insert(vec![result]);
-            } else {
-                data.get_mut(&name).unwrap().push(result);
+    // A single record is read into over and over, and each series name is only allocated the
+    // first time it is seen, so that large exports parse without an allocation per record
+    let reader = records.reader_mut();
+    let mut record = StringRecord::new();
+    loop {
+        match reader.read_record(&mut record) {
+            Ok(true) => {}
+            Ok(false) => break,
+            Err(e) if matches!(e.kind(), csv::ErrorKind::Io(_)) => {
+                return Err(AnalyticsParseError::UnreadableFile)
+            }
+            Err(_) => continue,
+        }
+        let Ok((name, point)) = parse_record(&record, number_locale) else {
+            continue;
+        };
+        match data.get_mut(name) {
+            Some(points) => points.push(point),
+            None => {
+                data.insert(name.into(), vec![point]);
             }
         }
     }
@@ -150,7 +468,45 @@ pub fn parse_analytics_file(file: &PathBuf) -> Result<AnalyticsData, AnalyticsPa
 
     Ok(AnalyticsData {
         universe_id,
+        playtime_unit: matches!(kpi_type, KpiType::TotalPlayTimeHours)
+            .then_some(PlaytimeUnit::Hours),
         kpi_type,
         data,
     })
 }
+
+/// Checks whether a file looks like a Roblox Analytics export by its header lines, without parsing
+/// the data records
+pub fn is_analytics_export(file: &Path) -> bool {
+    let Ok(file) = File::open(file) else {
+        return false;
+    };
+
+    has_analytics_header(file)
+}
+
+/// Checks whether a reader's contents start with the header lines of a Roblox Analytics export
+pub fn has_analytics_header<R: Read>(reader: R) -> bool {
+    let mut records = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(reader)
+        .into_records();
+
+    get_universe_id(&mut records).is_ok() && get_kpi_type(&mut records).is_ok()
+}
+
+/// Reads the Experience ID and KPI of an export from its header lines, without parsing the data
+/// records
+pub fn read_export_header(file: &Path) -> Option<(u64, KpiType)> {
+    let mut records = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(File::open(file).ok()?)
+        .into_records();
+
+    Some((
+        get_universe_id(&mut records).ok()?,
+        get_kpi_type(&mut records).ok()?,
+    ))
+}
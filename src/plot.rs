@@ -1,22 +1,57 @@
-use crate::data::{get_data_range, DataPoint};
-use crate::parse::AnalyticsData;
-use crate::Cli;
-use chrono::{DateTime, Utc};
-use log::{info, warn};
+use crate::benchmark::BenchmarkSeries;
+use crate::changepoint::find_changepoints;
+use crate::config::{Config, ConfigError, ProfileConfig};
+use crate::data::{
+    format_number, get_data_range_multi, AxisValues, DataPoint, DataPointArithmeticError,
+    DateRange, KpiType, Padding, PlaytimeUnit,
+};
+use crate::digest::{format_percent, KpiDigest};
+use crate::holiday::{holidays_for, HolidayError, HolidaySource};
+use crate::locale::{Lang, Locale, Localizer};
+use crate::metadata::{embed_data, embed_metadata, escape_xml, ChartMetadata, MetadataError};
+use crate::output::{check_output, write_atomically, OutputError};
+use crate::parse::{
+    parse_localized_analytics_file, AnalyticsData, AnalyticsParseError, MismatchError,
+};
+use crate::stats::LatestStats;
+use crate::style::{
+    GridStyle, LineStyle, MarkerShape, Opacity, Palette, Preset, SeriesPattern, SeriesRule,
+    SeriesStyle, Style, StyleOverrides, Theme, GREYSCALE, OKABE_ITO,
+};
+use crate::timings;
+use crate::transform::{Pipeline, Script, TransformError};
+use crate::warning::{self, Warning};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, TimeZone, Utc};
+use clap::{Args, Parser, ValueEnum};
+use log::info;
 use plotters::backend::{BitMapBackend, DrawingBackend};
-use plotters::chart::{ChartBuilder, LabelAreaPosition};
-use plotters::drawing::IntoDrawingArea;
+use plotters::chart::{ChartBuilder, ChartContext, LabelAreaPosition, SeriesLabelPosition};
+use plotters::coord::cartesian::Cartesian2d;
+use plotters::coord::ranged1d::{DefaultFormatting, KeyPointHint, Ranged};
+use plotters::coord::types::RangedDateTime;
+use plotters::coord::Shift;
+use plotters::drawing::{DrawingArea, DrawingAreaErrorKind, IntoDrawingArea};
+use plotters::element::{
+    Circle, Cross, EmptyElement, PathElement, Pie, Polygon, Rectangle, Text, TriangleMarker,
+};
 use plotters::series::LineSeries;
-use plotters::style::full_palette::{GREY, LIGHTBLUE, ORANGE};
-use plotters::style::FontFamily::SansSerif;
-use plotters::style::{Color, FontStyle, IntoFont, BLACK, WHITE};
+use plotters::style::full_palette::{
+    AMBER_400, BLUEGREY_300, BLUE_600, BROWN_400, GREEN_500, GREEN_700, GREY, GREY_400, GREY_700,
+    INDIGO_900, LIGHTBLUE, ORANGE, PURPLE_500, RED_400, RED_700, TEAL_400, TEAL_700,
+};
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, FontStyle, IntoFont, RGBColor, ShapeStyle, TextStyle, TRANSPARENT};
 use plotters_backend::{
-    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingErrorKind,
+    BackendColor, BackendCoord, BackendStyle, BackendTextStyle, DrawingErrorKind, FontTransform,
 };
 use plotters_svg::SVGBackend;
 use std::error::Error;
+use std::f64::consts::{FRAC_PI_2, TAU};
 use std::fmt::Display;
-use std::ops::Mul;
+use std::ops::{Mul, Range};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
 use thiserror::Error;
 
 enum DrawingBackendVariant<'a> {
@@ -252,192 +287,2606 @@ impl<'a> From<BitMapBackend<'a>> for DrawingBackendVariant<'a> {
     }
 }
 
+/// A horizontal target line drawn across the chart, parsed from `<value>[:label]`
+#[derive(Clone, Debug)]
+pub struct Goal {
+    pub value: f64,
+    pub label: Option<String>,
+}
+
 #[derive(Debug, Error)]
-pub enum PlottingError {
-    #[error("The analytics data series is missing!")]
-    SeriesMissing,
+pub enum GoalParseError {
+    #[error("The goal value \"{0}\" is not a number!")]
+    InvalidValue(String),
+}
 
-    #[error("The provided output file path is invalid!")]
-    InvalidOutput,
+impl FromStr for Goal {
+    type Err = GoalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (value, label) = match s.split_once(':') {
+            Some((value, label)) => (value, Some(label.trim().to_string())),
+            None => (s, None),
+        };
+
+        Ok(Goal {
+            value: value
+                .trim()
+                .parse()
+                .map_err(|_| GoalParseError::InvalidValue(value.to_string()))?,
+            label: label.filter(|label| !label.is_empty()),
+        })
+    }
 }
 
-pub fn plot_data(data: AnalyticsData, opts: &Cli) -> Result<(), PlottingError> {
-    let Cli {
-        normalize,
-        out_file,
-        ..
-    } = opts;
+/// A value applied to every series whose name matches a pattern, parsed from `[pattern=]value`
+#[derive(Clone, Debug)]
+pub struct SeriesValue<T> {
+    /// Matches every series when omitted
+    pub pattern: Option<SeriesPattern>,
+    pub value: T,
+}
 
-    info!("Finding data series...");
+#[derive(Debug, Error)]
+pub enum SeriesValueParseError {
+    #[error("The value \"{0}\" is invalid: {1}")]
+    InvalidValue(String, String),
 
-    let data_series = data
-        .data
-        .clone()
-        .into_iter()
-        .find(|(key, _)| key.starts_with("Total"))
-        .ok_or(PlottingError::SeriesMissing)?;
-    let bench_series = data
-        .data
-        .clone()
-        .into_iter()
-        .find(|(key, _)| key.starts_with("Benchmark"));
+    #[error("The series pattern \"{0}\" is invalid: {1}")]
+    InvalidPattern(String, regex::Error),
+}
 
-    if bench_series.is_some() {
-        info!("Found analytics and benchmark series!");
-    } else if *normalize {
-        warn!("Failed to find benchmark series! Make sure you are exporting the analytics data with benchmarks. The \"View by\" option must be set to \"None\" in your analytics dashboard for benchmarks to appear.")
+impl<T: FromStr> FromStr for SeriesValue<T>
+where
+    T::Err: Display,
+{
+    type Err = SeriesValueParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Patterns may contain "=" themselves, but values never do
+        let (pattern, value) = match s.rsplit_once('=') {
+            Some((pattern, value)) => (Some(pattern), value),
+            None => (None, s),
+        };
+
+        Ok(SeriesValue {
+            pattern: pattern
+                .map(|pattern| {
+                    SeriesPattern::try_from(pattern.to_string())
+                        .map_err(|e| SeriesValueParseError::InvalidPattern(pattern.to_string(), e))
+                })
+                .transpose()?,
+            value: value.trim().parse().map_err(|e: T::Err| {
+                SeriesValueParseError::InvalidValue(value.to_string(), e.to_string())
+            })?,
+        })
     }
+}
 
-    info!("Initializing chart...");
+/// How the analytics series is shown when it is normalized
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum NormalizeMode {
+    /// Rescales the series to account for fluctuations in the reference series, keeping its units
+    Rescale,
+    /// Plots the series as a percentage of the reference series, with a line marking 100%
+    Index,
+}
 
-    let backend = match &out_file.extension().and_then(|value| value.to_str()) {
-        Some("svg") => DrawingBackendVariant::Vector(SVGBackend::new(&out_file, (1200, 800))),
-        Some(_) => DrawingBackendVariant::Bitmap(BitMapBackend::new(&out_file, (1200, 800))),
-        _ => return Err(PlottingError::InvalidOutput),
-    };
-    let mut drawing_area = backend.into_drawing_area();
+/// How date labels are laid out when they are too wide for the space between them
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum DateLabels {
+    /// Leaves the labels side by side, even if they overlap
+    #[default]
+    Horizontal,
+    /// Turns the labels to read upwards
+    Rotated,
+    /// Alternates the labels between two rows
+    Staggered,
+}
 
-    info!("Chart initialized!");
+/// The kind of chart drawn
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ChartStyle {
+    /// The series over time
+    #[default]
+    Line,
+    /// The share of each breakdown series on a single day
+    Donut,
+}
 
-    drawing_area
-        .fill(&WHITE)
-        .expect("Failed to fill drawing area!");
-    drawing_area = drawing_area
-        .titled(
-            &format!("{} for Experience ID {}", data.kpi_type, data.universe_id),
-            (SansSerif, 50, FontStyle::Bold).into_font().color(&BLACK),
-        )
-        .expect("Failed to draw title!");
+/// Which side of the goal line to shade
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum GoalShade {
+    Above,
+    Below,
+}
 
-    if let Some(bench_series) = &bench_series {
-        drawing_area = if *normalize {
-            drawing_area.titled(
-                &format!("Normalized over series \"{}\"", bench_series.0),
-                (SansSerif, 25f64, FontStyle::Italic)
-                    .into_font()
-                    .color(&GREY),
+/// Draws a title centered at the top of the area like [`DrawingArea::titled`], returning the area
+/// below it. With fixed metrics, the title's height is worked out from its font size instead of
+/// being measured with whichever fonts are installed, which vary from machine to machine.
+fn titled<'a, DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    text: &str,
+    style: TextStyle<'a>,
+    fixed_metrics: bool,
+) -> Result<DrawingArea<DB, Shift>, DrawingAreaErrorKind<DB::ErrorType>> {
+    if !fixed_metrics {
+        return area.titled(text, style);
+    }
+
+    // Glyphs of the default fonts are about four fifths of the font size tall
+    let height = (style.font.get_size() * 0.8).ceil() as i32;
+    let padding = (height / 2).min(5);
+    let (width, _) = area.dim_in_pixel();
+    area.draw_text(
+        text,
+        &style.pos(Pos::new(HPos::Center, VPos::Top)),
+        (width as i32 / 2, padding),
+    )?;
+
+    Ok(area.split_vertically(padding * 2 + height).1)
+}
+
+/// Measures text as it would be drawn, or estimates its size from the font size when fonts are not
+/// to be measured
+fn text_size<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    text: &str,
+    style: &TextStyle,
+    fixed_metrics: bool,
+) -> (u32, u32) {
+    if fixed_metrics {
+        // Glyphs of the default fonts are on average about three fifths of the font size wide
+        let size = style.font.get_size();
+        return (
+            (text.chars().count() as f64 * size * 0.6).ceil() as u32,
+            (size * 0.8).ceil() as u32,
+        );
+    }
+
+    area.estimate_text_size(text, style)
+        .expect("Failed to measure label!")
+}
+
+/// Draws a table of labels and values into the top right corner of the area, or the top left corner
+/// of right-to-left layouts, on a background that keeps it readable over the series behind it
+fn draw_inline_stats<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    rows: &[(String, String)],
+    style: &Style,
+    fixed_metrics: bool,
+    right_to_left: bool,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let theme = style.theme;
+    let label_style = (style.font_family(), style.font_size(16f64))
+        .into_font()
+        .color(&theme.muted());
+    let value_style = (style.font_family(), style.font_size(16f64), FontStyle::Bold)
+        .into_font()
+        .color(&theme.foreground());
+
+    let sizes: Vec<((u32, u32), (u32, u32))> = rows
+        .iter()
+        .map(|(label, value)| {
+            (
+                text_size(area, label, &label_style, fixed_metrics),
+                text_size(area, value, &value_style, fixed_metrics),
             )
-        } else {
-            drawing_area.titled(
-                &format!("Plotted with series \"{}\"", bench_series.0),
-                (SansSerif, 25f64, FontStyle::Italic)
-                    .into_font()
-                    .color(&GREY),
+        })
+        .collect();
+    let label_width = sizes.iter().map(|(label, _)| label.0).max().unwrap_or(0);
+    let value_width = sizes.iter().map(|(_, value)| value.0).max().unwrap_or(0);
+    let row_height = sizes
+        .iter()
+        .map(|(label, value)| label.1.max(value.1))
+        .max()
+        .unwrap_or(0)
+        + INLINE_STATS_PADDING / 2;
+
+    let width = label_width + value_width + INLINE_STATS_PADDING * 4;
+    let height = row_height * rows.len() as u32 + INLINE_STATS_PADDING * 2;
+    let (area_width, _) = area.dim_in_pixel();
+    let left = if right_to_left {
+        INLINE_STATS_PADDING
+    } else {
+        area_width.saturating_sub(width + INLINE_STATS_PADDING)
+    } as i32;
+    let top = INLINE_STATS_PADDING as i32;
+    let corners = [(left, top), (left + width as i32, top + height as i32)];
+    area.draw(&Rectangle::new(
+        corners,
+        theme.background().mix(0.85).filled(),
+    ))?;
+    area.draw(&Rectangle::new(
+        corners,
+        Color::stroke_width(&theme.muted(), 1),
+    ))?;
+
+    for (index, (label, value)) in rows.iter().enumerate() {
+        let y = top + (INLINE_STATS_PADDING + row_height * index as u32 + row_height / 2) as i32;
+        area.draw(&Text::new(
+            label.clone(),
+            (left + INLINE_STATS_PADDING as i32, y),
+            label_style.pos(Pos::new(HPos::Left, VPos::Center)),
+        ))?;
+        area.draw(&Text::new(
+            value.clone(),
+            (left + (width - INLINE_STATS_PADDING) as i32, y),
+            value_style.pos(Pos::new(HPos::Right, VPos::Center)),
+        ))?;
+    }
+    Ok(())
+}
+
+/// Draws a dashed path through the given points. Dashes are measured in pixels so that they look
+/// the same regardless of the scale of the axes.
+fn draw_dashed_path<DB: DrawingBackend, X: Ranged, Y: Ranged>(
+    area: &DrawingArea<DB, Cartesian2d<X, Y>>,
+    points: &[(X::ValueType, Y::ValueType)],
+    style: ShapeStyle,
+    (dash, gap): (f64, f64),
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>> {
+    let base = area.get_base_pixel();
+    let pixels: Vec<(f64, f64)> = points
+        .iter()
+        .map(|point| {
+            let (x, y) = area.map_coordinate(point);
+            ((x - base.0) as f64, (y - base.1) as f64)
+        })
+        .collect();
+    let canvas = area.strip_coord_spec();
+
+    // Distance travelled into the current dash/gap cycle, carried across segments so that the
+    // pattern continues smoothly around corners
+    let mut offset = 0f64;
+    for segment in pixels.windows(2) {
+        let (from, to) = (segment[0], segment[1]);
+        let length = ((to.0 - from.0).powi(2) + (to.1 - from.1).powi(2)).sqrt();
+        if length == 0f64 {
+            continue;
+        }
+
+        let at = |distance: f64| {
+            let t = distance / length;
+            (
+                (from.0 + (to.0 - from.0) * t).round() as i32,
+                (from.1 + (to.1 - from.1) * t).round() as i32,
             )
+        };
+
+        let mut travelled = 0f64;
+        while travelled < length {
+            let in_dash = offset < dash;
+            let remaining = if in_dash {
+                dash - offset
+            } else {
+                dash + gap - offset
+            };
+            let step = remaining.min(length - travelled);
+
+            if in_dash {
+                canvas.draw(&PathElement::new(
+                    vec![at(travelled), at(travelled + step)],
+                    style,
+                ))?;
+            }
+
+            travelled += step;
+            offset = (offset + step) % (dash + gap);
         }
-        .expect("Failed to draw subtitle!")
     }
 
-    let mut chart = ChartBuilder::on(&drawing_area);
-    chart
-        .margin(5)
-        .margin_right(80)
-        .set_label_area_size(LabelAreaPosition::Left, 80)
-        .set_label_area_size(LabelAreaPosition::Bottom, 80);
-
-    let normalized_data = if bench_series.is_some() && *normalize {
-        info!("Normalizing data around benchmark...");
-        Some(normalize_data(
-            data_series.clone().1,
-            bench_series.clone().unwrap().1,
-        ))
-    } else {
-        None
-    };
+    Ok(())
+}
 
-    if normalized_data.is_some() {
-        info!("Data normalized!");
+/// Draws a marker of the given shape and size at each of the points
+fn draw_markers<DB: DrawingBackend, X: Ranged, Y: Ranged>(
+    area: &DrawingArea<DB, Cartesian2d<X, Y>>,
+    points: &[(X::ValueType, Y::ValueType)],
+    marker: MarkerShape,
+    size: u32,
+    style: ShapeStyle,
+) -> Result<(), DrawingAreaErrorKind<DB::ErrorType>>
+where
+    X::ValueType: Clone,
+    Y::ValueType: Clone,
+{
+    let filled = style.filled();
+    let size = size as i32;
+    for point in points.iter().cloned() {
+        match marker {
+            MarkerShape::Circle => area.draw(&Circle::new(point, size, filled))?,
+            MarkerShape::Square => area.draw(
+                &(EmptyElement::at(point) + Rectangle::new([(-size, -size), (size, size)], filled)),
+            )?,
+            MarkerShape::Triangle => area.draw(&TriangleMarker::new(point, size + 1, filled))?,
+            MarkerShape::Diamond => area.draw(
+                &(EmptyElement::at(point)
+                    + Polygon::new(
+                        vec![(0, -size - 1), (size + 1, 0), (0, size + 1), (-size - 1, 0)],
+                        filled,
+                    )),
+            )?,
+            MarkerShape::Cross => area.draw(&Cross::new(point, size + 1, style))?,
+        }
     }
+    Ok(())
+}
 
-    info!("Getting axis ranges...");
+/// Writes a date with its month name when a language is chosen, in numbers in the locale's order
+/// when a locale is, and as an ISO 8601 date otherwise
+fn write_date(date: &DateTime<Utc>, opts: &PlotOptions, localizer: &Localizer) -> String {
+    match (opts.lang, &opts.locale) {
+        (Some(_), _) => localizer.date(date),
+        (None, Some(locale)) => locale.numeric_date(date),
+        (None, None) => date.format("%F").to_string(),
+    }
+}
 
-    let (date_range, data_range) = if let Some(data) = &normalized_data {
-        get_data_range(data)
-    } else {
-        get_data_range(
-            &data
-                .data
-                .into_values()
-                .collect::<Vec<Vec<(DateTime<Utc>, DataPoint)>>>()
-                .into_iter()
-                .flatten()
-                .collect(),
-        )
-    };
+/// The date axis of a line chart, running from right to left in right-to-left layouts
+struct DateAxis {
+    dates: RangedDateTime<DateTime<Utc>>,
+    right_to_left: bool,
+}
 
-    info!("Ranges calculated!");
+impl Ranged for DateAxis {
+    type FormatOption = DefaultFormatting;
+    type ValueType = DateTime<Utc>;
 
-    let mut chart_context = chart
-        .build_cartesian_2d(date_range, data_range)
-        .expect("Failed to construct chart!");
-    chart_context
-        .configure_mesh()
-        .label_style((SansSerif, 18))
-        .x_label_formatter(&|x| x.format("%F").to_string())
-        .y_label_formatter(&|y| <DataPoint as Into<u64>>::into(*y).to_string())
-        .draw()
-        .expect("Failed to draw chart!");
-
-    if let Some(bench_series) = &bench_series {
-        chart.caption(
-            bench_series.0.clone(),
-            (SansSerif, 25, FontStyle::Italic, &GREY),
-        );
+    fn map(&self, value: &Self::ValueType, limit: (i32, i32)) -> i32 {
+        let pixel = self.dates.map(value, limit);
+        if self.right_to_left {
+            limit.0 + limit.1 - pixel
+        } else {
+            pixel
+        }
     }
 
-    if let Some(data) = normalized_data {
-        info!("Drawing normalized data series...");
-        chart_context
-            .draw_series(LineSeries::new(data, Color::stroke_width(&ORANGE, 2)).point_size(0))
-            .expect("Failed to draw data series!");
-    } else if let Some(bench_series) = bench_series {
-        info!("Drawing analytics data series...");
-        chart_context
-            .draw_series(
-                LineSeries::new(data_series.1, Color::stroke_width(&LIGHTBLUE, 2)).point_size(0),
-            )
-            .expect("Failed to draw analytics data series!");
-        info!("Drawing benchmark data series...");
-        chart_context
-            .draw_series(
-                LineSeries::new(bench_series.1, Color::stroke_width(&GREY, 1)).point_size(0),
-            )
-            .expect("Failed to draw benchmark data series!");
-    } else {
-        info!("Drawing analytics data series...");
-        chart_context
-            .draw_series(
-                LineSeries::new(data_series.1, Color::stroke_width(&LIGHTBLUE, 2)).point_size(0),
-            )
-            .expect("Failed to draw analytics data series!");
+    fn key_points<Hint: KeyPointHint>(&self, hint: Hint) -> Vec<Self::ValueType> {
+        self.dates.key_points(hint)
     }
 
-    info!("Data plotted!");
+    fn range(&self) -> Range<Self::ValueType> {
+        self.dates.range()
+    }
+}
 
-    // BitMapBackend will return an error when presenting when the output file extension is invalid
-    drawing_area
-        .present()
-        .map_err(|_| PlottingError::InvalidOutput)?;
+/// How many labels plotters aims to put along each axis by default
+const AXIS_LABELS: usize = 11;
 
-    Ok(())
+/// The gap plotters leaves between an axis and its labels, twice its largest tick mark
+const LABEL_DISTANCE: u32 = 10;
+
+/// The gap between the two rows of staggered date labels
+const STAGGER_GAP: u32 = 4;
+
+/// How close to a point the pointer has to be for its tooltip to show, in pixels
+const TOOLTIP_RADIUS: u32 = 6;
+
+/// The room above and below the footer
+const FOOTER_PADDING: u32 = 4;
+
+/// The room around and between the cells of the --inline-stats table
+const INLINE_STATS_PADDING: u32 = 8;
+
+/// The room on the right of the chart, where value labels are printed
+const VALUE_LABEL_AREA: u32 = 80;
+
+/// Extra room around the axis labels, so that they do not touch the edge of the chart
+const LABEL_MARGIN: u32 = 10;
+
+/// The colors of the slices of a donut chart, largest first
+const DONUT_COLORS: [RGBColor; 8] = [
+    LIGHTBLUE, ORANGE, GREEN_500, PURPLE_500, RED_400, TEAL_400, AMBER_400, BROWN_400,
+];
+
+/// The color of the slice gathering every breakdown series too small for its own
+const DONUT_OTHER_COLOR: RGBColor = GREY_400;
+
+/// The colors of the rolling averages drawn over the analytics series, in the order they are given
+const ROLLING_AVERAGE_COLORS: [RGBColor; 3] = [BLUE_600, INDIGO_900, TEAL_700];
+
+/// The colors of the KPIs combined with the analytics series, in turn, as bold as its own
+const COMBINED_COLORS: [RGBColor; 4] = [BLUE_600, GREEN_700, PURPLE_500, BROWN_400];
+
+/// The muted styles benchmark series are drawn with, in turn, so that they stay distinguishable
+/// from each other without drawing attention away from the analytics series
+const BENCHMARK_STYLES: [(RGBColor, LineStyle); 4] = [
+    (GREY, LineStyle::Solid),
+    (GREY_700, LineStyle::Dashed),
+    (GREY_400, LineStyle::Dotted),
+    (BLUEGREY_300, LineStyle::Dashed),
+];
+
+/// A named series to draw and the style to draw it with
+type Points = Vec<(DateTime<Utc>, DataPoint)>;
+type Line = (Arc<str>, Points, SeriesStyle);
+
+/// The position, text and color of a label for the latest value of each line, in backend pixels.
+/// Labels that would overlap are pushed apart vertically.
+fn value_labels_at<
+    DB: DrawingBackend,
+    X: Ranged<ValueType = DateTime<Utc>>,
+    Y: Ranged<ValueType = DataPoint>,
+>(
+    chart: &ChartContext<DB, Cartesian2d<X, Y>>,
+    lines: &[Line],
+    spacing: i32,
+) -> Vec<((i32, i32), String, RGBColor)> {
+    let mut labels: Vec<_> = lines
+        .iter()
+        .filter_map(|(_, points, series_style)| {
+            let last = points.iter().max_by_key(|(date, _)| *date)?;
+            Some((
+                chart.backend_coord(last),
+                format_number(last.1.into()),
+                series_style.color,
+            ))
+        })
+        .collect();
+    labels.sort_by_key(|((_, y), _, _)| *y);
+
+    for index in 1..labels.len() {
+        let previous = labels[index - 1].0 .1;
+        if labels[index].0 .1 - previous < spacing {
+            labels[index].0 .1 = previous + spacing;
+        }
+    }
+
+    labels
 }
 
-impl Mul<f64> for &DataPoint {
-    type Output = f64;
+/// Options controlling how a chart is plotted, shared by every command that renders charts
+#[derive(Args, Clone, Debug)]
+pub struct PlotOptions {
+    #[arg(long, value_name = "NAME")]
+    /// Plots the series with the given name, such as a single platform or country, instead of the "Total" series
+    pub series: Option<String>,
 
-    fn mul(self, rhs: f64) -> Self::Output {
-        match self {
-            DataPoint::Float(value) => value.to_num::<f64>() * rhs,
-            DataPoint::Integer(value) => *value as f64 * rhs,
-            DataPoint::Zero => 0f64,
+    #[arg(long, value_name = "STEPS")]
+    /// Transforms every series before plotting with comma-separated steps applied in order: fill-gaps, ma:DAYS (moving average), ema:ALPHA (exponential smoothing), resample:week or resample:month, and normalize:zscore or normalize:minmax (e.g. "fill-gaps,ma:7")
+    pub pipeline: Option<Pipeline>,
+
+    #[arg(long, value_name = "FILE")]
+    /// Transforms every series with a Rhai script defining `fn transform(name, points)`, after any --pipeline steps. Each point is a map with a `date` and a `value`, and the points returned are plotted
+    pub script: Option<PathBuf>,
+
+    #[arg(short, long)]
+    /// Plots the analytics series normalized against the benchmark series instead of plotting both the benchmark series and the analytics series
+    pub normalize: bool,
+
+    #[arg(long, value_name = "SERIES")]
+    /// Normalizes against the series with the given name instead of the first benchmark series (e.g. "Benchmark Top 25%"). Implies --normalize
+    pub normalize_against: Option<String>,
+
+    #[arg(long, value_name = "FILE")]
+    /// Normalizes against a series from another Roblox Analytics export: the one named by --normalize-against, or else its first benchmark series or its analytics series. Implies --normalize
+    pub normalize_file: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Warns instead of failing when an export combined with the input, such as with --with, --peer, --normalize-file or --combine, is of another experience or has a different granularity, such as weekly rather than daily points. Peers and --normalize-file exports may always be of another experience
+    pub allow_mismatch: bool,
+
+    #[arg(long)]
+    /// Plots --peer and --normalize-file exports of another KPI than the input, warning instead of failing
+    pub mix_kpis: bool,
+
+    /// The export to embed into SVG charts, as its file name and contents, as set by --embed-data
+    /// on the command line
+    #[arg(skip)]
+    pub embedded_data: Option<(String, Vec<u8>)>,
+
+    #[arg(long, value_name = "FILE")]
+    /// Plots the total of another experience's export of the same KPI alongside the analytics series, as a benchmark for KPIs Roblox has none for, such as Daily Revenue. The first is normalized against when the export has no benchmarks. Can be given multiple times
+    pub peer: Vec<PathBuf>,
+
+    #[arg(long, value_name = "FILE", num_args = 1.., conflicts_with_all = ["normalize", "normalize_against", "normalize_file", "normalize_mode", "peer"])]
+    /// Overlays the totals of exports of other KPIs of the same experience, with each KPI indexed to 100 on its first day, to show whether they move together (e.g. --combine revenue.csv playtime.csv)
+    pub combine: Vec<PathBuf>,
+
+    #[arg(long, default_value = "rescale")]
+    /// How the normalized series is shown. Index implies --normalize
+    pub normalize_mode: NormalizeMode,
+
+    #[arg(long)]
+    /// Rounds normalized values to whole numbers when the analytics series is a count, such as users, so that it keeps whole numbers on the y-axis
+    pub quantize: bool,
+
+    #[arg(long)]
+    /// Converts playtime exports from hours into the given unit. Has no effect on other KPIs
+    pub unit: Option<PlaytimeUnit>,
+
+    #[arg(long, value_name = "VALUE[:LABEL]")]
+    /// Draws a dashed horizontal target line at the given value, optionally with a label (e.g. "25000:Q3 DAU goal")
+    pub goal: Option<Goal>,
+
+    #[arg(long, requires = "goal")]
+    /// Shades the region above or below the goal line
+    pub goal_shade: Option<GoalShade>,
+
+    #[arg(long, requires = "goal", default_value = "dashed")]
+    /// How the goal line is stroked
+    pub goal_line_style: LineStyle,
+
+    #[arg(long = "style", default_value = "line")]
+    /// The kind of chart drawn. A donut shows how the breakdown series, such as platforms or countries, split the KPI on a single day
+    pub chart_style: ChartStyle,
+
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    /// The day shown by a donut chart. Defaults to the latest day in the export
+    pub date: Option<NaiveDate>,
+
+    #[arg(long)]
+    /// Finds the dates on which the analytics series shifts to a new level and marks them with their change, to tell lasting shifts apart from noise
+    pub changepoints: bool,
+
+    #[arg(long, value_name = "START..END")]
+    /// Leaves out the days in the range from the chart and its statistics, such as an outage or a bot raid (e.g. "2024-03-10..2024-03-12"). Can be given multiple times
+    pub exclude_dates: Vec<DateRange>,
+
+    #[arg(long)]
+    /// Shades the days left out with --exclude-dates on the chart
+    pub shade_excluded: bool,
+
+    #[arg(long, value_name = "CALENDAR")]
+    /// Marks holidays on the chart and compares them with other days in exported statistics and reports, from a built-in calendar (us, uk or br) or a CSV file of dates and names. Can be given multiple times
+    pub holidays: Vec<HolidaySource>,
+
+    #[arg(long, value_name = "[PATTERN=]STYLE")]
+    /// Strokes the series whose names match the regular expression as solid, dashed or dotted lines, or every series if no pattern is given (e.g. "^Benchmark=dotted"). Can be given multiple times
+    pub line_style: Vec<SeriesValue<LineStyle>>,
+
+    #[arg(long, value_name = "[PATTERN=]OPACITY")]
+    /// Draws the series whose names match the regular expression with an opacity between 0 and 1, or every series if no pattern is given (e.g. "^Benchmark=0.5"). Can be given multiple times
+    pub opacity: Vec<SeriesValue<Opacity>>,
+
+    #[arg(long, value_name = "PIXELS", value_parser = clap::value_parser!(u32).range(1..))]
+    /// The width of the analytics series and goal lines. The benchmark series is drawn at half this width. Defaults to 2
+    pub stroke_width: Option<u32>,
+
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Draws a rolling average over the given number of days boldly over a faint analytics series, with a legend. Can be given multiple times, replacing the averages of --preset dau
+    pub rolling_average: Vec<u32>,
+
+    #[arg(long, conflicts_with = "y_min")]
+    /// Starts the y-axis at zero instead of just below the smallest value
+    pub y_from_zero: bool,
+
+    #[arg(long, allow_negative_numbers = true)]
+    /// Overrides the lower bound of the y-axis
+    pub y_min: Option<f64>,
+
+    #[arg(long, allow_negative_numbers = true)]
+    /// Overrides the upper bound of the y-axis
+    pub y_max: Option<f64>,
+
+    #[arg(long, value_name = "PERCENT", default_value_t = 10.0)]
+    /// The margin added above and below the data on the y-axis, as a percentage of the data's span
+    pub padding: f64,
+
+    #[arg(long, value_name = "PERCENT")]
+    /// Overrides the margin added above the data
+    pub padding_top: Option<f64>,
+
+    #[arg(long, value_name = "PERCENT")]
+    /// Overrides the margin added below the data
+    pub padding_bottom: Option<f64>,
+
+    #[arg(long)]
+    /// The config file to read presets from. Defaults to rasorite.toml in the working directory, if present
+    pub config: Option<PathBuf>,
+
+    #[arg(long)]
+    /// A profile from the config file giving the experience's universe ID, name, KPIs and output directory
+    pub profile: Option<String>,
+
+    #[arg(long, value_name = "TEXT")]
+    /// The experience's name, shown in the title instead of its universe ID. Defaults to the profile's name, if any
+    pub name: Option<String>,
+
+    #[arg(long)]
+    /// A named bundle of dimensions, fonts, theme, markers and grid settings. Individual options override it
+    pub preset: Option<Preset>,
+
+    #[arg(long)]
+    /// The width of the chart in pixels
+    pub width: Option<u32>,
+
+    #[arg(long)]
+    /// The height of the chart in pixels
+    pub height: Option<u32>,
+
+    #[arg(long)]
+    /// The font family used for all text on the chart
+    pub font: Option<String>,
+
+    #[arg(long)]
+    /// A multiplier applied to all font sizes
+    pub font_scale: Option<f64>,
+
+    #[arg(long)]
+    /// The color theme of the chart
+    pub theme: Option<Theme>,
+
+    #[arg(long)]
+    /// Draws a marker at every data point
+    pub markers: bool,
+
+    #[arg(long)]
+    /// The colors series are drawn in. The colorblind and greyscale palettes also give each series its own dash pattern and marker shape
+    pub palette: Option<Palette>,
+
+    #[arg(long)]
+    /// How much of the background grid to draw. Defaults to full
+    pub grid: Option<GridStyle>,
+
+    #[arg(long)]
+    /// Does not add tooltips showing the date and value of each point when hovering over an SVG chart in a browser
+    pub no_tooltips: bool,
+
+    #[arg(long)]
+    /// Does not embed the universe ID, KPI, date range and other details into the output file
+    pub no_metadata: bool,
+
+    #[arg(long, value_name = "COUNT")]
+    /// The maximum number of minor grid lines between labelled values when using the full grid
+    pub minor_lines: Option<usize>,
+
+    #[arg(long)]
+    /// Prints the latest value of each series in the right margin, next to the end of its line
+    pub value_labels: bool,
+
+    #[arg(long)]
+    /// Draws a small table of the analytics series' last value, 7-day average and change over 30 days in the top right corner of the chart
+    pub inline_stats: bool,
+
+    #[arg(long, value_name = "DAYS", num_args = 0..=1, default_missing_value = "30", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Shows the change in the analytics series' average over the last number of days against the days before them under the title, in green for a rise and red for a fall. Defaults to 30 days
+    pub delta_badge: Option<u32>,
+
+    #[arg(long)]
+    /// Colors falls green and rises red in --delta-badge, for KPIs where down is good
+    pub lower_is_better: bool,
+
+    #[arg(long)]
+    /// Leaves out the title and subtitle and keeps margins to a minimum, for charts embedded under their own headings
+    pub tight: bool,
+
+    #[arg(long, value_name = "TEXT")]
+    /// Free text printed small at the bottom of the chart, such as where the data came from
+    pub footer: Option<String>,
+
+    #[arg(long)]
+    /// Prints the data's date range and the time the chart was generated at the bottom of the chart, after any --footer
+    pub footer_stamp: bool,
+
+    #[arg(long, value_name = "TEXT")]
+    /// The title of the x-axis. Defaults to "Date", and an empty title leaves the axis untitled
+    pub x_desc: Option<String>,
+
+    #[arg(long, value_name = "TEXT")]
+    /// The title of the y-axis. Defaults to the KPI's name and unit, and an empty title leaves the axis untitled
+    pub y_desc: Option<String>,
+
+    #[arg(long, conflicts_with_all = ["x_desc", "y_desc"])]
+    /// Leaves both axes untitled
+    pub no_axis_titles: bool,
+
+    #[arg(long, default_value = "horizontal")]
+    /// How date labels are laid out when the chart is too narrow for them to sit side by side
+    pub date_labels: DateLabels,
+
+    #[arg(long)]
+    /// The language of the chart's titles and labels. Dates are written with month names when a language is given, and as ISO 8601 dates otherwise
+    pub lang: Option<Lang>,
+
+    #[arg(long, value_name = "TAG")]
+    /// The regional conventions of the chart, as a language tag such as en-GB. Dates are written in numbers in the locale's order unless --lang writes out their month names, and right-to-left locales such as ar or he mirror the layout
+    pub locale: Option<Locale>,
+
+    #[arg(long, value_name = "TAG")]
+    /// The locale the export's values are written in, as a language tag such as de-DE, for exports that have been through a spreadsheet. Digit grouping such as 1.234.567 is ignored and the locale's decimal separator is read as the decimal point
+    pub number_locale: Option<Locale>,
+
+    #[arg(long)]
+    /// Lays out text without measuring the installed fonts and leaves the generation time out of the embedded metadata, so that the same SVG is produced on every machine
+    pub deterministic: bool,
+}
+
+/// Plotting options given as a standalone list of flags, such as the flags of a chart in the config
+/// file
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+pub struct PlotArgs {
+    #[command(flatten)]
+    pub plot: PlotOptions,
+}
+
+impl PlotOptions {
+    /// Whether the analytics series is normalized, either against the first benchmark series or
+    /// against a chosen reference series
+    pub fn normalizes(&self) -> bool {
+        self.normalize
+            || self.normalize_against.is_some()
+            || self.normalize_file.is_some()
+            || self.normalize_mode == NormalizeMode::Index
+    }
+
+    /// The --pipeline steps followed by any --script, or None if neither was given
+    fn transforms(&self) -> Result<Option<Pipeline>, TransformError> {
+        let Some(file) = &self.script else {
+            return Ok(self.pipeline.clone());
+        };
+        info!("Compiling script {}...", file.display());
+        let script = Script::load(file)?;
+        Ok(Some(
+            self.pipeline
+                .clone()
+                .unwrap_or_default()
+                .then(Arc::new(script)),
+        ))
+    }
+
+    /// Looks up the chosen profile in the config file and fills in the options it gives that were
+    /// not given on the command line
+    pub fn apply_profile<'a>(
+        &mut self,
+        config: &'a Config,
+    ) -> Result<Option<&'a ProfileConfig>, ConfigError> {
+        let profile = config.profile(self.profile.as_deref())?;
+        if let Some(profile) = profile {
+            self.name = self.name.take().or_else(|| profile.name.clone());
+        }
+        Ok(profile)
+    }
+
+    /// Resolves the chart style from the chosen preset, its overrides in the config file and the
+    /// individual style options, in increasing order of precedence
+    pub fn resolve_style(&self, config: &Config) -> Style {
+        let mut style = self.preset.map(|preset| preset.style()).unwrap_or_default();
+
+        if let Some(overrides) = self
+            .preset
+            .and_then(|preset| config.presets.get(&preset.to_string()))
+        {
+            overrides.apply(&mut style);
+        }
+
+        StyleOverrides {
+            width: self.width,
+            height: self.height,
+            font: self.font.clone(),
+            font_scale: self.font_scale,
+            theme: self.theme,
+            markers: self.markers.then_some(true),
+            palette: self.palette,
+            grid: self.grid,
+            stroke_width: self.stroke_width,
+            rolling_averages: (!self.rolling_average.is_empty())
+                .then(|| self.rolling_average.clone()),
         }
+        .apply(&mut style);
+        style.series = config.series.clone();
+        style.lower_is_better = config.lower_is_better.clone();
+        // Series styles given on the command line take precedence over the config file
+        style
+            .series
+            .extend(self.line_style.iter().map(|series| SeriesRule {
+                line_style: Some(series.value),
+                ..SeriesRule::new(series.pattern.clone())
+            }));
+        style
+            .series
+            .extend(self.opacity.iter().map(|series| SeriesRule {
+                opacity: Some(series.value),
+                ..SeriesRule::new(series.pattern.clone())
+            }));
+
+        style
     }
 }
 
+#[derive(Debug, Error)]
+pub enum PlottingError {
+    #[error("The analytics data series is missing!")]
+    SeriesMissing,
+
+    #[error("The series \"{0}\" could not be found!")]
+    NamedSeriesMissing(String),
+
+    #[error("The provided output file path is invalid!")]
+    InvalidOutput,
+
+    #[error(transparent)]
+    Output(#[from] OutputError),
+
+    #[error("The output directory \"{0}\" could not be created!")]
+    UnwritableDirectory(PathBuf),
+
+    #[error("The y-axis minimum must be less than the y-axis maximum!")]
+    InvalidAxisBounds,
+
+    #[error("Axis padding cannot be negative!")]
+    InvalidPadding,
+
+    #[error("The reference series \"{0}\" could not be found!")]
+    ReferenceMissing(String),
+
+    #[error("The reference file \"{0}\" could not be parsed: {1}")]
+    InvalidReferenceFile(PathBuf, AnalyticsParseError),
+
+    #[error(transparent)]
+    Transform(#[from] TransformError),
+
+    #[error(transparent)]
+    Holiday(#[from] HolidayError),
+
+    #[error("The peer file \"{0}\" could not be parsed: {1}")]
+    InvalidPeerFile(PathBuf, AnalyticsParseError),
+
+    #[error("The peer file \"{0}\" has no \"Total\" series to compare against!")]
+    PeerSeriesMissing(PathBuf),
+
+    #[error("The combined file \"{0}\" could not be parsed: {1}")]
+    InvalidCombinedFile(PathBuf, AnalyticsParseError),
+
+    #[error("The combined file \"{0}\" has no \"Total\" series to plot!")]
+    CombinedSeriesMissing(PathBuf),
+
+    #[error("{0} is combined more than once! Each export given with --combine must be of a different KPI than the input and each other.")]
+    CombinedKpiRepeated(KpiType),
+
+    #[error("\"{0}\" cannot be combined with the input! {1}")]
+    MismatchedFile(PathBuf, MismatchError),
+
+    #[error("The export has no breakdown series to show the shares of! Export it with a \"View by\" breakdown, such as platform or country.")]
+    BreakdownMissing,
+
+    #[error("The export has no breakdown data on {0}!")]
+    DateMissing(NaiveDate),
+
+    #[error("A calculation on the data failed: {0}")]
+    Arithmetic(#[from] DataPointArithmeticError),
+
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+}
+
+/// The series the analytics series is normalized against
+struct Reference {
+    /// The name of the series, as it appears in its export
+    key: String,
+    /// The name of the file the series was read from, when it is not the chart's own export
+    file: Option<String>,
+    points: Points,
+}
+
+impl Reference {
+    /// How the series is described in the chart's subtitle
+    fn describe(&self, locale: &Localizer) -> String {
+        let description = match BenchmarkSeries::parse(&self.key) {
+            Some(benchmark) => locale.message(
+                "reference-benchmark",
+                &[("benchmark", locale.benchmark(&benchmark).into())],
+            ),
+            None => locale.message("reference-series", &[("series", self.key.as_str().into())]),
+        };
+
+        match &self.file {
+            Some(file) => locale.message(
+                "reference-from-file",
+                &[
+                    ("reference", description.into()),
+                    ("file", file.as_str().into()),
+                ],
+            ),
+            None => description,
+        }
+    }
+}
+
+/// The total of another experience's export, charted as a benchmark
+struct Peer {
+    /// The name of the series on the chart
+    key: String,
+    universe_id: u64,
+    /// The name of the file the series was read from
+    file: String,
+    points: Points,
+}
+
+impl Peer {
+    fn reference(&self) -> Reference {
+        Reference {
+            key: self.key.clone(),
+            file: Some(self.file.clone()),
+            points: self.points.clone(),
+        }
+    }
+}
+
+/// Reads the totals of the exports given with `--peer`, which must be of the same KPI as the chart
+fn read_peers(
+    data: &AnalyticsData,
+    pipeline: Option<&Pipeline>,
+    opts: &PlotOptions,
+) -> Result<Vec<Peer>, PlottingError> {
+    opts.peer
+        .iter()
+        .map(|file| {
+            info!("Reading peer series from {}...", file.display());
+            let mut peer = parse_localized_analytics_file(file, opts.number_locale.as_ref())
+                .map_err(|e| PlottingError::InvalidPeerFile(file.clone(), e))?;
+
+            data.check_same_kpi(&peer, opts.mix_kpis)
+                .and_then(|()| data.check_combinable(&peer, false, opts.allow_mismatch))
+                .map_err(|e| PlottingError::MismatchedFile(file.clone(), e))?;
+            if let Some(unit) = opts.unit {
+                peer.convert_playtime(unit)?;
+            }
+            peer.add_computed_total()?;
+            if let Some(pipeline) = pipeline {
+                pipeline.apply(&mut peer)?;
+            }
+
+            let points = peer
+                .data
+                .iter()
+                .filter(|(key, _)| key.starts_with("Total"))
+                .min_by(|(a, _), (b, _)| a.cmp(b))
+                .map(|(_, points)| points.clone())
+                .ok_or_else(|| PlottingError::PeerSeriesMissing(file.clone()))?;
+
+            Ok(Peer {
+                key: format!("Experience ID {}", peer.universe_id),
+                universe_id: peer.universe_id,
+                file: file
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy()
+                    .into_owned(),
+                points,
+            })
+        })
+        .collect()
+}
+
+/// The total of an export of another KPI, charted with `--combine`
+struct Combined {
+    kpi: KpiType,
+    /// The total indexed to 100 on its first day
+    points: Points,
+}
+
+/// Reads the totals of the exports given with `--combine`, which must be of the same experience as
+/// the chart and each of a different KPI
+fn read_combined(
+    data: &AnalyticsData,
+    pipeline: Option<&Pipeline>,
+    opts: &PlotOptions,
+) -> Result<Vec<Combined>, PlottingError> {
+    let mut combined: Vec<Combined> = Vec::new();
+    for file in &opts.combine {
+        info!("Reading combined series from {}...", file.display());
+        let mut other = parse_localized_analytics_file(file, opts.number_locale.as_ref())
+            .map_err(|e| PlottingError::InvalidCombinedFile(file.clone(), e))?;
+
+        data.check_combinable(&other, true, opts.allow_mismatch)
+            .map_err(|e| PlottingError::MismatchedFile(file.clone(), e))?;
+        if other.kpi_type == data.kpi_type || combined.iter().any(|c| c.kpi == other.kpi_type) {
+            return Err(PlottingError::CombinedKpiRepeated(other.kpi_type));
+        }
+        if let Some(unit) = opts.unit {
+            other.convert_playtime(unit)?;
+        }
+        other.add_computed_total()?;
+        if let Some(pipeline) = pipeline {
+            pipeline.apply(&mut other)?;
+        }
+
+        let points = other
+            .data
+            .iter()
+            .filter(|(key, _)| key.starts_with("Total"))
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, points)| rebase_data(points))
+            .ok_or_else(|| PlottingError::CombinedSeriesMissing(file.clone()))??;
+
+        combined.push(Combined {
+            kpi: other.kpi_type,
+            points,
+        });
+    }
+    Ok(combined)
+}
+
+/// Finds the series to normalize against: the series named by `--normalize-against` or else the
+/// first benchmark series, taken from `--normalize-file` if given or else from the chart's own
+/// export. A file without benchmarks falls back to its analytics series, so that one experience
+/// can be normalized against another, and an export without benchmarks falls back to the first
+/// peer.
+fn find_reference(
+    data: &AnalyticsData,
+    peers: &[Peer],
+    pipeline: Option<&Pipeline>,
+    opts: &PlotOptions,
+) -> Result<Option<Reference>, PlottingError> {
+    let other = opts
+        .normalize_file
+        .as_ref()
+        .map(|file| {
+            info!("Reading reference series from {}...", file.display());
+            let mut other = parse_localized_analytics_file(file, opts.number_locale.as_ref())
+                .map_err(|e| PlottingError::InvalidReferenceFile(file.clone(), e))?;
+            // The reference may be another experience, such as one to normalize against
+            data.check_same_kpi(&other, opts.mix_kpis)
+                .and_then(|()| data.check_combinable(&other, false, opts.allow_mismatch))
+                .map_err(|e| PlottingError::MismatchedFile(file.clone(), e))?;
+            if let Some(unit) = opts.unit {
+                other.convert_playtime(unit)?;
+            }
+            other.add_computed_total()?;
+            if let Some(pipeline) = pipeline {
+                pipeline.apply(&mut other)?;
+            }
+            Ok::<_, PlottingError>(other)
+        })
+        .transpose()?;
+    let source = other.as_ref().unwrap_or(data);
+
+    let key = match &opts.normalize_against {
+        Some(name) if source.data.contains_key(name.as_str()) => name.clone(),
+        Some(name) => return Err(PlottingError::ReferenceMissing(name.clone())),
+        None => match source.benchmarks().into_iter().next() {
+            Some(benchmark) => benchmark.key,
+            None if other.is_some() => {
+                match source.data.keys().find(|key| key.starts_with("Total")) {
+                    Some(key) => key.to_string(),
+                    None => return Err(PlottingError::SeriesMissing),
+                }
+            }
+            None => return Ok(peers.first().map(Peer::reference)),
+        },
+    };
+
+    Ok(Some(Reference {
+        points: source.data[key.as_str()].clone(),
+        key,
+        file: opts.normalize_file.as_ref().map(|file| {
+            file.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned()
+        }),
+    }))
+}
+
+pub fn plot_data(
+    data: AnalyticsData,
+    out_file: &Path,
+    opts: &PlotOptions,
+    style: &Style,
+) -> Result<(), PlottingError> {
+    let is_vector = check_output(out_file)?;
+
+    if let Some(parent) = out_file
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty() && !parent.exists())
+    {
+        info!("Creating output directory {}...", parent.display());
+        std::fs::create_dir_all(parent)
+            .map_err(|_| PlottingError::UnwritableDirectory(parent.to_path_buf()))?;
+    }
+
+    write_atomically(
+        out_file,
+        |file| {
+            let backend = if is_vector {
+                DrawingBackendVariant::Vector(SVGBackend::new(file, (style.width, style.height)))
+            } else {
+                DrawingBackendVariant::Bitmap(BitMapBackend::new(file, (style.width, style.height)))
+            };
+
+            let DrawnChart { metadata, tooltips } = draw_chart(data, backend, opts, style)?;
+            let _write = timings::start("write");
+
+            if is_vector && !tooltips.is_empty() {
+                let svg =
+                    std::fs::read_to_string(file).map_err(|_| PlottingError::InvalidOutput)?;
+                let svg = add_svg_tooltips(&svg, &tooltips).ok_or(PlottingError::InvalidOutput)?;
+                std::fs::write(file, svg).map_err(|_| PlottingError::InvalidOutput)?;
+                info!("Added tooltips for {} points!", tooltips.len());
+            }
+
+            if !opts.no_metadata {
+                if embed_metadata(file, &metadata)? {
+                    info!("Embedded chart metadata!");
+                } else {
+                    info!("The output format does not support metadata, skipping...");
+                }
+            }
+
+            if let Some((name, contents)) = &opts.embedded_data {
+                if embed_data(file, name, contents)? {
+                    info!("Embedded the input data into the chart!");
+                } else {
+                    warning::emit(Warning::EmbedSvgOnly);
+                }
+            }
+
+            Ok(())
+        },
+        || PlottingError::InvalidOutput,
+    )
+}
+
+/// Renders the chart into an RGB pixel buffer the size of the style rather than into a file
+#[cfg(feature = "preview")]
+pub fn render_bitmap(
+    data: AnalyticsData,
+    opts: &PlotOptions,
+    style: &Style,
+) -> Result<Vec<u8>, PlottingError> {
+    let mut buffer = vec![0; style.width as usize * style.height as usize * 3];
+    let backend = BitMapBackend::with_buffer(&mut buffer, (style.width, style.height));
+    draw_chart(data, backend.into(), opts, style)?;
+
+    Ok(buffer)
+}
+
+/// Renders the chart into an SVG document rather than into a file
+pub fn render_svg(
+    data: AnalyticsData,
+    opts: &PlotOptions,
+    style: &Style,
+) -> Result<String, PlottingError> {
+    let mut svg = String::new();
+    let backend = SVGBackend::with_string(&mut svg, (style.width, style.height));
+    let DrawnChart { tooltips, .. } = draw_chart(data, backend.into(), opts, style)?;
+
+    add_svg_tooltips(&svg, &tooltips).ok_or(PlottingError::InvalidOutput)
+}
+
+/// A label shown when hovering over a plotted point
+struct PointTooltip {
+    /// Where the point is drawn, in pixels from the top left of the chart
+    position: (i32, i32),
+    text: String,
+}
+
+/// What drawing a chart produces besides the drawing itself
+struct DrawnChart {
+    metadata: ChartMetadata,
+    tooltips: Vec<PointTooltip>,
+}
+
+/// Adds an invisible hover target with a `<title>` over each point of an SVG chart, so that
+/// browsers show the point's date and value when hovering over it
+fn add_svg_tooltips(svg: &str, tooltips: &[PointTooltip]) -> Option<String> {
+    if tooltips.is_empty() {
+        return Some(svg.to_string());
+    }
+
+    let svg_end = svg.rfind("</svg>")?;
+    let mut element = String::from("<g fill=\"none\" pointer-events=\"all\">\n");
+    for PointTooltip {
+        position: (x, y),
+        text,
+    } in tooltips
+    {
+        element.push_str(&format!(
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"><title>{}</title></circle>\n",
+            x,
+            y,
+            TOOLTIP_RADIUS,
+            escape_xml(text)
+        ));
+    }
+    element.push_str("</g>\n");
+
+    let mut result = svg.to_string();
+    result.insert_str(svg_end, &element);
+    Some(result)
+}
+
+/// Draws a donut chart of how the breakdown series split the KPI on a single day. Breakdowns too
+/// small for a slice of their own are gathered into one.
+fn draw_donut(
+    data: AnalyticsData,
+    backend: DrawingBackendVariant,
+    opts: &PlotOptions,
+    style: &Style,
+) -> Result<DrawnChart, PlottingError> {
+    let font = style.font_family();
+    let theme = style.theme;
+    let locale = Localizer::new(opts.lang.unwrap_or_default());
+
+    info!("Finding breakdown series...");
+
+    let breakdowns: Vec<(&str, &Points)> = data
+        .breakdowns()
+        .into_iter()
+        .map(|key| (key, &data.data[key]))
+        .collect();
+    let date = match opts.date {
+        Some(date) => date,
+        None => breakdowns
+            .iter()
+            .flat_map(|(_, points)| points.iter().map(|(date, _)| date.date_naive()))
+            .max()
+            .ok_or(PlottingError::BreakdownMissing)?,
+    };
+
+    let mut slices: Vec<(String, f64)> = breakdowns
+        .into_iter()
+        .filter_map(|(key, points)| {
+            let (_, point) = points.iter().find(|(day, _)| day.date_naive() == date)?;
+            Some((key.to_string(), f64::from(*point)))
+        })
+        .filter(|(_, value)| *value > 0.0)
+        .collect();
+    if slices.is_empty() {
+        return Err(PlottingError::DateMissing(date));
+    }
+    // Largest first, and by name for equal values so that the output never depends on the order
+    // the series were found in
+    slices.sort_by(|(a_name, a), (b_name, b)| b.total_cmp(a).then_with(|| a_name.cmp(b_name)));
+    let palette: &[RGBColor] = match style.palette {
+        Palette::Default => &DONUT_COLORS,
+        Palette::Colorblind => &OKABE_ITO,
+        Palette::Greyscale => &GREYSCALE,
+    };
+    let grouped = slices.len() > palette.len();
+    if grouped {
+        let other: f64 = slices
+            .drain(palette.len() - 1..)
+            .map(|(_, value)| value)
+            .sum();
+        slices.push((locale.message("donut-other", &[]), other));
+    }
+    info!("Found {} slices on {}", slices.len(), date);
+
+    let total: f64 = slices.iter().map(|(_, value)| value).sum();
+    let labels: Vec<String> = slices
+        .iter()
+        .map(|(name, value)| format!("{} ({:.1}%)", name, value / total * 100.0))
+        .collect();
+    let values: Vec<f64> = slices.iter().map(|(_, value)| *value).collect();
+    let mut colors = palette[..slices.len()].to_vec();
+    if grouped {
+        colors[slices.len() - 1] = DONUT_OTHER_COLOR;
+    }
+
+    let mut drawing_area = backend.into_drawing_area();
+    drawing_area
+        .fill(&theme.background())
+        .expect("Failed to fill drawing area!");
+
+    let day = date
+        .and_hms_opt(0, 0, 0)
+        .expect("Midnight is a valid time!")
+        .and_utc();
+    if opts.tight {
+        info!("Leaving out the title block for a tight layout...");
+    } else {
+        drawing_area = titled(
+            &drawing_area,
+            &chart_title(&data, &[], opts, &locale),
+            (font, style.font_size(50f64), FontStyle::Bold)
+                .into_font()
+                .color(&theme.foreground()),
+            opts.deterministic,
+        )
+        .expect("Failed to draw title!");
+        drawing_area = titled(
+            &drawing_area,
+            &locale.message("donut-subtitle", &[("date", locale.date(&day).into())]),
+            (font, style.font_size(25f64), FontStyle::Italic)
+                .into_font()
+                .color(&theme.muted()),
+            opts.deterministic,
+        )
+        .expect("Failed to draw subtitle!");
+    }
+
+    info!("Drawing slices...");
+
+    let (width, height) = drawing_area.dim_in_pixel();
+    let center = (width as i32 / 2, height as i32 / 2);
+    // Unlike other elements, the donut is positioned in pixels of the whole chart
+    let base = drawing_area.get_base_pixel();
+    let pie_center = (base.0 + center.0, base.1 + center.1);
+    // Room is left around the donut for the labels beside it
+    let radius = (height as f64 / 2.0 - style.font_size(40f64)).min(width as f64 / 4.0);
+    let label_style = (font, style.font_size(18f64))
+        .into_font()
+        .color(&theme.foreground());
+
+    // The labels are drawn separately, as the donut measures them with the installed fonts to
+    // place them
+    let no_labels = vec![""; slices.len()];
+    let mut pie = Pie::new(&pie_center, &radius, &values, &colors, &no_labels);
+    // Starting at the top, as people read a clock
+    pie.start_angle(-90.0);
+    pie.donut_hole(radius * 0.55);
+    drawing_area
+        .draw(&pie)
+        .expect("Failed to draw donut chart!");
+
+    let label_radius = radius + style.font_size(12f64);
+    let mut angle = -FRAC_PI_2;
+    for (label, value) in labels.into_iter().zip(&values) {
+        let share = value / total * TAU;
+        let middle = angle + share / 2.0;
+        angle += share;
+
+        let (sin, cos) = middle.sin_cos();
+        let side = if cos >= 0.0 { HPos::Left } else { HPos::Right };
+        drawing_area
+            .draw(&Text::new(
+                label,
+                (
+                    center.0 + (label_radius * cos).round() as i32,
+                    center.1 + (label_radius * sin).round() as i32,
+                ),
+                label_style.pos(Pos::new(side, VPos::Center)),
+            ))
+            .expect("Failed to draw donut label!");
+    }
+
+    drawing_area
+        .draw(&Text::new(
+            format_number(total),
+            center,
+            (font, style.font_size(36f64), FontStyle::Bold)
+                .into_font()
+                .color(&theme.foreground())
+                .pos(Pos::new(HPos::Center, VPos::Center)),
+        ))
+        .expect("Failed to draw donut total!");
+
+    info!("Data plotted!");
+
+    drawing_area
+        .present()
+        .map_err(|_| PlottingError::InvalidOutput)?;
+
+    Ok(DrawnChart {
+        metadata: ChartMetadata {
+            universe_id: data.universe_id,
+            kpi: data.kpi_type.to_string(),
+            date_range: (day, day),
+            transforms: vec![format!("donut:{}", date)],
+            generated: (!opts.deterministic).then(Utc::now),
+        },
+        tooltips: Vec::new(),
+    })
+}
+
+/// The chart's title, naming the KPI and the experience
+fn chart_title(
+    data: &AnalyticsData,
+    combined: &[Combined],
+    opts: &PlotOptions,
+    locale: &Localizer,
+) -> String {
+    // The unit is only named when asked for, as Roblox's own charts leave it out
+    let kpi = match (opts.unit, data.playtime_unit) {
+        (Some(_), Some(unit)) => locale.message(
+            "kpi-in-unit",
+            &[
+                ("kpi", locale.kpi(&data.kpi_type).into()),
+                ("unit", locale.playtime_unit(unit).into()),
+            ],
+        ),
+        _ => locale.kpi(&data.kpi_type),
+    };
+    // A breakdown charted on its own is named, so that it is not mistaken for the total
+    let kpi = match &opts.series {
+        Some(series) if opts.chart_style == ChartStyle::Line => format!("{} ({})", kpi, series),
+        _ => kpi,
+    };
+    // The legend names combined KPIs, as a title listing them all would not fit
+    let kpi = if combined.is_empty() {
+        kpi
+    } else {
+        locale.message("combined-kpis", &[("count", (combined.len() + 1).into())])
+    };
+
+    match &opts.name {
+        Some(name) => locale.message(
+            "chart-title-named",
+            &[("kpi", kpi.into()), ("name", name.clone().into())],
+        ),
+        None => locale.message(
+            "chart-title",
+            &[
+                ("kpi", kpi.into()),
+                ("universe", data.universe_id.to_string().into()),
+            ],
+        ),
+    }
+}
+
+/// Draws the chart onto the given backend, returning the metadata describing it and where its
+/// points are
+fn draw_chart(
+    mut data: AnalyticsData,
+    backend: DrawingBackendVariant,
+    opts: &PlotOptions,
+    style: &Style,
+) -> Result<DrawnChart, PlottingError> {
+    let PlotOptions {
+        goal,
+        goal_shade,
+        goal_line_style,
+        y_from_zero,
+        y_min,
+        y_max,
+        padding,
+        padding_top,
+        padding_bottom,
+        minor_lines,
+        value_labels,
+        deterministic,
+        lang,
+        tight,
+        ..
+    } = opts;
+
+    if let Some(unit) = opts.unit {
+        data.convert_playtime(unit)?;
+    }
+    if opts.preset == Some(Preset::Dau) && !matches!(data.kpi_type, KpiType::DailyActiveUsers) {
+        warning::emit(Warning::PresetKpi(data.kpi_type.clone()));
+    }
+    data.exclude_dates(&opts.exclude_dates);
+    // The total is summed before any transforms, which do not add up the way raw values do
+    if opts.chart_style == ChartStyle::Line && opts.series.is_none() {
+        data.add_computed_total()?;
+    }
+    let pipeline = opts.transforms()?;
+    if let Some(pipeline) = &pipeline {
+        info!("Transforming data with {}...", pipeline.names().join(", "));
+        timings::time("transform", || pipeline.apply(&mut data))?;
+    }
+    if opts.chart_style == ChartStyle::Donut {
+        return draw_donut(data, backend, opts, style);
+    }
+
+    let font = style.font_family();
+    let theme = style.theme;
+    let locale = Localizer::new(lang.unwrap_or_default());
+    // Patterned palettes tell series apart by their markers as well as their colors. Markers grow
+    // with heavy lines so that they still stand out.
+    let right_to_left = opts
+        .locale
+        .as_ref()
+        .is_some_and(|locale| locale.is_right_to_left());
+    let point_size = if style.markers || style.palette.patterned() {
+        style.stroke_width.max(3)
+    } else {
+        0
+    };
+
+    let padding = Padding {
+        top: padding_top.unwrap_or(*padding),
+        bottom: padding_bottom.unwrap_or(*padding),
+    };
+    if padding.top < 0f64 || padding.bottom < 0f64 {
+        return Err(PlottingError::InvalidPadding);
+    }
+
+    info!("Finding data series...");
+
+    let data_series = match &opts.series {
+        Some(name) => data
+            .data
+            .get_key_value(name.as_str())
+            .map(|(key, points)| (key.clone(), points.clone()))
+            .ok_or_else(|| PlottingError::NamedSeriesMissing(name.clone()))?,
+        // The first by name, so that the same series is chosen every time an export has several
+        None => data
+            .data
+            .iter()
+            .filter(|(key, _)| key.starts_with("Total"))
+            .min_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(key, points)| (key.clone(), points.clone()))
+            .ok_or(PlottingError::SeriesMissing)?,
+    };
+    let benchmarks: Vec<(BenchmarkSeries, Points)> = data
+        .benchmarks()
+        .into_iter()
+        .map(|benchmark| {
+            let points = data.data[benchmark.key.as_str()].clone();
+            (benchmark, points)
+        })
+        .collect();
+    let peers = read_peers(&data, pipeline.as_ref(), opts)?;
+    let combined = read_combined(&data, pipeline.as_ref(), opts)?;
+    let combining = !combined.is_empty();
+    // Only the series drawn as lines take up room on the axes, so that a chart of one breakdown is
+    // not stretched to fit the others
+    let charted: Vec<(&str, &Points)> = std::iter::once((&*data_series.0, &data_series.1))
+        .chain(
+            benchmarks
+                .iter()
+                .map(|(benchmark, points)| (benchmark.key.as_str(), points)),
+        )
+        .chain(peers.iter().map(|peer| (peer.key.as_str(), &peer.points)))
+        .collect();
+    let reference = if opts.normalizes() {
+        find_reference(&data, &peers, pipeline.as_ref(), opts)?
+    } else {
+        None
+    };
+
+    if !benchmarks.is_empty() {
+        info!("Found analytics and benchmark series!");
+    }
+    if opts.normalizes() && reference.is_none() {
+        warning::emit(Warning::BenchmarksMissing);
+    }
+
+    info!("Initializing chart...");
+    let layout = timings::start("layout");
+
+    let mut drawing_area = backend.into_drawing_area();
+
+    info!("Chart initialized!");
+
+    drawing_area
+        .fill(&theme.background())
+        .expect("Failed to fill drawing area!");
+    let index = opts.normalize_mode == NormalizeMode::Index;
+    if *tight {
+        info!("Leaving out the title block for a tight layout...");
+    } else {
+        drawing_area = titled(
+            &drawing_area,
+            &chart_title(&data, &combined, opts, &locale),
+            (font, style.font_size(50f64), FontStyle::Bold)
+                .into_font()
+                .color(&theme.foreground()),
+            *deterministic,
+        )
+        .expect("Failed to draw title!");
+
+        if let Some(reference) = &reference {
+            drawing_area = titled(
+                &drawing_area,
+                &locale.message(
+                    if index {
+                        "percentage-of"
+                    } else {
+                        "normalized-over"
+                    },
+                    &[("reference", reference.describe(&locale).into())],
+                ),
+                (font, style.font_size(25f64), FontStyle::Italic)
+                    .into_font()
+                    .color(&theme.muted()),
+                *deterministic,
+            )
+            .expect("Failed to draw subtitle!");
+        } else if !combining && (!benchmarks.is_empty() || !peers.is_empty()) {
+            let descriptions: Vec<String> = benchmarks
+                .iter()
+                .map(|(benchmark, _)| locale.benchmark(benchmark))
+                .chain(peers.iter().map(|peer| {
+                    locale.message("peer", &[("universe", peer.universe_id.to_string().into())])
+                }))
+                .collect();
+            drawing_area = titled(
+                &drawing_area,
+                &locale.message(
+                    "plotted-against",
+                    &[
+                        ("count", descriptions.len().into()),
+                        ("benchmarks", descriptions.join(", ").into()),
+                    ],
+                ),
+                (font, style.font_size(25f64), FontStyle::Italic)
+                    .into_font()
+                    .color(&theme.muted()),
+                *deterministic,
+            )
+            .expect("Failed to draw subtitle!");
+        }
+
+        let delta = opts.delta_badge.and_then(|days| {
+            let digest = KpiDigest::new(&data, days)?;
+            let delta = digest
+                .series
+                .into_iter()
+                .find(|series| *series.name == *data_series.0)?;
+            Some((days, delta.change_percent()?))
+        });
+        match delta {
+            Some((days, percent)) => {
+                let lower_is_better = opts.lower_is_better || style.is_lower_better(&data.kpi_type);
+                let color = if (percent >= 0.0) != lower_is_better {
+                    GREEN_700
+                } else {
+                    RED_700
+                };
+                drawing_area = titled(
+                    &drawing_area,
+                    &locale.message(
+                        "delta-badge",
+                        &[
+                            ("arrow", if percent >= 0.0 { "▲" } else { "▼" }.into()),
+                            ("change", format_percent(Some(percent)).into()),
+                            ("days", days.into()),
+                        ],
+                    ),
+                    (font, style.font_size(22f64), FontStyle::Bold)
+                        .into_font()
+                        .color(&color),
+                    *deterministic,
+                )
+                .expect("Failed to draw delta badge!");
+            }
+            None if opts.delta_badge.is_some() => {
+                warning::emit(Warning::DeltaBadgeTooShort);
+            }
+            None => {}
+        }
+    }
+
+    let mut footer = Vec::new();
+    if let Some(text) = &opts.footer {
+        footer.push(text.clone());
+    }
+    if opts.footer_stamp {
+        let dates = data.data.values().flatten().map(|(date, _)| *date);
+        if let (Some(start), Some(end)) = (dates.clone().min(), dates.max()) {
+            let format_date = |date: &DateTime<Utc>| write_date(date, opts, &locale);
+            footer.push(locale.message(
+                "footer-data-range",
+                &[
+                    ("start", format_date(&start).into()),
+                    ("end", format_date(&end).into()),
+                ],
+            ));
+        }
+        if !deterministic {
+            footer.push(locale.message(
+                "footer-generated",
+                &[(
+                    "generated",
+                    Utc::now().format("%F %H:%M UTC").to_string().into(),
+                )],
+            ));
+        }
+    }
+    if !footer.is_empty() {
+        let footer = footer.join(" · ");
+        let footer_style = (font, style.font_size(14f64))
+            .into_font()
+            .color(&theme.muted());
+        let (_, text_height) = text_size(&drawing_area, &footer, &footer_style, *deterministic);
+        let footer_height = text_height + FOOTER_PADDING * 2;
+        let (_, height) = drawing_area.dim_in_pixel();
+        let (chart_area, footer_area) =
+            drawing_area.split_vertically(height.saturating_sub(footer_height));
+        // The footer starts on the side the chart is read from
+        let (width, _) = footer_area.dim_in_pixel();
+        let (x, side) = if right_to_left {
+            (width as i32 - 5, HPos::Right)
+        } else {
+            (5, HPos::Left)
+        };
+        footer_area
+            .draw(&Text::new(
+                footer,
+                (x, (footer_height / 2) as i32),
+                footer_style.pos(Pos::new(side, VPos::Center)),
+            ))
+            .expect("Failed to draw footer!");
+        drawing_area = chart_area;
+    }
+
+    let mut chart = ChartBuilder::on(&drawing_area);
+    drop(layout);
+
+    // Only counts are rounded, as rounding fractional KPIs would throw away their precision
+    let quantize = opts.quantize
+        && !index
+        && AxisValues::of(data_series.1.iter().map(|(_, point)| point)) == AxisValues::Counts;
+    let normalize = timings::start("normalize");
+    let normalized_data = if combining {
+        info!("Indexing data to its first day...");
+        Some(rebase_data(&data_series.1))
+    } else {
+        reference.as_ref().map(|reference| {
+            info!("Normalizing data around \"{}\"...", reference.key);
+            if index {
+                return index_data(&data_series.1, &reference.points);
+            }
+            let normalized = normalize_data(data_series.clone().1, reference.points.clone())?;
+            if quantize {
+                info!("Rounding normalized data...");
+                Ok(quantize_data(normalized))
+            } else {
+                Ok(normalized)
+            }
+        })
+    };
+    let normalized_data = normalized_data.transpose()?;
+    drop(normalize);
+
+    let mut transforms = pipeline.as_ref().map(Pipeline::names).unwrap_or_default();
+    transforms.extend(
+        opts.combine
+            .iter()
+            .map(|file| format!("combine:{}", file.display())),
+    );
+    if let Some(reference) = &reference {
+        info!("Data normalized!");
+        let transform = if index { "index" } else { "normalize" };
+        transforms.push(match &opts.normalize_file {
+            Some(file) => format!("{}:{}:{}", transform, file.display(), reference.key),
+            None => format!("{}:{}", transform, reference.key),
+        });
+        if quantize {
+            transforms.push("quantize".to_string());
+        }
+    }
+
+    info!("Getting axis ranges...");
+    let ranges = timings::start("ranges");
+
+    let (date_range, data_range) = if let Some(data) = &normalized_data {
+        get_data_range_multi(
+            std::iter::once(data.as_slice())
+                .chain(combined.iter().map(|combined| combined.points.as_slice())),
+            padding,
+        )
+    } else {
+        // Hidden series take up no room on the axes, unless every series is hidden
+        let visible =
+            |(name, points): &&(&str, &Points)| !style.is_hidden(name) && !points.is_empty();
+        if charted.iter().any(|series| visible(&series)) {
+            get_data_range_multi(
+                charted
+                    .iter()
+                    .filter(visible)
+                    .map(|(_, points)| points.as_slice()),
+                padding,
+            )
+        } else {
+            get_data_range_multi(charted.iter().map(|(_, points)| points.as_slice()), padding)
+        }
+    };
+
+    let axis_values = match &normalized_data {
+        Some(_) if index => AxisValues::Percentages,
+        Some(data) => AxisValues::of(data.iter().map(|(_, point)| point)),
+        None if data.kpi_type.is_percentage() => AxisValues::Percentages,
+        None => AxisValues::of(
+            charted
+                .iter()
+                .filter(|(name, _)| !style.is_hidden(name))
+                .flat_map(|(_, points)| points.iter().map(|(_, point)| point)),
+        ),
+    };
+
+    let data_range = match goal {
+        Some(goal) => data_range.including(goal.value, padding),
+        None => data_range,
+    };
+    // The 100% line is always in view, so that it is clear which side of the reference the series is
+    // on. Combined KPIs all start on it.
+    let reference_line = combining || (index && normalized_data.is_some());
+    let data_range = if reference_line {
+        data_range.including(100f64, padding)
+    } else {
+        data_range
+    };
+    // A flat series has a range with no width of its own, so only bounds set on the command line
+    // can leave the axis without one
+    let data_range = data_range
+        .widened()
+        .with_bounds(if *y_from_zero { Some(0f64) } else { *y_min }, *y_max);
+
+    let value_bounds = data_range.range();
+    if f64::from(value_bounds.start) >= f64::from(value_bounds.end) {
+        return Err(PlottingError::InvalidAxisBounds);
+    }
+
+    drop(ranges);
+    info!("Ranges calculated!");
+    let _draw = timings::start("draw");
+
+    let (date_start, date_end) = (date_range.start, date_range.end);
+
+    let format_value = |value: &DataPoint| axis_values.format(value);
+    let format_date = |date: &DateTime<Utc>| write_date(date, opts, &locale);
+    let label_style = (font, style.font_size(18f64))
+        .into_font()
+        .color(&theme.foreground());
+    let desc_style = (font, style.font_size(20f64))
+        .into_font()
+        .color(&theme.foreground());
+
+    let y_unit = if index && normalized_data.is_some() {
+        Some("percent".to_string())
+    } else if let Some(unit) = data.playtime_unit {
+        Some(unit.to_string())
+    } else if let KpiType::DailyRevenue | KpiType::Arpdau = data.kpi_type {
+        Some("robux".to_string())
+    } else {
+        None
+    };
+    let default_y_desc = match y_unit {
+        _ if combining => locale.message("combined-axis", &[]),
+        Some(unit) => locale.message(
+            "kpi-in-unit",
+            &[
+                ("kpi", locale.kpi(&data.kpi_type).into()),
+                (
+                    "unit",
+                    locale.message("unit", &[("unit", unit.into())]).into(),
+                ),
+            ],
+        ),
+        None => locale.kpi(&data.kpi_type),
+    };
+    let (x_desc, y_desc) = if opts.no_axis_titles {
+        (None, None)
+    } else {
+        (
+            Some(
+                opts.x_desc
+                    .clone()
+                    .unwrap_or_else(|| locale.message("x-axis", &[])),
+            ),
+            Some(opts.y_desc.clone().unwrap_or(default_y_desc)),
+        )
+    };
+    let x_desc = x_desc.filter(|desc| !desc.is_empty());
+    let y_desc = y_desc.filter(|desc| !desc.is_empty());
+    let desc_room = |desc: &Option<String>| {
+        desc.as_ref().map_or(0, |desc| {
+            text_size(&drawing_area, desc, &desc_style, *deterministic).1 + LABEL_MARGIN
+        })
+    };
+    let (x_desc_room, y_desc_room) = (desc_room(&x_desc), desc_room(&y_desc));
+
+    // The label areas are sized to fit the labels plotters will draw, so that long values are not
+    // clipped and short ones do not waste room
+    let y_labels: Vec<String> = data_range
+        .key_points(AXIS_LABELS)
+        .iter()
+        .map(format_value)
+        .collect();
+    let x_dates = RangedDateTime::from(date_range.clone()).key_points(AXIS_LABELS);
+    let x_labels: Vec<String> = x_dates.iter().map(format_date).collect();
+    let label_sizes = |labels: &[String]| {
+        labels
+            .iter()
+            .map(|label| text_size(&drawing_area, label, &label_style, *deterministic))
+            .collect::<Vec<_>>()
+    };
+    let y_label_width = label_sizes(&y_labels)
+        .iter()
+        .map(|(width, _)| *width)
+        .max()
+        .unwrap_or(0);
+    let x_label_sizes = label_sizes(&x_labels);
+    let x_label_width = x_label_sizes
+        .iter()
+        .map(|(width, _)| *width)
+        .max()
+        .unwrap_or(0);
+    let x_label_height = x_label_sizes
+        .iter()
+        .map(|(_, height)| *height)
+        .max()
+        .unwrap_or(0);
+
+    // Date labels are only rearranged when they would overlap, going by the room between the
+    // first two on an axis as wide as the widest value label leaves
+    let date_labels = {
+        let (width, _) = drawing_area.dim_in_pixel();
+        let left =
+            (y_label_width + LABEL_DISTANCE + y_desc_room).max(x_label_width / 2) + LABEL_MARGIN;
+        let axis_width = width.saturating_sub(left + 5 + VALUE_LABEL_AREA) as f64;
+        let axis_duration = (date_end - date_start).num_seconds().max(1) as f64;
+        let spacing = match x_dates.as_slice() {
+            [first, second, ..] => {
+                (*second - *first).num_seconds() as f64 / axis_duration * axis_width
+            }
+            _ => f64::INFINITY,
+        };
+        if (x_label_width + LABEL_MARGIN) as f64 > spacing {
+            info!(
+                "Date labels are crowded, laying them out as {:?}",
+                opts.date_labels
+            );
+            opts.date_labels
+        } else {
+            DateLabels::Horizontal
+        }
+    };
+
+    // The first date label is centred on the y-axis, so half of it hangs over the y-axis' label area
+    let (x_label_overhang, x_label_area) = match date_labels {
+        DateLabels::Horizontal => (
+            x_label_sizes.first().map_or(0, |(width, _)| width / 2),
+            x_label_height,
+        ),
+        DateLabels::Rotated => (x_label_height / 2, x_label_width),
+        DateLabels::Staggered => (
+            x_label_sizes.first().map_or(0, |(width, _)| width / 2),
+            x_label_height * 2 + STAGGER_GAP,
+        ),
+    };
+    // A tight layout only keeps the room that labels hanging past the ends of the axes need
+    let label_margin = if *tight { 2 } else { LABEL_MARGIN };
+    let latest_margin = if *tight {
+        let last_label_overhang = match date_labels {
+            DateLabels::Rotated => x_label_height / 2,
+            _ => x_label_sizes.last().map_or(0, |(width, _)| width / 2),
+        };
+        chart.margin(0).margin_top(x_label_height / 2);
+        if *value_labels {
+            VALUE_LABEL_AREA
+        } else {
+            last_label_overhang + 1
+        }
+    } else {
+        chart.margin(5);
+        VALUE_LABEL_AREA
+    };
+    // Right-to-left layouts are mirrored, with the y-axis on the right and the latest dates on the
+    // left
+    let y_axis_side = if right_to_left {
+        chart.margin_left(latest_margin);
+        LabelAreaPosition::Right
+    } else {
+        chart.margin_right(latest_margin);
+        LabelAreaPosition::Left
+    };
+    chart
+        .set_label_area_size(
+            y_axis_side,
+            (y_label_width + LABEL_DISTANCE + y_desc_room).max(x_label_overhang) + label_margin,
+        )
+        .set_label_area_size(
+            LabelAreaPosition::Bottom,
+            x_label_area + LABEL_DISTANCE + x_desc_room + label_margin,
+        );
+
+    // Rearranged date labels are drawn separately, as plotters can only lay them out side by side
+    let no_label = |_: &DateTime<Utc>| String::new();
+
+    let mut chart_context = chart
+        .build_cartesian_2d(
+            DateAxis {
+                dates: RangedDateTime::from(date_range),
+                right_to_left,
+            },
+            data_range,
+        )
+        .expect("Failed to construct chart!");
+    let mut mesh = chart_context.configure_mesh();
+    mesh.label_style(label_style.clone())
+        .axis_style(theme.foreground())
+        .bold_line_style(theme.foreground().mix(0.2))
+        .light_line_style(theme.foreground().mix(0.1))
+        .x_label_formatter(if date_labels == DateLabels::Horizontal {
+            &format_date
+        } else {
+            &no_label
+        })
+        .y_label_formatter(&format_value)
+        .axis_desc_style(desc_style.clone());
+    if let Some(x_desc) = &x_desc {
+        mesh.x_desc(x_desc);
+    }
+    if let Some(y_desc) = &y_desc {
+        mesh.y_desc(y_desc);
+    }
+    match style.grid {
+        GridStyle::None => {
+            mesh.disable_mesh();
+        }
+        // Asking plotters for zero light lines on a date axis overflows, so they are hidden by
+        // making them transparent instead
+        GridStyle::Light => {
+            mesh.light_line_style(TRANSPARENT)
+                .bold_line_style(theme.foreground().mix(0.1));
+        }
+        GridStyle::Full => match minor_lines {
+            Some(0) => {
+                mesh.light_line_style(TRANSPARENT);
+            }
+            Some(minor_lines) => {
+                mesh.max_light_lines(*minor_lines);
+            }
+            None => {}
+        },
+    }
+    mesh.draw().expect("Failed to draw chart!");
+
+    if date_labels != DateLabels::Horizontal {
+        let base = drawing_area.get_base_pixel();
+        for (row, ((date, label), (_, height))) in x_dates
+            .iter()
+            .zip(&x_labels)
+            .zip(&x_label_sizes)
+            .enumerate()
+        {
+            let (x, y) = chart_context.backend_coord(&(*date, value_bounds.start));
+            let (x, y) = (x - base.0, y - base.1 + LABEL_DISTANCE as i32);
+            let text = match date_labels {
+                DateLabels::Rotated => Text::new(
+                    label.clone(),
+                    (x - *height as i32 / 2, y),
+                    label_style
+                        .transform(FontTransform::Rotate270)
+                        .pos(Pos::new(HPos::Right, VPos::Top)),
+                ),
+                _ => Text::new(
+                    label.clone(),
+                    (
+                        x,
+                        y + (row % 2) as i32 * (x_label_height + STAGGER_GAP) as i32,
+                    ),
+                    label_style.pos(Pos::new(HPos::Center, VPos::Top)),
+                ),
+            };
+            drawing_area
+                .draw(&text)
+                .expect("Failed to draw date label!");
+        }
+    }
+
+    if let Some(reference) = &reference {
+        chart.caption(
+            reference.key.clone(),
+            (
+                font,
+                style.font_size(25f64),
+                FontStyle::Italic,
+                &theme.muted(),
+            ),
+        );
+    }
+
+    if opts.shade_excluded {
+        info!("Shading excluded dates...");
+
+        // Each day is shaded from half a day before to half a day after, so that a single excluded
+        // day still shows
+        let midnight = |date: NaiveDate| Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN));
+        for range in &opts.exclude_dates {
+            let start = (midnight(range.start) - Duration::hours(12)).max(date_start);
+            let end = (midnight(range.end) + Duration::hours(12)).min(date_end);
+            if start >= end {
+                continue;
+            }
+            chart_context
+                .draw_series(std::iter::once(Rectangle::new(
+                    [(start, value_bounds.start), (end, value_bounds.end)],
+                    theme.muted().mix(0.2).filled(),
+                )))
+                .expect("Failed to draw excluded dates!");
+        }
+    }
+
+    if reference_line {
+        info!("Drawing reference line...");
+
+        let reference_point = DataPoint::from(100f64);
+        draw_dashed_path(
+            chart_context.plotting_area(),
+            &[(date_start, reference_point), (date_end, reference_point)],
+            Color::stroke_width(&theme.muted(), style.stroke_width),
+            LineStyle::Dashed
+                .dashes(style.stroke_width)
+                .expect("Dashed lines have dashes!"),
+        )
+        .expect("Failed to draw reference line!");
+    }
+
+    if let Some(goal) = goal {
+        info!("Drawing goal line...");
+
+        let goal_point = DataPoint::from(goal.value);
+
+        if let Some(shade) = goal_shade {
+            let bound = match shade {
+                GoalShade::Above => value_bounds.end,
+                GoalShade::Below => value_bounds.start,
+            };
+            chart_context
+                .draw_series(std::iter::once(Rectangle::new(
+                    [(date_start, goal_point), (date_end, bound)],
+                    GREEN_700.mix(0.1).filled(),
+                )))
+                .expect("Failed to draw goal region!");
+        }
+
+        let goal_line = [(date_start, goal_point), (date_end, goal_point)];
+        let goal_style = Color::stroke_width(&GREEN_700, style.stroke_width);
+        match goal_line_style.dashes(style.stroke_width) {
+            Some(dashes) => {
+                draw_dashed_path(
+                    chart_context.plotting_area(),
+                    &goal_line,
+                    goal_style,
+                    dashes,
+                )
+                .expect("Failed to draw goal line!");
+            }
+            None => {
+                chart_context
+                    .draw_series(std::iter::once(PathElement::new(goal_line, goal_style)))
+                    .expect("Failed to draw goal line!");
+            }
+        }
+
+        let label = goal
+            .label
+            .clone()
+            .unwrap_or_else(|| locale.message("goal", &[("value", goal.value.to_string().into())]));
+        chart_context
+            .draw_series(std::iter::once(
+                EmptyElement::at((date_start, goal_point))
+                    + Text::new(
+                        label,
+                        (
+                            if right_to_left { -8 } else { 8 },
+                            -style.font_size(26f64) as i32,
+                        ),
+                        (font, style.font_size(20f64), FontStyle::Bold)
+                            .into_font()
+                            .color(&GREEN_700)
+                            .pos(Pos::new(
+                                if right_to_left {
+                                    HPos::Right
+                                } else {
+                                    HPos::Left
+                                },
+                                VPos::Top,
+                            )),
+                    ),
+            ))
+            .expect("Failed to draw goal label!");
+    }
+
+    if opts.changepoints {
+        info!("Finding changepoints...");
+
+        let plotted = normalized_data.as_ref().unwrap_or(&data_series.1);
+        for changepoint in find_changepoints(plotted) {
+            info!(
+                "Found a shift from {} to {} on {}",
+                format_number(changepoint.before),
+                format_number(changepoint.after),
+                changepoint.date.format("%F")
+            );
+
+            draw_dashed_path(
+                chart_context.plotting_area(),
+                &[
+                    (changepoint.date, value_bounds.start),
+                    (changepoint.date, value_bounds.end),
+                ],
+                Color::stroke_width(&PURPLE_500, (style.stroke_width / 2).max(1)),
+                LineStyle::Dashed
+                    .dashes(style.stroke_width)
+                    .expect("Dashed lines have dashes!"),
+            )
+            .expect("Failed to draw changepoint!");
+
+            let label = match changepoint.change_percent() {
+                Some(percent) => format!("{:+.1}%", percent),
+                None => format_number(changepoint.after - changepoint.before),
+            };
+            chart_context
+                .draw_series(std::iter::once(
+                    EmptyElement::at((changepoint.date, value_bounds.end))
+                        + Text::new(
+                            label,
+                            (4, 4),
+                            (font, style.font_size(16f64), FontStyle::Bold)
+                                .into_font()
+                                .color(&PURPLE_500),
+                        ),
+                ))
+                .expect("Failed to draw changepoint label!");
+        }
+    }
+
+    // Holidays on the same day share a line and a label
+    let mut holidays: Vec<(NaiveDate, String)> = Vec::new();
+    for holiday in holidays_for(&opts.holidays, &data)? {
+        match holidays.last_mut() {
+            Some((date, names)) if *date == holiday.date => {
+                names.push_str(" / ");
+                names.push_str(&holiday.name);
+            }
+            _ => holidays.push((holiday.date, holiday.name)),
+        }
+    }
+    for (date, names) in holidays {
+        let date = Utc.from_utc_datetime(&date.and_time(NaiveTime::MIN));
+        if !(date_start..=date_end).contains(&date) {
+            continue;
+        }
+        info!("Marking {} on {}...", names, date.format("%F"));
+
+        draw_dashed_path(
+            chart_context.plotting_area(),
+            &[(date, value_bounds.start), (date, value_bounds.end)],
+            Color::stroke_width(&theme.muted(), 1),
+            LineStyle::Dotted
+                .dashes(style.stroke_width)
+                .expect("Dotted lines have dashes!"),
+        )
+        .expect("Failed to draw holiday!");
+        chart_context
+            .draw_series(std::iter::once(
+                EmptyElement::at((date, value_bounds.start))
+                    + Text::new(
+                        names,
+                        (4, -4),
+                        (font, style.font_size(14f64))
+                            .into_font()
+                            .color(&theme.muted())
+                            .transform(FontTransform::Rotate270)
+                            .pos(Pos::new(HPos::Left, VPos::Top)),
+                    ),
+            ))
+            .expect("Failed to draw holiday label!");
+    }
+
+    // Taken from the series as it is drawn, before it is moved into the lines
+    let latest = if opts.inline_stats {
+        LatestStats::new(normalized_data.as_ref().unwrap_or(&data_series.1))
+    } else {
+        None
+    };
+
+    let normalized_data_drawn = normalized_data.is_some();
+    let mut lines = Vec::new();
+    if let Some(normalized) = normalized_data {
+        // Combined series are named by their KPIs, as each of them is a total
+        let name = if combining {
+            locale.kpi(&data.kpi_type).into()
+        } else {
+            data_series.0
+        };
+        lines.push((
+            name,
+            normalized,
+            SeriesStyle::new(ORANGE, style.stroke_width, 0),
+        ));
+        for (index, combined) in combined.into_iter().enumerate() {
+            lines.push((
+                locale.kpi(&combined.kpi).into(),
+                combined.points,
+                SeriesStyle::new(
+                    COMBINED_COLORS[index % COMBINED_COLORS.len()],
+                    style.stroke_width,
+                    index as i32 + 1,
+                ),
+            ));
+        }
+    } else {
+        lines.push((
+            data_series.0,
+            data_series.1,
+            SeriesStyle::new(LIGHTBLUE, style.stroke_width, 0),
+        ));
+        let width = (style.stroke_width / 2).max(1);
+        for (index, (benchmark, points)) in benchmarks.into_iter().enumerate() {
+            let (color, line_style) = BENCHMARK_STYLES[index % BENCHMARK_STYLES.len()];
+            lines.push((
+                benchmark.key.into(),
+                points,
+                SeriesStyle {
+                    line_style,
+                    ..SeriesStyle::new(color, width, index as i32 + 1)
+                },
+            ));
+        }
+        // Peers are styled as benchmarks, continuing on from the export's own
+        for (index, peer) in (lines.len() - 1..).zip(peers) {
+            let (color, line_style) = BENCHMARK_STYLES[index % BENCHMARK_STYLES.len()];
+            lines.push((
+                peer.key.into(),
+                peer.points,
+                SeriesStyle {
+                    line_style,
+                    ..SeriesStyle::new(color, width, index as i32 + 1)
+                },
+            ));
+        }
+    }
+    // Rolling averages are drawn boldly over the main series, which fades into the background
+    if !style.rolling_averages.is_empty() {
+        let (name, points, main_style) = &mut lines[0];
+        let (name, points) = (name.clone(), points.clone());
+        *main_style = SeriesStyle {
+            opacity: 0.35,
+            ..SeriesStyle::new(main_style.color, (style.stroke_width / 2).max(1), -1)
+        };
+        // Drawn on top of every other series, in the order they were given
+        let order = lines.len() as i32;
+        for (index, days) in style.rolling_averages.iter().enumerate() {
+            info!("Averaging \"{}\" over {} days...", name, days);
+            let averaged = Pipeline::moving_average(*days).apply_series(&name, points.clone())?;
+            let label = locale.message("rolling-average", &[("days", (*days).into())]);
+            lines.push((
+                format!("{} ({})", name, label).into(),
+                averaged,
+                SeriesStyle::new(
+                    ROLLING_AVERAGE_COLORS[index % ROLLING_AVERAGE_COLORS.len()],
+                    style.stroke_width + 1,
+                    order + index as i32,
+                ),
+            ));
+        }
+    }
+    let mut lines: Vec<Line> = lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, (name, points, default))| {
+            let default = style.palette.series_style(index, default);
+            let series_style = style.series_style(&name, default);
+            (name, points, series_style)
+        })
+        .filter(|(_, _, series_style)| !series_style.hidden)
+        .collect();
+    // Series with the same order are drawn by name, so that the output never depends on the order
+    // they were found in
+    lines.sort_by(|(a_name, _, a_style), (b_name, _, b_style)| {
+        a_style
+            .order
+            .cmp(&b_style.order)
+            .then_with(|| a_name.cmp(b_name))
+    });
+
+    // Worked out before the lines are drawn, as drawing consumes their points
+    let labels = if *value_labels {
+        value_labels_at(&chart_context, &lines, style.font_size(18f64) as i32)
+    } else {
+        Vec::new()
+    };
+
+    let mut tooltips = Vec::new();
+    if !opts.no_tooltips {
+        let percent = if index && normalized_data_drawn {
+            "%"
+        } else {
+            ""
+        };
+        for (name, points, _) in &lines {
+            tooltips.extend(points.iter().map(|point| PointTooltip {
+                position: chart_context.backend_coord(point),
+                text: format!(
+                    "{}\n{}: {}{}",
+                    name,
+                    format_date(&point.0),
+                    point.1,
+                    percent
+                ),
+            }));
+        }
+    }
+
+    // With rolling averages drawn, a legend tells them apart from the series they average, and
+    // combined KPIs apart from each other
+    let legend = !style.rolling_averages.is_empty() || combining;
+    if legend {
+        for (name, _, series_style) in &lines {
+            let shape = Color::stroke_width(
+                &series_style.color.mix(series_style.opacity),
+                series_style.width,
+            );
+            chart_context
+                .draw_series(std::iter::empty::<PathElement<(DateTime<Utc>, DataPoint)>>())
+                .expect("Failed to add legend entry!")
+                .label(name.to_string())
+                .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], shape));
+        }
+    }
+
+    for (name, points, series_style) in lines {
+        info!("Drawing series \"{}\"...", name);
+
+        let shape = Color::stroke_width(
+            &series_style.color.mix(series_style.opacity),
+            series_style.width,
+        );
+        let dashes = series_style.line_style.dashes(series_style.width);
+        if dashes.is_none() && series_style.marker == MarkerShape::Circle {
+            // Round markers on a solid line are drawn by plotters along with the line
+            chart_context
+                .draw_series(LineSeries::new(points, shape).point_size(point_size))
+                .expect("Failed to draw data series!");
+            continue;
+        }
+        match dashes {
+            None => {
+                chart_context
+                    .draw_series(LineSeries::new(points.iter().cloned(), shape))
+                    .expect("Failed to draw data series!");
+            }
+            Some(dashes) => {
+                draw_dashed_path(chart_context.plotting_area(), &points, shape, dashes)
+                    .expect("Failed to draw data series!");
+            }
+        }
+        if point_size > 0 {
+            draw_markers(
+                chart_context.plotting_area(),
+                &points,
+                series_style.marker,
+                point_size,
+                shape,
+            )
+            .expect("Failed to draw data series markers!");
+        }
+    }
+
+    if legend {
+        chart_context
+            .configure_series_labels()
+            .position(if right_to_left {
+                SeriesLabelPosition::UpperRight
+            } else {
+                SeriesLabelPosition::UpperLeft
+            })
+            .background_style(theme.background().mix(0.8))
+            .border_style(theme.muted())
+            .label_font(
+                (font, style.font_size(16f64))
+                    .into_font()
+                    .color(&theme.foreground()),
+            )
+            .draw()
+            .expect("Failed to draw legend!");
+    }
+
+    if let Some(latest) = latest {
+        let rows = [
+            (
+                locale.message(
+                    "inline-stats-last",
+                    &[("date", format_date(&latest.last_date).into())],
+                ),
+                format_value(&latest.last.into()),
+            ),
+            (
+                locale.message("inline-stats-week-average", &[]),
+                format_value(&latest.week_average.into()),
+            ),
+            (
+                locale.message("inline-stats-month-change", &[]),
+                format_percent(latest.month_change_percent),
+            ),
+        ];
+        draw_inline_stats(
+            &chart_context.plotting_area().strip_coord_spec(),
+            &rows,
+            style,
+            *deterministic,
+            right_to_left,
+        )
+        .expect("Failed to draw inline stats!");
+    }
+
+    let base = drawing_area.get_base_pixel();
+    for ((x, y), label, color) in labels {
+        drawing_area
+            .draw(&Text::new(
+                label,
+                (x - base.0 + if right_to_left { -8 } else { 8 }, y - base.1),
+                (font, style.font_size(18f64), FontStyle::Bold)
+                    .into_font()
+                    .color(&color)
+                    .pos(Pos::new(
+                        if right_to_left {
+                            HPos::Right
+                        } else {
+                            HPos::Left
+                        },
+                        VPos::Center,
+                    )),
+            ))
+            .expect("Failed to draw value label!");
+    }
+
+    info!("Data plotted!");
+
+    // BitMapBackend will return an error when presenting when the output file extension is invalid
+    drawing_area
+        .present()
+        .map_err(|_| PlottingError::InvalidOutput)?;
+
+    Ok(DrawnChart {
+        metadata: ChartMetadata {
+            universe_id: data.universe_id,
+            kpi: data.kpi_type.to_string(),
+            date_range: (date_start, date_end),
+            transforms,
+            generated: (!deterministic).then(Utc::now),
+        },
+        tooltips,
+    })
+}
+
+impl Mul<f64> for &DataPoint {
+    type Output = f64;
+
+    fn mul(self, rhs: f64) -> Self::Output {
+        match self {
+            DataPoint::Float(value) => value.to_num::<f64>() * rhs,
+            DataPoint::Integer(value) => *value as f64 * rhs,
+            DataPoint::Zero => 0f64,
+        }
+    }
+}
+
+/// Rounds every point of a normalized series to a whole number
+pub fn quantize_data(data: Vec<(DateTime<Utc>, DataPoint)>) -> Vec<(DateTime<Utc>, DataPoint)> {
+    data.into_iter()
+        .map(|(date, point)| (date, point.round()))
+        .collect()
+}
+
+/// Divides each point of the data by its first point that is not zero, so that the series starts
+/// at 100. A series of zeroes has nothing to divide by and is left out.
+pub fn rebase_data(
+    data: &[(DateTime<Utc>, DataPoint)],
+) -> Result<Vec<(DateTime<Utc>, DataPoint)>, DataPointArithmeticError> {
+    let Some((_, base)) = data
+        .iter()
+        .filter(|(_, point)| !matches!(point, DataPoint::Zero))
+        .min_by_key(|(date, _)| *date)
+    else {
+        return Ok(Vec::new());
+    };
+    let base = f64::from(*base);
+    data.iter()
+        .map(|(date, point)| {
+            DataPoint::checked_from_f64(f64::from(*point) / base * 100f64)
+                .map(|point| (*date, point))
+        })
+        .collect()
+}
+
+/// Divides each point of the data by the reference point on the same day, as a percentage. Days
+/// on which the reference is zero are skipped.
+pub fn index_data(
+    data: &[(DateTime<Utc>, DataPoint)],
+    reference: &[(DateTime<Utc>, DataPoint)],
+) -> Result<Vec<(DateTime<Utc>, DataPoint)>, DataPointArithmeticError> {
+    reference
+        .iter()
+        .filter(|(_, reference_point)| !matches!(reference_point, DataPoint::Zero))
+        .filter_map(|(date, reference_point)| {
+            let (_, data_point) = data.iter().find(|(data_date, _)| data_date == date)?;
+            Some(
+                DataPoint::checked_from_f64(
+                    f64::from(*data_point) / f64::from(*reference_point) * 100f64,
+                )
+                .map(|point| (*date, point)),
+            )
+        })
+        .collect()
+}
+
 pub fn normalize_data(
     data: Vec<(DateTime<Utc>, DataPoint)>,
     bench: Vec<(DateTime<Utc>, DataPoint)>,
-) -> Vec<(DateTime<Utc>, DataPoint)> {
+) -> Result<Vec<(DateTime<Utc>, DataPoint)>, DataPointArithmeticError> {
     let mut result = Vec::new();
     let avg = bench
         .iter()
@@ -450,8 +2899,8 @@ pub fn normalize_data(
         let Some((_, data_point)) = data.iter().find(|(date_point, _)| date_point == &date) else {
             continue;
         };
-        result.push((date, DataPoint::from(data_point * scalar)));
+        result.push((date, DataPoint::checked_from_f64(data_point * scalar)?));
     }
 
-    result
+    Ok(result)
 }
@@ -0,0 +1,335 @@
+use crate::config::{load_config, ConfigError};
+use crate::data::{format_number, DataPointArithmeticError};
+use crate::output::{check_output, check_overwrite, write_atomically, OutputError};
+use crate::parse::{parse_analytics_file, AnalyticsData, AnalyticsParseError, MismatchError};
+use crate::style::{Style, Theme};
+use crate::warning::{self, Warning};
+use chrono::{DateTime, Duration, Utc};
+use clap::Args;
+use log::info;
+use plotters::backend::{BitMapBackend, DrawingBackend};
+use plotters::chart::ChartBuilder;
+use plotters::coord::Shift;
+use plotters::drawing::{DrawingArea, IntoDrawingArea};
+use plotters::series::LineSeries;
+use plotters::style::full_palette::LIGHTBLUE;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+use plotters::style::{Color, FontStyle, IntoFont};
+use plotters_svg::SVGBackend;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const CELL_WIDTH: u32 = 480;
+const CELL_HEIGHT: u32 = 320;
+const TITLE_HEIGHT: u32 = 70;
+
+#[derive(Args)]
+pub struct PortfolioOptions {
+    #[arg(required = true)]
+    /// The Roblox Analytics exports to compare, all of the same KPI, one chart per export
+    files: Vec<PathBuf>,
+
+    #[arg(short, long)]
+    /// The image file to write the portfolio to
+    out: PathBuf,
+
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    /// The number of charts per row
+    columns: u32,
+
+    #[arg(long)]
+    /// Draws every chart on the same y-axis scale, so that experiences can be compared by size as well as by trend
+    shared_y: bool,
+
+    #[arg(long)]
+    /// Draws exports of different KPIs side by side, warning instead of failing
+    mix_kpis: bool,
+
+    #[arg(long)]
+    /// Warns instead of failing when the exports have different granularities, such as weekly rather than daily points
+    allow_mismatch: bool,
+
+    #[arg(long)]
+    /// The config file to take experience names from, instead of the default location
+    config: Option<PathBuf>,
+
+    #[arg(short, long)]
+    /// Overwrites the output file if it already exists
+    force: bool,
+
+    #[arg(long, value_enum, default_value_t = Theme::Light)]
+    /// The color theme of the portfolio
+    theme: Theme,
+}
+
+#[derive(Debug, Error)]
+pub enum PortfolioError {
+    #[error(transparent)]
+    Parse(#[from] AnalyticsParseError),
+
+    #[error(transparent)]
+    Arithmetic(#[from] DataPointArithmeticError),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Output(#[from] OutputError),
+
+    #[error("\"{0}\" cannot be compared with the first export! {1}")]
+    Mismatch(PathBuf, MismatchError),
+
+    #[error("None of the exports contain a \"Total\" series to show!")]
+    NoCharts,
+
+    #[error("The portfolio file \"{0}\" could not be written!")]
+    UnwritableFile(PathBuf),
+}
+
+/// One experience's chart in the portfolio
+struct Panel {
+    title: String,
+    points: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl Panel {
+    /// Builds a panel from an export's "Total" series, or None if it has no data
+    fn new(data: &AnalyticsData, names: &HashMap<u64, String>) -> Option<Self> {
+        let (_, points) = data
+            .data
+            .iter()
+            .filter(|(name, _)| name.starts_with("Total"))
+            .min_by(|(a, _), (b, _)| a.cmp(b))?;
+
+        let mut points: Vec<(DateTime<Utc>, f64)> = points
+            .iter()
+            .map(|(date, point)| (*date, (*point).into()))
+            .collect();
+        if points.is_empty() {
+            return None;
+        }
+        points.sort_by_key(|(date, _)| *date);
+
+        Some(Panel {
+            title: names
+                .get(&data.universe_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Experience ID {}", data.universe_id)),
+            points,
+        })
+    }
+
+    /// The lowest and highest values of the panel
+    fn value_range(&self) -> Range<f64> {
+        self.points
+            .iter()
+            .fold(f64::MAX..f64::MIN, |range, (_, value)| {
+                range.start.min(*value)..range.end.max(*value)
+            })
+    }
+}
+
+/// Pads a value range so that lines do not run along the edges of a panel. Ranges of positive
+/// values are not padded below zero, and flat series still get a non-empty range.
+fn padded(range: Range<f64>) -> Range<f64> {
+    let span = range.end - range.start;
+    if span <= 0.0 {
+        return range.start..range.start + 1.0;
+    }
+    let start = range.start - span * 0.05;
+    let start = if range.start >= 0.0 {
+        start.max(0.0)
+    } else {
+        start
+    };
+    start..range.end + span * 0.05
+}
+
+fn draw_panel<DB: DrawingBackend>(
+    area: &DrawingArea<DB, Shift>,
+    panel: &Panel,
+    range: Range<f64>,
+    style: &Style,
+) {
+    let font = style.font_family();
+    let theme = style.theme;
+
+    let (first, last) = (panel.points[0].0, panel.points[panel.points.len() - 1].0);
+    // A single day still needs a non-empty range to be drawn
+    let last = if last > first {
+        last
+    } else {
+        first + Duration::days(1)
+    };
+
+    let mut chart = ChartBuilder::on(area)
+        .caption(
+            &panel.title,
+            (font, style.font_size(22.0), FontStyle::Bold)
+                .into_font()
+                .color(&theme.foreground()),
+        )
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(60)
+        .build_cartesian_2d(first..last, range)
+        .expect("Failed to construct panel!");
+    chart
+        .configure_mesh()
+        .x_labels(3)
+        .y_labels(5)
+        .x_label_formatter(&|date| date.format("%b %-d").to_string())
+        .y_label_formatter(&|value| format_number(*value))
+        .label_style(
+            (font, style.font_size(13.0))
+                .into_font()
+                .color(&theme.foreground()),
+        )
+        .axis_style(theme.foreground())
+        .bold_line_style(theme.foreground().mix(0.2))
+        .light_line_style(theme.foreground().mix(0.05))
+        .draw()
+        .expect("Failed to draw panel axes!");
+    chart
+        .draw_series(LineSeries::new(
+            panel.points.iter().copied(),
+            LIGHTBLUE.stroke_width(2),
+        ))
+        .expect("Failed to draw panel series!");
+}
+
+fn draw_portfolio<DB: DrawingBackend>(
+    area: DrawingArea<DB, Shift>,
+    title: &str,
+    panels: &[Panel],
+    columns: usize,
+    shared_range: Option<Range<f64>>,
+    style: &Style,
+    file: &Path,
+) -> Result<(), PortfolioError> {
+    area.fill(&style.theme.background())
+        .expect("Failed to fill drawing area!");
+    let (title_area, grid) = area.split_vertically(TITLE_HEIGHT);
+    let (width, _) = title_area.dim_in_pixel();
+    title_area
+        .draw_text(
+            title,
+            &(style.font_family(), style.font_size(36.0), FontStyle::Bold)
+                .into_font()
+                .color(&style.theme.foreground())
+                .pos(Pos::new(HPos::Center, VPos::Center)),
+            (width as i32 / 2, TITLE_HEIGHT as i32 / 2),
+        )
+        .expect("Failed to draw portfolio title!");
+
+    let rows = panels.len().div_ceil(columns);
+    for (cell, panel) in grid.split_evenly((rows, columns)).iter().zip(panels) {
+        let range = shared_range
+            .clone()
+            .unwrap_or_else(|| padded(panel.value_range()));
+        draw_panel(&cell.margin(8, 8, 8, 8), panel, range, style);
+    }
+
+    area.present()
+        .map_err(|_| PortfolioError::UnwritableFile(file.to_path_buf()))?;
+
+    Ok(())
+}
+
+impl PortfolioOptions {
+    pub fn run(&self) -> Result<(), PortfolioError> {
+        // Checked before reading the exports, so that an unusable output fails fast
+        let is_vector = check_output(&self.out)?;
+        if !self.force {
+            check_overwrite([self.out.as_path()])?;
+        }
+
+        let names = load_config(self.config.as_deref())?.experience_names();
+
+        let mut exports = Vec::new();
+        for file in &self.files {
+            info!("Reading {}...", file.display());
+            let mut data = parse_analytics_file(file)?;
+            data.add_computed_total()?;
+            exports.push((file, data));
+        }
+        // Every export is compared with the first, so that the charts share a KPI and a scale
+        let (_, first) = &exports[0];
+        for (file, data) in &exports[1..] {
+            first
+                .check_same_kpi(data, self.mix_kpis)
+                .and_then(|()| first.check_combinable(data, false, self.allow_mismatch))
+                .map_err(|e| PortfolioError::Mismatch((*file).clone(), e))?;
+        }
+
+        let mut panels = Vec::new();
+        for (file, data) in &exports {
+            match Panel::new(data, &names) {
+                Some(panel) => panels.push(panel),
+                None => warning::emit(Warning::NoTotal(file.display().to_string())),
+            }
+        }
+        if panels.is_empty() {
+            return Err(PortfolioError::NoCharts);
+        }
+
+        let shared_range = self
+            .shared_y
+            .then(|| {
+                panels
+                    .iter()
+                    .map(Panel::value_range)
+                    .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+                    .map(padded)
+            })
+            .flatten();
+
+        info!("Drawing portfolio with {} charts...", panels.len());
+
+        let title = first.kpi_type.to_string();
+        let columns = (self.columns as usize).min(panels.len());
+        let rows = panels.len().div_ceil(columns);
+        let size = (
+            CELL_WIDTH * columns as u32,
+            TITLE_HEIGHT + CELL_HEIGHT * rows as u32,
+        );
+        let style = Style {
+            theme: self.theme,
+            ..Style::default()
+        };
+
+        write_atomically(
+            &self.out,
+            |file| {
+                if is_vector {
+                    draw_portfolio(
+                        SVGBackend::new(file, size).into_drawing_area(),
+                        &title,
+                        &panels,
+                        columns,
+                        shared_range,
+                        &style,
+                        &self.out,
+                    )
+                } else {
+                    draw_portfolio(
+                        BitMapBackend::new(file, size).into_drawing_area(),
+                        &title,
+                        &panels,
+                        columns,
+                        shared_range,
+                        &style,
+                        &self.out,
+                    )
+                }
+            },
+            || PortfolioError::UnwritableFile(self.out.clone()),
+        )?;
+
+        info!("Wrote portfolio to {}", self.out.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+use crate::config::{load_config, ConfigError};
+use crate::parse::{parse_localized_analytics_file, AnalyticsData, AnalyticsParseError};
+use crate::plot::{render_bitmap, PlottingError};
+use crate::Cli;
+use clap::Parser;
+use minifb::{Key, ScaleMode, Window, WindowOptions};
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{self, TryRecvError};
+use std::thread;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PreviewError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Parse(#[from] AnalyticsParseError),
+
+    #[error(transparent)]
+    Plot(#[from] PlottingError),
+
+    #[error("Unable to open the preview window: {0}")]
+    Window(#[from] minifb::Error),
+}
+
+/// Renders the chart for the given options as 0RGB pixels, as expected by the window
+fn render(cli: &Cli, data: &AnalyticsData) -> Result<(Vec<u32>, usize, usize), PreviewError> {
+    let config = load_config(cli.plot.config.as_deref())?;
+    let mut plot = cli.plot.clone();
+    plot.apply_profile(&config)?;
+    let style = plot.resolve_style(&config);
+
+    let buffer = render_bitmap(data.clone(), &plot, &style)?
+        .chunks_exact(3)
+        .map(|pixel| u32::from_be_bytes([0, pixel[0], pixel[1], pixel[2]]))
+        .collect();
+
+    Ok((buffer, style.width as usize, style.height as usize))
+}
+
+fn prompt() {
+    print!("> ");
+    let _ = std::io::stdout().flush();
+}
+
+/// Shows the chart in a window and re-renders it as flags are entered at the prompt. Returns the
+/// arguments the user settled on once the window is closed.
+pub fn preview(cli: &Cli, args: Vec<String>) -> Result<Vec<String>, PreviewError> {
+    let in_file = cli
+        .in_file
+        .clone()
+        .expect("The input file is required when no subcommand is given");
+    let data = parse_localized_analytics_file(&in_file, cli.plot.number_locale.as_ref())?;
+
+    let mut args = args;
+    let original = args.clone();
+    let (mut buffer, mut width, mut height) = render(cli, &data)?;
+
+    let mut window = Window::new(
+        "Rasorite Preview - Press ESC to finish",
+        width,
+        height,
+        WindowOptions {
+            resize: true,
+            scale_mode: ScaleMode::AspectRatioStretch,
+            ..WindowOptions::default()
+        },
+    )?;
+    window.set_target_fps(30);
+
+    // Reading from stdin blocks, so it happens on its own thread while the window keeps updating
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    println!("Enter flags to change the chart, e.g. --theme dark --goal 300");
+    println!("\"reset\" restores the original flags, \"quit\" or ESC writes the chart and exits");
+    prompt();
+
+    while window.is_open() && !window.is_key_down(Key::Escape) {
+        match receiver.try_recv() {
+            Ok(line) => {
+                let line = line.trim();
+                let changed = match line {
+                    "" => None,
+                    "quit" | "exit" => break,
+                    "reset" => Some(original.clone()),
+                    _ => match shell_words::split(line) {
+                        Ok(words) => Some(args.iter().cloned().chain(words).collect()),
+                        Err(e) => {
+                            println!("Unable to read flags: {}", e);
+                            None
+                        }
+                    },
+                };
+
+                if let Some(changed) = changed {
+                    match Cli::try_parse_from(
+                        std::iter::once("rasorite".to_string()).chain(changed.iter().cloned()),
+                    ) {
+                        Ok(changed_cli) => match render(&changed_cli, &data) {
+                            Ok(rendered) => {
+                                (buffer, width, height) = rendered;
+                                args = changed;
+                            }
+                            Err(e) => println!("{}", e),
+                        },
+                        Err(e) => println!("{}", e.render()),
+                    }
+                }
+
+                prompt();
+            }
+            Err(TryRecvError::Empty) => {}
+            Err(TryRecvError::Disconnected) => break,
+        }
+
+        window.update_with_buffer(&buffer, width, height)?;
+    }
+
+    println!();
+
+    Ok(args)
+}
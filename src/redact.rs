@@ -0,0 +1,118 @@
+use log::{Log, Metadata, Record};
+use regex::Regex;
+use std::backtrace::{Backtrace, BacktraceStatus};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Patterns matching the credentials Rasorite handles, with what each match is replaced with
+fn patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            // Authorization headers, such as those sent to Google and Notion
+            (r"(?i)\b(bearer\s+)[\w\-.~+/]+=*", "${1}[REDACTED]"),
+            // Google access tokens outside of headers, such as in token responses
+            (r"\bya29\.[\w\-.]+", "[REDACTED]"),
+            // Notion integration tokens, in their old and new formats
+            (r"\b(secret|ntn)_[A-Za-z0-9]{20,}", "[REDACTED]"),
+            // The .ROBLOSECURITY cookie, which always starts with a warning not to share it
+            (r#"_\|WARNING:-DO-NOT-SHARE-THIS\.[^\s;"']*"#, "[REDACTED]"),
+            // Webhook URLs, anyone holding which can post to the channel behind them: Discord's and
+            // Slack's, then any other URL with a hook in it, such as Zapier's or Microsoft Teams'.
+            // Punctuation after a URL, such as the colon before an error, is left in place
+            (
+                r#"(?i)\b(https?://)?(\w+\.)?discord(app)?\.com/api/webhooks/[^\s"'<>`]*[^\s"'<>`:,.;)]"#,
+                "[REDACTED]",
+            ),
+            (
+                r#"(?i)\b(https?://)?hooks\.slack\.com/[^\s"'<>`]*[^\s"'<>`:,.;)]"#,
+                "[REDACTED]",
+            ),
+            (r#"(?i)\bhttps?://[^\s"'<>`]*hook([^\s"'<>`]*[^\s"'<>`:,.;)])?"#, "[REDACTED]"),
+            // Private keys, such as the one in a Google service account key file
+            (
+                r"-----BEGIN [A-Z ]*PRIVATE KEY-----[\s\S]*?-----END [A-Z ]*PRIVATE KEY-----",
+                "[REDACTED]",
+            ),
+        ]
+        .into_iter()
+        .map(|(pattern, replacement)| {
+            (
+                Regex::new(pattern).expect("Failed to build redaction pattern!"),
+                replacement,
+            )
+        })
+        .collect()
+    })
+}
+
+/// Replaces any credentials in the text with a placeholder, so that it is safe to paste into a bug
+/// report
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let mut text = Cow::Borrowed(text);
+    for (pattern, replacement) in patterns() {
+        if let Cow::Owned(replaced) = pattern.replace_all(&text, *replacement) {
+            text = Cow::Owned(replaced);
+        }
+    }
+    text
+}
+
+/// Wraps a logger so that every message is redacted before it is written
+pub struct RedactingLogger<L: Log>(pub L);
+
+impl<L: Log> Log for RedactingLogger<L> {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.0.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let message = record.args().to_string();
+        self.0.log(
+            &Record::builder()
+                .args(format_args!("{}", redact(&message)))
+                .metadata(record.metadata().clone())
+                .module_path(record.module_path())
+                .file(record.file())
+                .line(record.line())
+                .build(),
+        );
+    }
+
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+/// Replaces the panic hook with one that redacts panic messages before printing them
+pub fn redact_panics() {
+    std::panic::set_hook(Box::new(|info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+        let thread = std::thread::current();
+        let location = info
+            .location()
+            .map(|location| format!(" at {}", location))
+            .unwrap_or_default();
+
+        eprintln!(
+            "thread '{}' panicked{}:\n{}",
+            thread.name().unwrap_or("<unnamed>"),
+            location,
+            redact(message)
+        );
+
+        let backtrace = Backtrace::capture();
+        if backtrace.status() == BacktraceStatus::Captured {
+            eprintln!("stack backtrace:\n{}", redact(&backtrace.to_string()));
+        } else {
+            eprintln!(
+                "note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace"
+            );
+        }
+    }));
+}
@@ -0,0 +1,144 @@
+use crate::holiday::Holiday;
+use crate::output::write_atomically;
+use crate::parse::AnalyticsData;
+use crate::stats::{series_stats, SeriesStats};
+use crate::warning::Warning;
+use chrono::{DateTime, Utc};
+use log::info;
+use minijinja::{Environment, UndefinedBehavior};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+    #[error("The template \"{0}\" could not be read!")]
+    UnreadableTemplate(PathBuf),
+
+    #[error("The report \"{0}\" could not be written!")]
+    UnwritableReport(PathBuf),
+
+    #[error("Unable to render the template: {0:#}")]
+    Template(#[from] minijinja::Error),
+}
+
+#[derive(Serialize)]
+struct PointReport {
+    date: DateTime<Utc>,
+    value: f64,
+    /// The value written as it is in exports and tooltips
+    text: String,
+}
+
+#[derive(Serialize)]
+struct SeriesReport {
+    #[serde(flatten)]
+    stats: SeriesStats,
+    change: f64,
+    change_percent: Option<f64>,
+    holiday_change_percent: Option<f64>,
+    /// Every point of the series in date order
+    values: Vec<PointReport>,
+}
+
+/// Everything a report template can refer to
+#[derive(Serialize)]
+pub struct ReportContext {
+    universe_id: u64,
+    kpi: String,
+    /// The path of the rendered chart
+    chart: PathBuf,
+    /// The paths of any data exports written alongside the chart
+    exports: Vec<PathBuf>,
+    generated: DateTime<Utc>,
+    series: Vec<SeriesReport>,
+    /// Everything warned about while the chart was made, each with a `kind` and a `message`
+    warnings: Vec<Warning>,
+}
+
+impl ReportContext {
+    /// Gathers the context for an export, comparing the given holidays with other days in the
+    /// statistics of each series
+    pub fn new(
+        data: &AnalyticsData,
+        chart: &Path,
+        exports: Vec<PathBuf>,
+        holidays: &[Holiday],
+        warnings: Vec<Warning>,
+    ) -> Self {
+        let series = series_stats(data, holidays)
+            .into_iter()
+            .map(|stats| {
+                let mut values: Vec<PointReport> = data.data[stats.name.as_str()]
+                    .iter()
+                    .map(|(date, point)| PointReport {
+                        date: *date,
+                        value: (*point).into(),
+                        text: point.to_string(),
+                    })
+                    .collect();
+                values.sort_by_key(|point| point.date);
+
+                SeriesReport {
+                    change: stats.change(),
+                    change_percent: stats.change_percent(),
+                    holiday_change_percent: stats.holiday_change_percent(),
+                    stats,
+                    values,
+                }
+            })
+            .collect();
+
+        ReportContext {
+            universe_id: data.universe_id,
+            kpi: data.kpi_type.to_string(),
+            chart: chart.to_path_buf(),
+            exports,
+            generated: Utc::now(),
+            series,
+            warnings,
+        }
+    }
+}
+
+/// Formats an RFC 3339 date, as dates are passed to templates, with a strftime format string
+fn date_filter(value: String, format: Option<String>) -> Result<String, minijinja::Error> {
+    let date = DateTime::parse_from_rfc3339(&value).map_err(|e| {
+        minijinja::Error::new(minijinja::ErrorKind::InvalidOperation, "not a date").with_source(e)
+    })?;
+
+    Ok(date.format(format.as_deref().unwrap_or("%F")).to_string())
+}
+
+/// Renders a user-provided minijinja template with the report context into the given file
+pub fn render_report(
+    template: &Path,
+    output: &Path,
+    context: &ReportContext,
+) -> Result<(), ReportError> {
+    info!("Rendering report template {}...", template.display());
+
+    let source = fs::read_to_string(template)
+        .map_err(|_| ReportError::UnreadableTemplate(template.to_path_buf()))?;
+
+    let mut environment = Environment::new();
+    // Typos in variable names should fail loudly rather than produce blank reports
+    environment.set_undefined_behavior(UndefinedBehavior::Strict);
+    environment.add_filter("date", date_filter);
+
+    // Named after the file so that errors point at it
+    let name = template.display().to_string();
+    environment.add_template_owned(name.clone(), source)?;
+    let report = environment.get_template(&name)?.render(context)?;
+    let unwritable = || ReportError::UnwritableReport(output.to_path_buf());
+    write_atomically(
+        output,
+        |temporary| fs::write(temporary, report).map_err(|_| unwritable()),
+        unwritable,
+    )?;
+
+    info!("Wrote report to {}", output.display());
+
+    Ok(())
+}
@@ -0,0 +1,267 @@
+use crate::data::DataPointArithmeticError;
+use crate::digest::{format_percent, format_value, KpiDigest};
+use crate::output::{check_output, check_overwrite, write_atomically, OutputError};
+use crate::parse::{parse_analytics_file, AnalyticsData, AnalyticsParseError};
+use crate::style::{Style, Theme};
+use crate::warning::{self, Warning};
+use chrono::{DateTime, Utc};
+use clap::Args;
+use log::info;
+use plotters::backend::{BitMapBackend, DrawingBackend};
+use plotters::chart::ChartBuilder;
+use plotters::coord::Shift;
+use plotters::drawing::{DrawingArea, IntoDrawingArea};
+use plotters::element::Text;
+use plotters::series::AreaSeries;
+use plotters::style::full_palette::{GREEN_700, LIGHTBLUE, RED_700};
+use plotters::style::{Color, FontStyle, IntoFont};
+use plotters_svg::SVGBackend;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const CELL_WIDTH: u32 = 400;
+const CELL_HEIGHT: u32 = 260;
+
+#[derive(Args)]
+pub struct ScorecardOptions {
+    #[arg(required = true)]
+    /// The Roblox Analytics exports to show, one card per export
+    files: Vec<PathBuf>,
+
+    #[arg(short, long)]
+    /// The image file to write the scorecard to
+    out: PathBuf,
+
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    /// The number of cards per row
+    columns: u32,
+
+    #[arg(long, default_value_t = 7, value_parser = clap::value_parser!(u32).range(1..))]
+    /// The number of days the change is calculated over, compared with the same number of days before them
+    days: u32,
+
+    #[arg(short, long)]
+    /// Overwrites the output file if it already exists
+    force: bool,
+
+    #[arg(long, value_enum, default_value_t = Theme::Light)]
+    /// The color theme of the scorecard
+    theme: Theme,
+}
+
+#[derive(Debug, Error)]
+pub enum ScorecardError {
+    #[error(transparent)]
+    Parse(#[from] AnalyticsParseError),
+
+    #[error(transparent)]
+    Arithmetic(#[from] DataPointArithmeticError),
+
+    #[error(transparent)]
+    Output(#[from] OutputError),
+
+    #[error("None of the exports contain a \"Total\" series to show!")]
+    NoCards,
+
+    #[error("The scorecard file \"{0}\" could not be written!")]
+    UnwritableFile(PathBuf),
+}
+
+/// Everything shown on a single card
+struct Card {
+    title: String,
+    subtitle: String,
+    latest: f64,
+    change_percent: Option<f64>,
+    points: Vec<(DateTime<Utc>, f64)>,
+}
+
+impl Card {
+    /// Builds a card from an export's "Total" series, or None if it has no data
+    fn new(data: &AnalyticsData, days: u32) -> Option<Self> {
+        let (name, points) = data
+            .data
+            .iter()
+            .find(|(name, _)| name.starts_with("Total"))?;
+
+        let mut points: Vec<(DateTime<Utc>, f64)> = points
+            .iter()
+            .map(|(date, point)| (*date, (*point).into()))
+            .collect();
+        points.sort_by_key(|(date, _)| *date);
+        let (last_date, latest) = *points.last()?;
+
+        let change_percent = KpiDigest::new(data, days).and_then(|digest| {
+            digest
+                .series
+                .iter()
+                .find(|series| *series.name == **name)
+                .and_then(|series| series.change_percent())
+        });
+
+        Some(Card {
+            title: data.kpi_type.to_string(),
+            subtitle: format!(
+                "Experience {} · {} · {}d change",
+                data.universe_id,
+                last_date.format("%F"),
+                days
+            ),
+            latest,
+            change_percent,
+            points,
+        })
+    }
+}
+
+fn draw_card<DB: DrawingBackend>(area: &DrawingArea<DB, Shift>, card: &Card, style: &Style) {
+    let font = style.font_family();
+    let theme = style.theme;
+    let width = area.dim_in_pixel().0 as i32;
+
+    area.draw(&Text::new(
+        card.title.clone(),
+        (20, 16),
+        (font, style.font_size(26.0), FontStyle::Bold)
+            .into_font()
+            .color(&theme.foreground()),
+    ))
+    .expect("Failed to draw card title!");
+    area.draw(&Text::new(
+        card.subtitle.clone(),
+        (20, 50),
+        (font, style.font_size(15.0))
+            .into_font()
+            .color(&theme.muted()),
+    ))
+    .expect("Failed to draw card subtitle!");
+    area.draw(&Text::new(
+        format_value(Some(card.latest)),
+        (20, 76),
+        (font, style.font_size(56.0), FontStyle::Bold)
+            .into_font()
+            .color(&theme.foreground()),
+    ))
+    .expect("Failed to draw card value!");
+
+    let (arrow, color) = match card.change_percent {
+        Some(change) if change > 0.0 => ("▲", GREEN_700),
+        Some(change) if change < 0.0 => ("▼", RED_700),
+        _ => ("", theme.muted()),
+    };
+    area.draw(&Text::new(
+        format!("{} {}", arrow, format_percent(card.change_percent))
+            .trim()
+            .to_string(),
+        (width / 2 + 20, 96),
+        (font, style.font_size(28.0), FontStyle::Bold)
+            .into_font()
+            .color(&color),
+    ))
+    .expect("Failed to draw card change!");
+
+    let sparkline = area.margin(150, 20, 20, 20);
+    let (Some(first), Some(last)) = (card.points.first(), card.points.last()) else {
+        return;
+    };
+    let (min, max) = card
+        .points
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), (_, value)| {
+            (min.min(*value), max.max(*value))
+        });
+    // Flat series still need a non-empty range to be drawn
+    let max = if max > min { max } else { min + 1.0 };
+
+    let mut chart = ChartBuilder::on(&sparkline)
+        .build_cartesian_2d(first.0..last.0, min..max)
+        .expect("Failed to construct sparkline!");
+    chart
+        .draw_series(
+            AreaSeries::new(card.points.iter().copied(), min, LIGHTBLUE.mix(0.3))
+                .border_style(LIGHTBLUE.stroke_width(2)),
+        )
+        .expect("Failed to draw sparkline!");
+}
+
+fn draw_scorecard<DB: DrawingBackend>(
+    area: DrawingArea<DB, Shift>,
+    cards: &[Card],
+    columns: usize,
+    style: &Style,
+    file: &Path,
+) -> Result<(), ScorecardError> {
+    area.fill(&style.theme.background())
+        .expect("Failed to fill drawing area!");
+
+    let rows = cards.len().div_ceil(columns);
+    for (cell, card) in area.split_evenly((rows, columns)).iter().zip(cards) {
+        draw_card(&cell.margin(8, 8, 8, 8), card, style);
+    }
+
+    area.present()
+        .map_err(|_| ScorecardError::UnwritableFile(file.to_path_buf()))?;
+
+    Ok(())
+}
+
+impl ScorecardOptions {
+    pub fn run(&self) -> Result<(), ScorecardError> {
+        // Checked before reading the exports, so that an unusable output fails fast
+        let is_vector = check_output(&self.out)?;
+        if !self.force {
+            check_overwrite([self.out.as_path()])?;
+        }
+
+        let mut cards = Vec::new();
+        for file in &self.files {
+            let mut data = parse_analytics_file(file)?;
+            data.add_computed_total()?;
+            match Card::new(&data, self.days) {
+                Some(card) => cards.push(card),
+                None => warning::emit(Warning::NoTotal(file.display().to_string())),
+            }
+        }
+        if cards.is_empty() {
+            return Err(ScorecardError::NoCards);
+        }
+
+        info!("Drawing scorecard with {} cards...", cards.len());
+
+        let columns = (self.columns as usize).min(cards.len());
+        let rows = cards.len().div_ceil(columns);
+        let size = (CELL_WIDTH * columns as u32, CELL_HEIGHT * rows as u32);
+        let style = Style {
+            theme: self.theme,
+            ..Style::default()
+        };
+
+        write_atomically(
+            &self.out,
+            |file| {
+                if is_vector {
+                    draw_scorecard(
+                        SVGBackend::new(file, size).into_drawing_area(),
+                        &cards,
+                        columns,
+                        &style,
+                        &self.out,
+                    )
+                } else {
+                    draw_scorecard(
+                        BitMapBackend::new(file, size).into_drawing_area(),
+                        &cards,
+                        columns,
+                        &style,
+                        &self.out,
+                    )
+                }
+            },
+            || ScorecardError::UnwritableFile(self.out.clone()),
+        )?;
+
+        info!("Wrote scorecard to {}", self.out.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+use crate::config::{config_path, load_config, ChartConfig, Config, ConfigError};
+use crate::metadata::escape_xml;
+use crate::parse::parse_localized_analytics_file;
+use crate::plot::{plot_data, PlotArgs};
+use clap::{Args, Parser};
+use log::{error, info, warn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+use thiserror::Error;
+use tiny_http::{Header, Response, Server};
+
+#[derive(Args, Clone)]
+pub struct ServeOptions {
+    #[arg(long)]
+    /// The config file listing the charts to serve. Defaults to rasorite.toml in the working directory
+    config: Option<PathBuf>,
+
+    #[arg(long, default_value = "127.0.0.1")]
+    /// The address to listen on
+    host: String,
+
+    #[arg(short, long, default_value_t = 8080)]
+    /// The port to listen on
+    port: u16,
+
+    #[arg(short, long, value_name = "DIR", default_value = ".rasorite")]
+    /// The directory rendered charts are kept in
+    out: PathBuf,
+
+    #[arg(long, default_value_t = 2.0, value_name = "SECONDS")]
+    /// How often to check the inputs and config file for changes
+    interval: f64,
+}
+
+#[derive(Debug, Error)]
+pub enum ServeError {
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("The config file does not list any charts! Add them as [[charts]] entries.")]
+    NoCharts,
+
+    #[error("The polling interval must be a positive number of seconds!")]
+    InvalidInterval,
+
+    #[error("The directory \"{0}\" could not be created!")]
+    UnwritableDirectory(PathBuf),
+
+    #[error("Unable to listen on {0}: {1}")]
+    Bind(String, String),
+}
+
+struct ChartState {
+    config: ChartConfig,
+    file: PathBuf,
+    /// When the input was last modified as of the last render, or None if it has not been rendered
+    rendered: Option<Option<SystemTime>>,
+    error: Option<String>,
+}
+
+struct State {
+    charts: Vec<ChartState>,
+    /// Incremented whenever any chart is re-rendered, so that browsers know when to refresh
+    generation: u64,
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn render_chart(chart: &ChartConfig, file: &Path, config: &Config) -> Result<(), String> {
+    let mut args = PlotArgs::try_parse_from(&chart.args).map_err(|e| e.render().to_string())?;
+    let profile = args.plot.apply_profile(config).map_err(|e| e.to_string())?;
+    let style = args.plot.resolve_style(config);
+    let data = parse_localized_analytics_file(&chart.input, args.plot.number_locale.as_ref())
+        .map_err(|e| e.to_string())?;
+    if let (Some(profile), Some(name)) = (profile, &args.plot.profile) {
+        profile.check(name, &data).map_err(|e| e.to_string())?;
+    }
+
+    plot_data(data, file, &args.plot, &style).map_err(|e| e.to_string())
+}
+
+fn content_type(value: &str) -> Header {
+    Header::from_bytes(&b"Content-Type"[..], value.as_bytes()).expect("Failed to build header!")
+}
+
+impl State {
+    fn new(config: &Config, out: &Path) -> Self {
+        State {
+            charts: config
+                .charts
+                .iter()
+                .enumerate()
+                .map(|(index, chart)| ChartState {
+                    config: chart.clone(),
+                    file: out.join(format!("{}.svg", index)),
+                    rendered: None,
+                    error: None,
+                })
+                .collect(),
+            generation: 0,
+        }
+    }
+
+    /// Re-renders every chart whose input has changed since it was last rendered
+    fn refresh(&mut self, config: &Config) {
+        for chart in &mut self.charts {
+            let modified = modified(&chart.config.input);
+            if chart.rendered == Some(modified) {
+                continue;
+            }
+
+            info!("Rendering chart \"{}\"...", chart.config.name);
+
+            chart.rendered = Some(modified);
+            chart.error = render_chart(&chart.config, &chart.file, config).err();
+            if let Some(e) = &chart.error {
+                warn!("Unable to render chart \"{}\": {}", chart.config.name, e);
+            }
+            self.generation += 1;
+        }
+    }
+
+    fn page(&self) -> String {
+        let mut page = String::from(concat!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+            "<title>Rasorite</title>\n<style>\n",
+            "body { font-family: sans-serif; margin: 2em; background: #f4f4f4; }\n",
+            "section { background: white; padding: 1em; margin-bottom: 2em; }\n",
+            "img { width: 100%; }\n",
+            ".error { color: #b00020; white-space: pre-wrap; }\n",
+            "</style>\n</head>\n<body>\n<h1>Rasorite</h1>\n",
+        ));
+
+        for (index, chart) in self.charts.iter().enumerate() {
+            page.push_str(&format!(
+                "<section>\n<h2>{}</h2>\n",
+                escape_xml(&chart.config.name)
+            ));
+            match &chart.error {
+                Some(e) => page.push_str(&format!("<p class=\"error\">{}</p>\n", escape_xml(e))),
+                None => page.push_str(&format!(
+                    "<img src=\"/charts/{}.svg?v={}\" alt=\"{}\">\n",
+                    index,
+                    self.generation,
+                    escape_xml(&chart.config.name)
+                )),
+            }
+            page.push_str("</section>\n");
+        }
+
+        page.push_str(&format!(
+            concat!(
+                "<script>\nconst generation = \"{}\";\nsetInterval(async () => {{\n",
+                "  const response = await fetch(\"/generation\").catch(() => null);\n",
+                "  if (response && (await response.text()) !== generation) location.reload();\n",
+                "}}, 2000);\n</script>\n</body>\n</html>\n",
+            ),
+            self.generation
+        ));
+
+        page
+    }
+}
+
+impl ServeOptions {
+    /// Watches the inputs and config file, re-rendering charts whenever they change
+    fn watch(&self, state: Arc<Mutex<State>>, mut config: Config) {
+        let config_file = config_path(self.config.as_deref());
+        let mut config_modified = config_file.as_deref().and_then(modified);
+
+        loop {
+            thread::sleep(Duration::from_secs_f64(self.interval));
+
+            let modified = config_file.as_deref().and_then(modified);
+            if modified != config_modified {
+                config_modified = modified;
+                match load_config(self.config.as_deref()) {
+                    Ok(reloaded) => {
+                        info!("Config file changed, re-rendering all charts...");
+                        config = reloaded;
+                        let mut state = state.lock().unwrap();
+                        let generation = state.generation + 1;
+                        *state = State::new(&config, &self.out);
+                        state.generation = generation;
+                    }
+                    Err(e) => warn!("Unable to reload the config file: {}", e),
+                }
+            }
+
+            state.lock().unwrap().refresh(&config);
+        }
+    }
+
+    pub fn serve(&self) -> Result<(), ServeError> {
+        if !(self.interval > 0.0 && self.interval.is_finite()) {
+            return Err(ServeError::InvalidInterval);
+        }
+
+        let config = load_config(self.config.as_deref())?;
+        if config.charts.is_empty() {
+            return Err(ServeError::NoCharts);
+        }
+
+        fs::create_dir_all(&self.out)
+            .map_err(|_| ServeError::UnwritableDirectory(self.out.clone()))?;
+
+        let state = Arc::new(Mutex::new(State::new(&config, &self.out)));
+        state.lock().unwrap().refresh(&config);
+
+        let address = format!("{}:{}", self.host, self.port);
+        let server =
+            Server::http(&address).map_err(|e| ServeError::Bind(address.clone(), e.to_string()))?;
+
+        info!("Serving charts at http://{}/", address);
+
+        let options = Arc::new(self.clone());
+        {
+            let state = Arc::clone(&state);
+            let options = Arc::clone(&options);
+            thread::spawn(move || options.watch(state, config));
+        }
+
+        for request in server.incoming_requests() {
+            let path = request
+                .url()
+                .split('?')
+                .next()
+                .unwrap_or_default()
+                .to_string();
+            let state = state.lock().unwrap();
+
+            let result = match path.as_str() {
+                "/" => request.respond(
+                    Response::from_string(state.page())
+                        .with_header(content_type("text/html; charset=utf-8")),
+                ),
+                "/generation" => {
+                    request.respond(Response::from_string(state.generation.to_string()))
+                }
+                _ => match path
+                    .strip_prefix("/charts/")
+                    .and_then(|name| name.strip_suffix(".svg"))
+                    .and_then(|index| index.parse::<usize>().ok())
+                    .and_then(|index| state.charts.get(index))
+                    .and_then(|chart| fs::read(&chart.file).ok())
+                {
+                    Some(contents) => request.respond(
+                        Response::from_data(contents).with_header(content_type("image/svg+xml")),
+                    ),
+                    None => request.respond(Response::empty(404)),
+                },
+            };
+
+            if let Err(e) = result {
+                error!("Unable to respond to a request: {}", e);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,206 @@
+use crate::config::SheetsConfig;
+use crate::parse::AnalyticsData;
+use chrono::Utc;
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use thiserror::Error;
+
+const SCOPE: &str = "https://www.googleapis.com/auth/spreadsheets";
+const SHEETS_API: &str = "https://sheets.googleapis.com/v4/spreadsheets";
+const HEADER: [&str; 5] = ["Date", "Experience ID", "KPI", "Series", "Value"];
+
+#[derive(Debug, Error)]
+pub enum SheetsError {
+    #[error("Publishing to Google Sheets requires a [sheets] section in the config file!")]
+    MissingConfig,
+
+    #[error("The service account credentials \"{0}\" could not be read!")]
+    UnreadableCredentials(PathBuf),
+
+    #[error("The service account credentials \"{0}\" are invalid: {1}")]
+    InvalidCredentials(PathBuf, serde_json::Error),
+
+    #[error("Unable to sign the service account token: {0}")]
+    Signing(#[from] jsonwebtoken::errors::Error),
+
+    #[error("The request to Google failed: {0}")]
+    Request(String),
+}
+
+impl From<ureq::Error> for SheetsError {
+    fn from(value: ureq::Error) -> Self {
+        match value {
+            // Google explains what went wrong in the body of error responses
+            ureq::Error::Status(code, response) => SheetsError::Request(format!(
+                "{} {}",
+                code,
+                response.into_string().unwrap_or_default()
+            )),
+            // The URL is left out, as it names the spreadsheet being published to
+            ureq::Error::Transport(transport) => SheetsError::Request(match transport.message() {
+                Some(message) => format!("{}: {}", transport.kind(), message),
+                None => transport.kind().to_string(),
+            }),
+        }
+    }
+}
+
+impl From<std::io::Error> for SheetsError {
+    fn from(value: std::io::Error) -> Self {
+        SheetsError::Request(value.to_string())
+    }
+}
+
+/// The fields of a service account key file needed to authenticate
+#[derive(Deserialize)]
+struct ServiceAccount {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+/// Exchanges a signed service account token for an access token
+fn access_token(config: &SheetsConfig) -> Result<String, SheetsError> {
+    let contents = std::fs::read_to_string(&config.credentials)
+        .map_err(|_| SheetsError::UnreadableCredentials(config.credentials.clone()))?;
+    let account: ServiceAccount = serde_json::from_str(&contents)
+        .map_err(|e| SheetsError::InvalidCredentials(config.credentials.clone(), e))?;
+
+    let now = Utc::now().timestamp();
+    let assertion = jsonwebtoken::encode(
+        &Header::new(Algorithm::RS256),
+        &Claims {
+            iss: &account.client_email,
+            scope: SCOPE,
+            aud: &account.token_uri,
+            iat: now,
+            exp: now + 3600,
+        },
+        &EncodingKey::from_rsa_pem(account.private_key.as_bytes())?,
+    )?;
+
+    let response: TokenResponse = ureq::post(&account.token_uri)
+        .send_form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", &assertion),
+        ])?
+        .into_json()?;
+
+    Ok(response.access_token)
+}
+
+/// Rows are keyed by everything but the value, so that publishing the same export twice updates
+/// the existing rows instead of duplicating them
+type RowKey = (String, String, String, String);
+
+fn row_key(row: &[Value]) -> Option<RowKey> {
+    let cell = |index: usize| match row.get(index)? {
+        Value::String(value) => Some(value.clone()),
+        value => Some(value.to_string()),
+    };
+    Some((cell(0)?, cell(1)?, cell(2)?, cell(3)?))
+}
+
+/// Upserts every point of every series into the configured sheet, one row per series per date
+pub fn publish(data: &AnalyticsData, config: Option<&SheetsConfig>) -> Result<(), SheetsError> {
+    let config = config.ok_or(SheetsError::MissingConfig)?;
+
+    info!("Authenticating with Google...");
+
+    let token = access_token(config)?;
+    let authorization = format!("Bearer {}", token);
+    let range = format!(
+        "{}/{}/values/{}",
+        SHEETS_API,
+        config.spreadsheet_id,
+        urlencode(&format!("'{}'!A:E", config.sheet.replace('\'', "''")))
+    );
+
+    info!("Reading existing rows from sheet \"{}\"...", config.sheet);
+
+    let existing: Value = ureq::get(&range)
+        .set("Authorization", &authorization)
+        .query("valueRenderOption", "UNFORMATTED_VALUE")
+        .call()?
+        .into_json()?;
+
+    let mut rows: BTreeMap<RowKey, Value> = existing["values"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .skip(1)
+        .filter_map(|row| {
+            let row = row.as_array()?;
+            Some((row_key(row)?, row.get(4).cloned().unwrap_or(Value::Null)))
+        })
+        .collect();
+
+    let mut updated = 0;
+    for (series, points) in &data.data {
+        for (date, point) in points {
+            let key = (
+                date.format("%F").to_string(),
+                data.universe_id.to_string(),
+                data.kpi_type.to_string(),
+                series.to_string(),
+            );
+            rows.insert(key, json!(<f64>::from(*point)));
+            updated += 1;
+        }
+    }
+
+    let values: Vec<Value> = std::iter::once(json!(HEADER))
+        .chain(
+            rows.into_iter()
+                .map(|((date, universe_id, kpi, series), value)| {
+                    json!([date, universe_id, kpi, series, value])
+                }),
+        )
+        .collect();
+
+    info!(
+        "Writing {} rows, {} from this export...",
+        values.len() - 1,
+        updated
+    );
+
+    ureq::put(&range)
+        .set("Authorization", &authorization)
+        // Values are written as-is so that they read back identically the next time
+        .query("valueInputOption", "RAW")
+        .send_json(json!({ "values": values }))?;
+
+    info!("Published to sheet \"{}\"", config.sheet);
+
+    Ok(())
+}
+
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
@@ -0,0 +1,186 @@
+use crate::data::DataPoint;
+use crate::holiday::Holiday;
+use crate::parse::AnalyticsData;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// The hours in an average year, counting leap years
+const HOURS_PER_YEAR: f64 = 24.0 * 365.25;
+
+/// Summary statistics for a single series
+#[derive(Serialize, Clone, Debug)]
+pub struct SeriesStats {
+    pub name: String,
+    pub points: usize,
+    pub first_date: DateTime<Utc>,
+    pub last_date: DateTime<Utc>,
+    /// The value on the first date
+    pub first: f64,
+    /// The value on the last date
+    pub last: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub median: f64,
+    /// The population standard deviation
+    pub std_dev: f64,
+    pub total: f64,
+    /// The total playtime expressed in years of one person playing non-stop, for playtime exports
+    pub person_years: Option<f64>,
+    /// The mean on holidays, when a holiday calendar is given and the series covers any
+    pub holiday_mean: Option<f64>,
+    /// The mean on every other day, when a holiday calendar is given
+    pub non_holiday_mean: Option<f64>,
+}
+
+impl SeriesStats {
+    /// Calculates the statistics of a series, or None if it has no points
+    pub fn new(name: &str, points: &[(DateTime<Utc>, DataPoint)]) -> Option<Self> {
+        let mut points: Vec<(DateTime<Utc>, f64)> = points
+            .iter()
+            .map(|(date, point)| (*date, (*point).into()))
+            .collect();
+        points.sort_by_key(|(date, _)| *date);
+
+        let (first_date, first) = *points.first()?;
+        let (last_date, last) = *points.last()?;
+
+        let mut values: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
+        values.sort_by(f64::total_cmp);
+
+        let count = values.len() as f64;
+        let total: f64 = values.iter().sum();
+        let mean = total / count;
+        let median = if values.len().is_multiple_of(2) {
+            (values[values.len() / 2 - 1] + values[values.len() / 2]) / 2.0
+        } else {
+            values[values.len() / 2]
+        };
+        let variance = values
+            .iter()
+            .map(|value| (value - mean).powi(2))
+            .sum::<f64>()
+            / count;
+
+        Some(SeriesStats {
+            name: name.to_string(),
+            points: values.len(),
+            first_date,
+            last_date,
+            first,
+            last,
+            min: values[0],
+            max: values[values.len() - 1],
+            mean,
+            median,
+            std_dev: variance.sqrt(),
+            total,
+            person_years: None,
+            holiday_mean: None,
+            non_holiday_mean: None,
+        })
+    }
+
+    /// Fills in the means on and off the given holidays
+    fn compare_holidays(
+        &mut self,
+        points: &[(DateTime<Utc>, DataPoint)],
+        holidays: &HashSet<NaiveDate>,
+    ) {
+        let mean = |on_holiday: bool| {
+            let values: Vec<f64> = points
+                .iter()
+                .filter(|(date, _)| holidays.contains(&date.date_naive()) == on_holiday)
+                .map(|(_, point)| (*point).into())
+                .collect();
+            (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+        };
+        self.holiday_mean = mean(true);
+        self.non_holiday_mean = mean(false);
+    }
+
+    /// How much higher the holiday mean is than the mean on other days, as a percentage
+    pub fn holiday_change_percent(&self) -> Option<f64> {
+        match (self.holiday_mean, self.non_holiday_mean) {
+            (Some(holiday), Some(other)) if other != 0.0 => Some((holiday - other) / other * 100.0),
+            _ => None,
+        }
+    }
+
+    /// The difference between the last and first values
+    pub fn change(&self) -> f64 {
+        self.last - self.first
+    }
+
+    /// The change relative to the first value, or None if the first value is zero
+    pub fn change_percent(&self) -> Option<f64> {
+        (self.first != 0.0).then(|| self.change() / self.first * 100.0)
+    }
+}
+
+/// Calculates the statistics of every series in the data, ordered by series name, comparing the
+/// given holidays with other days if there are any
+pub fn series_stats(data: &AnalyticsData, holidays: &[Holiday]) -> Vec<SeriesStats> {
+    let mut stats: Vec<SeriesStats> = data
+        .data
+        .iter()
+        .filter_map(|(name, points)| SeriesStats::new(name, points))
+        .collect();
+    if let Some(unit) = data.playtime_unit {
+        for stats in &mut stats {
+            stats.person_years = Some(stats.total / unit.per_hour() / HOURS_PER_YEAR);
+        }
+    }
+    if !holidays.is_empty() {
+        let holidays: HashSet<NaiveDate> = holidays.iter().map(|holiday| holiday.date).collect();
+        for stats in &mut stats {
+            stats.compare_holidays(&data.data[stats.name.as_str()], &holidays);
+        }
+    }
+    stats.sort_by(|a, b| a.name.cmp(&b.name));
+    stats
+}
+
+/// The latest figures of a series, as shown in the corner of a chart with --inline-stats
+#[derive(Serialize, Clone, Debug)]
+pub struct LatestStats {
+    pub last_date: DateTime<Utc>,
+    pub last: f64,
+    /// The mean over the last 7 days, counting the last date
+    pub week_average: f64,
+    /// The change from the value 30 days before the last date, or the latest before it, as a
+    /// percentage. None if the series does not go back that far or the value was zero.
+    pub month_change_percent: Option<f64>,
+}
+
+impl LatestStats {
+    /// Calculates the latest figures of a series, or None if it has no points
+    pub fn new(points: &[(DateTime<Utc>, DataPoint)]) -> Option<Self> {
+        let mut points: Vec<(DateTime<Utc>, f64)> = points
+            .iter()
+            .map(|(date, point)| (*date, (*point).into()))
+            .collect();
+        points.sort_by_key(|(date, _)| *date);
+        let (last_date, last) = *points.last()?;
+
+        let week: Vec<f64> = points
+            .iter()
+            .filter(|(date, _)| *date > last_date - Duration::days(7))
+            .map(|(_, value)| *value)
+            .collect();
+        let month_ago = points
+            .iter()
+            .rev()
+            .find(|(date, _)| *date <= last_date - Duration::days(30))
+            .map(|(_, value)| *value)
+            .filter(|value| *value != 0.0);
+
+        Some(LatestStats {
+            last_date,
+            last,
+            week_average: week.iter().sum::<f64>() / week.len() as f64,
+            month_change_percent: month_ago.map(|before| (last - before) / before * 100.0),
+        })
+    }
+}
@@ -0,0 +1,512 @@
+use crate::data::KpiType;
+use clap::ValueEnum;
+use plotters::style::full_palette::GREY;
+use plotters::style::{FontFamily, RGBColor, BLACK, WHITE};
+use regex::Regex;
+use serde::Deserialize;
+use std::str::FromStr;
+use strum::Display;
+
+/// How much of the background mesh to draw behind the data
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum GridStyle {
+    /// No grid lines, only the axes
+    None,
+    /// Faint grid lines at labelled values only
+    Light,
+    /// Grid lines at labelled values with minor lines in between
+    Full,
+}
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+    Light,
+    Dark,
+}
+
+impl Theme {
+    pub fn background(&self) -> RGBColor {
+        match self {
+            Theme::Light => WHITE,
+            Theme::Dark => RGBColor(30, 30, 30),
+        }
+    }
+
+    /// The color used for titles, axes and labels
+    pub fn foreground(&self) -> RGBColor {
+        match self {
+            Theme::Light => BLACK,
+            Theme::Dark => RGBColor(230, 230, 230),
+        }
+    }
+
+    /// The color used for subtitles and other secondary text
+    pub fn muted(&self) -> RGBColor {
+        match self {
+            Theme::Light => GREY,
+            Theme::Dark => RGBColor(150, 150, 150),
+        }
+    }
+}
+
+/// The colors series are drawn in
+#[derive(ValueEnum, Deserialize, Display, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Palette {
+    /// A light blue analytics series over grey benchmarks
+    #[default]
+    Default,
+    /// Okabe-Ito colors, with a different dash pattern and marker shape for each series, so that
+    /// series can be told apart with any color vision and in greyscale
+    Colorblind,
+    /// Black and shades of grey for light backgrounds, with a different dash pattern and marker
+    /// shape for each series, for charts that are printed
+    Greyscale,
+}
+
+/// The Okabe-Ito colors that stay distinguishable with every common color vision deficiency,
+/// leaving out black so that they show on both themes
+pub const OKABE_ITO: [RGBColor; 7] = [
+    RGBColor(0, 114, 178),
+    RGBColor(230, 159, 0),
+    RGBColor(0, 158, 115),
+    RGBColor(213, 94, 0),
+    RGBColor(204, 121, 167),
+    RGBColor(86, 180, 233),
+    RGBColor(240, 228, 66),
+];
+
+/// The shades of grey series are drawn in with the greyscale palette, dark enough to print clearly
+pub const GREYSCALE: [RGBColor; 4] = [
+    RGBColor(0, 0, 0),
+    RGBColor(96, 96, 96),
+    RGBColor(144, 144, 144),
+    RGBColor(48, 48, 48),
+];
+
+impl Palette {
+    /// The style of the series drawn at the given position, starting from its default style. Dash
+    /// patterns and marker shapes are cycled at different lengths, so that no two of the
+    /// first series look the same even without their colors.
+    pub fn series_style(&self, index: usize, default: SeriesStyle) -> SeriesStyle {
+        const LINE_STYLES: [LineStyle; 3] =
+            [LineStyle::Solid, LineStyle::Dashed, LineStyle::Dotted];
+        const MARKERS: [MarkerShape; 5] = [
+            MarkerShape::Circle,
+            MarkerShape::Square,
+            MarkerShape::Triangle,
+            MarkerShape::Diamond,
+            MarkerShape::Cross,
+        ];
+
+        let colors: &[RGBColor] = match self {
+            Palette::Default => return default,
+            Palette::Colorblind => &OKABE_ITO,
+            Palette::Greyscale => &GREYSCALE,
+        };
+        SeriesStyle {
+            color: colors[index % colors.len()],
+            line_style: LINE_STYLES[index % LINE_STYLES.len()],
+            marker: MARKERS[index % MARKERS.len()],
+            ..default
+        }
+    }
+
+    /// Whether the palette tells series apart by their dash patterns and markers as well as their
+    /// colors
+    pub fn patterned(&self) -> bool {
+        !matches!(self, Palette::Default)
+    }
+}
+
+/// Named bundles of style settings for common destinations
+#[derive(ValueEnum, Display, Clone, Copy, PartialEq, Eq, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub enum Preset {
+    /// Large, light charts for documents and slide decks
+    Report,
+    /// Dark 16:9 charts with big text for social media posts
+    Social,
+    /// Small, dark charts with point markers for dashboards
+    Dashboard,
+    /// Faint daily active users under bold 7-day and 28-day rolling averages
+    Dau,
+    /// Large, high-contrast greyscale charts with heavy lines and big text, for printing at 300 DPI
+    Print,
+}
+
+impl Preset {
+    pub fn style(&self) -> Style {
+        match self {
+            Preset::Report => Style {
+                width: 1600,
+                height: 900,
+                grid: GridStyle::Light,
+                ..Style::default()
+            },
+            Preset::Social => Style {
+                width: 1200,
+                height: 675,
+                font_scale: 1.25,
+                theme: Theme::Dark,
+                grid: GridStyle::None,
+                ..Style::default()
+            },
+            Preset::Dashboard => Style {
+                width: 800,
+                height: 450,
+                font_scale: 0.75,
+                theme: Theme::Dark,
+                markers: true,
+                grid: GridStyle::Light,
+                ..Style::default()
+            },
+            Preset::Dau => Style {
+                grid: GridStyle::Light,
+                rolling_averages: vec![7, 28],
+                ..Style::default()
+            },
+            // 8 by 5.33 inches at 300 DPI, with text and lines scaled up beyond the default layout
+            Preset::Print => Style {
+                width: 2400,
+                height: 1600,
+                font_scale: 2.25,
+                theme: Theme::Light,
+                markers: true,
+                palette: Palette::Greyscale,
+                grid: GridStyle::Light,
+                stroke_width: 5,
+                ..Style::default()
+            },
+        }
+    }
+}
+
+/// The visual settings a chart is rendered with, resolved from a preset, the config file and the
+/// command line
+#[derive(Clone, Debug)]
+pub struct Style {
+    pub width: u32,
+    pub height: u32,
+    pub font: String,
+    pub font_scale: f64,
+    pub theme: Theme,
+    pub markers: bool,
+    pub palette: Palette,
+    pub grid: GridStyle,
+    /// The width of the main lines in pixels
+    pub stroke_width: u32,
+    /// Per-series styles from the config file, applied in order
+    pub series: Vec<SeriesRule>,
+    /// The days averaged over by each rolling average drawn over the main series, which is then
+    /// drawn faintly
+    pub rolling_averages: Vec<u32>,
+    /// The KPIs for which a fall is good news, from the config file
+    pub lower_is_better: Vec<String>,
+}
+
+impl Style {
+    pub fn font_family(&self) -> FontFamily<'_> {
+        FontFamily::from(self.font.as_str())
+    }
+
+    /// Scales a font size from the default 1200x800 layout
+    pub fn font_size(&self, size: f64) -> f64 {
+        size * self.font_scale
+    }
+
+    /// Whether a fall in the KPI is good news, such as for a KPI counting errors
+    pub fn is_lower_better(&self, kpi: &KpiType) -> bool {
+        self.lower_is_better.contains(&kpi.to_string())
+    }
+
+    /// Whether a rule hides the named series
+    pub fn is_hidden(&self, name: &str) -> bool {
+        self.series
+            .iter()
+            .rev()
+            .filter(|rule| rule.pattern.0.is_match(name))
+            .find_map(|rule| rule.hidden)
+            .unwrap_or(false)
+    }
+
+    /// The style of the named series: the given default with every matching rule applied in order
+    pub fn series_style(&self, name: &str, default: SeriesStyle) -> SeriesStyle {
+        self.series
+            .iter()
+            .filter(|rule| rule.pattern.0.is_match(name))
+            .fold(default, |mut style, rule| {
+                rule.apply(&mut style);
+                style
+            })
+    }
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Style {
+            width: 1200,
+            height: 800,
+            font: "sans-serif".to_string(),
+            font_scale: 1.0,
+            theme: Theme::Light,
+            markers: false,
+            palette: Palette::Default,
+            grid: GridStyle::Full,
+            stroke_width: 2,
+            series: Vec::new(),
+            rolling_averages: Vec::new(),
+            lower_is_better: Vec::new(),
+        }
+    }
+}
+
+/// A partial [`Style`], as found in the config file or given on the command line
+#[derive(Deserialize, Default, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct StyleOverrides {
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub font: Option<String>,
+    pub font_scale: Option<f64>,
+    pub theme: Option<Theme>,
+    pub markers: Option<bool>,
+    pub palette: Option<Palette>,
+    pub grid: Option<GridStyle>,
+    pub stroke_width: Option<u32>,
+    pub rolling_averages: Option<Vec<u32>>,
+}
+
+impl StyleOverrides {
+    pub fn apply(&self, style: &mut Style) {
+        if let Some(width) = self.width {
+            style.width = width;
+        }
+        if let Some(height) = self.height {
+            style.height = height;
+        }
+        if let Some(font) = &self.font {
+            style.font = font.clone();
+        }
+        if let Some(font_scale) = self.font_scale {
+            style.font_scale = font_scale;
+        }
+        if let Some(theme) = self.theme {
+            style.theme = theme;
+        }
+        if let Some(markers) = self.markers {
+            style.markers = markers;
+        }
+        if let Some(palette) = self.palette {
+            style.palette = palette;
+        }
+        if let Some(grid) = self.grid {
+            style.grid = grid;
+        }
+        if let Some(stroke_width) = self.stroke_width {
+            style.stroke_width = stroke_width;
+        }
+        if let Some(rolling_averages) = &self.rolling_averages {
+            style.rolling_averages = rolling_averages.clone();
+        }
+    }
+}
+
+/// How the line of a series is stroked
+#[derive(ValueEnum, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum LineStyle {
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl FromStr for LineStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <LineStyle as ValueEnum>::from_str(s, true)
+            .map_err(|_| "expected solid, dashed or dotted".to_string())
+    }
+}
+
+impl LineStyle {
+    /// The lengths of the dashes and gaps in pixels for a line of the given width, or None for a
+    /// solid line
+    pub fn dashes(&self, width: u32) -> Option<(f64, f64)> {
+        let width = width.max(1) as f64;
+        match self {
+            LineStyle::Solid => None,
+            LineStyle::Dashed => Some((6.0 * width, 4.0 * width)),
+            LineStyle::Dotted => Some((width, 2.0 * width)),
+        }
+    }
+}
+
+/// The shape of the markers drawn at each point of a series
+#[derive(ValueEnum, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum MarkerShape {
+    Circle,
+    Square,
+    Triangle,
+    Diamond,
+    Cross,
+}
+
+/// A color written as a `#rrggbb` hex string
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(try_from = "String")]
+pub struct HexColor(pub RGBColor);
+
+impl TryFrom<String> for HexColor {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("\"{}\" is not a color, expected #rrggbb", value);
+        let hex = value.strip_prefix('#').ok_or_else(invalid)?;
+        if hex.len() != 6 {
+            return Err(invalid());
+        }
+        let channel = |index: usize| {
+            hex.get(index..index + 2)
+                .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+                .ok_or_else(invalid)
+        };
+
+        Ok(HexColor(RGBColor(channel(0)?, channel(2)?, channel(4)?)))
+    }
+}
+
+/// How opaque a series is, from 0 (invisible) to 1 (opaque)
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(try_from = "f64")]
+pub struct Opacity(pub f64);
+
+impl TryFrom<f64> for Opacity {
+    type Error = String;
+
+    fn try_from(value: f64) -> Result<Self, Self::Error> {
+        if (0.0..=1.0).contains(&value) {
+            Ok(Opacity(value))
+        } else {
+            Err(format!("the opacity {} is not between 0 and 1", value))
+        }
+    }
+}
+
+impl FromStr for Opacity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<f64>()
+            .map_err(|_| "expected a number between 0 and 1".to_string())?
+            .try_into()
+    }
+}
+
+/// A regular expression matched against series names
+#[derive(Deserialize, Clone, Debug)]
+#[serde(try_from = "String")]
+pub struct SeriesPattern(pub Regex);
+
+impl TryFrom<String> for SeriesPattern {
+    type Error = regex::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Regex::new(&value).map(SeriesPattern)
+    }
+}
+
+impl SeriesPattern {
+    /// A pattern matching every series
+    pub fn any() -> Self {
+        SeriesPattern(Regex::new("").expect("Failed to build pattern!"))
+    }
+}
+
+/// How a single series is drawn
+#[derive(Clone, Copy, Debug)]
+pub struct SeriesStyle {
+    pub color: RGBColor,
+    pub width: u32,
+    pub line_style: LineStyle,
+    pub marker: MarkerShape,
+    pub opacity: f64,
+    /// Series are drawn in increasing order, so higher orders are drawn on top
+    pub order: i32,
+    pub hidden: bool,
+}
+
+impl SeriesStyle {
+    pub fn new(color: RGBColor, width: u32, order: i32) -> Self {
+        SeriesStyle {
+            color,
+            width,
+            line_style: LineStyle::Solid,
+            marker: MarkerShape::Circle,
+            opacity: 1.0,
+            order,
+            hidden: false,
+        }
+    }
+}
+
+/// Style settings for every series whose name matches a pattern, as found in the `[[series]]`
+/// entries of the config file
+#[derive(Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct SeriesRule {
+    pub pattern: SeriesPattern,
+    pub color: Option<HexColor>,
+    pub width: Option<u32>,
+    pub line_style: Option<LineStyle>,
+    pub marker: Option<MarkerShape>,
+    pub opacity: Option<Opacity>,
+    pub order: Option<i32>,
+    pub hidden: Option<bool>,
+}
+
+impl SeriesRule {
+    /// A rule that changes nothing about the series matching the pattern, or every series if none
+    /// is given
+    pub fn new(pattern: Option<SeriesPattern>) -> Self {
+        SeriesRule {
+            pattern: pattern.unwrap_or_else(SeriesPattern::any),
+            color: None,
+            width: None,
+            line_style: None,
+            marker: None,
+            opacity: None,
+            order: None,
+            hidden: None,
+        }
+    }
+
+    pub fn apply(&self, style: &mut SeriesStyle) {
+        if let Some(color) = self.color {
+            style.color = color.0;
+        }
+        if let Some(width) = self.width {
+            style.width = width;
+        }
+        if let Some(line_style) = self.line_style {
+            style.line_style = line_style;
+        }
+        if let Some(marker) = self.marker {
+            style.marker = marker;
+        }
+        if let Some(opacity) = self.opacity {
+            style.opacity = opacity.0;
+        }
+        if let Some(order) = self.order {
+            style.order = order;
+        }
+        if let Some(hidden) = self.hidden {
+            style.hidden = hidden;
+        }
+    }
+}
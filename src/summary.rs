@@ -0,0 +1,106 @@
+use crate::data::format_number;
+use crate::parse::AnalyticsData;
+use crate::redact::redact;
+use crate::stats::{series_stats, LatestStats};
+use crate::warning::Warning;
+use log::info;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// The variable GitHub Actions names the step summary file in
+pub const GITHUB_STEP_SUMMARY: &str = "GITHUB_STEP_SUMMARY";
+
+#[derive(Debug, Error)]
+pub enum SummaryError {
+    #[error("${0} is not set! --github-summary is meant to be used in a GitHub Actions workflow.")]
+    Unset(&'static str),
+
+    #[error("The step summary \"{0}\" could not be written!")]
+    UnwritableSummary(PathBuf),
+}
+
+fn format_percent(percent: Option<f64>) -> String {
+    percent.map_or_else(|| "–".to_string(), |percent| format!("{:+.1}%", percent))
+}
+
+/// The run as Markdown for a GitHub Actions step summary: the latest figures and statistics of
+/// each series, the paths of the chart and its exports for later steps to upload, and any warnings
+pub fn render_summary(
+    data: &AnalyticsData,
+    chart: &Path,
+    exports: &[PathBuf],
+    warnings: &[Warning],
+) -> String {
+    let mut markdown = format!(
+        "### {} for Experience ID {}\n\n",
+        data.kpi_type, data.universe_id
+    );
+
+    let stats = series_stats(data, &[]);
+    if let (Some(first), Some(last)) = (
+        stats.iter().map(|stats| stats.first_date).min(),
+        stats.iter().map(|stats| stats.last_date).max(),
+    ) {
+        markdown.push_str(&format!(
+            "_{} to {}_\n\n",
+            first.format("%F"),
+            last.format("%F")
+        ));
+    }
+
+    markdown.push_str("| Series | Latest | 7-day average | 30-day change | Min | Max | Mean |\n");
+    markdown.push_str("|---|---:|---:|---:|---:|---:|---:|\n");
+    for stats in &stats {
+        let latest = LatestStats::new(&data.data[stats.name.as_str()]);
+        markdown.push_str(&format!(
+            "| {} | {} | {} | {} | {} | {} | {} |\n",
+            stats.name.replace('|', "\\|"),
+            format_number(stats.last),
+            latest.as_ref().map_or_else(
+                || "–".to_string(),
+                |latest| format_number(latest.week_average)
+            ),
+            format_percent(latest.and_then(|latest| latest.month_change_percent)),
+            format_number(stats.min),
+            format_number(stats.max),
+            format_number(stats.mean)
+        ));
+    }
+
+    markdown.push_str(&format!("\n- Chart: `{}`\n", chart.display()));
+    for export in exports {
+        markdown.push_str(&format!("- Export: `{}`\n", export.display()));
+    }
+
+    if !warnings.is_empty() {
+        markdown.push_str("\n> [!WARNING]\n");
+        for warning in warnings {
+            markdown.push_str(&format!("> - {}\n", redact(&warning.to_string())));
+        }
+    }
+    markdown.push('\n');
+
+    markdown
+}
+
+/// Appends the summary to the file GitHub Actions names in $GITHUB_STEP_SUMMARY, so that the
+/// summaries of several runs in a step follow one another
+pub fn write_github_summary(summary: &str) -> Result<(), SummaryError> {
+    let file = std::env::var_os(GITHUB_STEP_SUMMARY)
+        .filter(|file| !file.is_empty())
+        .map(PathBuf::from)
+        .ok_or(SummaryError::Unset(GITHUB_STEP_SUMMARY))?;
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&file)
+        .and_then(|mut opened| opened.write_all(summary.as_bytes()))
+        .map_err(|_| SummaryError::UnwritableSummary(file.clone()))?;
+
+    info!("Wrote step summary to {}", file.display());
+
+    Ok(())
+}
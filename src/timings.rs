@@ -0,0 +1,101 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The stages recorded so far and how long each took, or None when stages are not being timed
+static STAGES: Mutex<Option<Vec<(&'static str, Duration)>>> = Mutex::new(None);
+
+fn stages() -> std::sync::MutexGuard<'static, Option<Vec<(&'static str, Duration)>>> {
+    // A panic while recording leaves the durations intact, so a poisoned lock is still usable
+    STAGES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Starts recording how long each stage of the run takes, forgetting any stages recorded before
+pub fn enable() {
+    *stages() = Some(Vec::new());
+}
+
+/// Whether stages are being timed
+pub fn enabled() -> bool {
+    stages().is_some()
+}
+
+/// A stage being timed, recorded once it is dropped
+pub struct Stage {
+    name: &'static str,
+    start: Option<Instant>,
+}
+
+impl Drop for Stage {
+    fn drop(&mut self) {
+        let Some(start) = self.start else {
+            return;
+        };
+        if let Some(stages) = stages().as_mut() {
+            stages.push((self.name, start.elapsed()));
+        }
+    }
+}
+
+/// Starts timing a stage, which lasts until the returned [`Stage`] is dropped. Does nothing unless
+/// timings are enabled, so that stages can be marked out in code that runs where there is no clock,
+/// such as in the browser.
+pub fn start(name: &'static str) -> Stage {
+    Stage {
+        name,
+        start: enabled().then(Instant::now),
+    }
+}
+
+/// Runs a stage, timing it if timings are enabled
+pub fn time<T>(name: &'static str, run: impl FnOnce() -> T) -> T {
+    let _stage = start(name);
+    run()
+}
+
+/// The recorded stages in the order they were first run, with the durations of stages run more
+/// than once added together
+pub fn recorded() -> Vec<(&'static str, Duration)> {
+    let mut totals: Vec<(&'static str, Duration)> = Vec::new();
+    for (name, duration) in stages().iter().flatten() {
+        match totals.iter_mut().find(|(total, _)| total == name) {
+            Some((_, total)) => *total += *duration,
+            None => totals.push((name, *duration)),
+        }
+    }
+    totals
+}
+
+/// A table of the recorded stages, their durations in milliseconds and their share of the total
+pub fn report() -> String {
+    let stages = recorded();
+    let total: Duration = stages.iter().map(|(_, duration)| *duration).sum();
+    let width = stages
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(std::iter::once("total".len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut report = String::new();
+    for (name, duration) in &stages {
+        let share = if total.is_zero() {
+            0.0
+        } else {
+            duration.as_secs_f64() / total.as_secs_f64() * 100.0
+        };
+        report.push_str(&format!(
+            "{:<width$}  {:>10.3} ms  {:>5.1}%\n",
+            name,
+            duration.as_secs_f64() * 1000.0,
+            share,
+        ));
+    }
+    report.push_str(&format!(
+        "{:<width$}  {:>10.3} ms\n",
+        "total",
+        total.as_secs_f64() * 1000.0,
+    ));
+    report
+}
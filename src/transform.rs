@@ -0,0 +1,431 @@
+use crate::data::{DataPoint, DataPointArithmeticError};
+use crate::parse::AnalyticsData;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A series' points in date order
+pub type Points = Vec<(DateTime<Utc>, DataPoint)>;
+
+#[derive(Debug, Error)]
+pub enum TransformError {
+    #[error("The transform \"{0}\" does not exist! Choose from fill-gaps, ma:DAYS, ema:ALPHA, resample:week, resample:month and normalize:zscore or normalize:minmax.")]
+    UnknownTransform(String),
+
+    #[error("The transform \"{0}\" was given an invalid argument: {1}!")]
+    InvalidArgument(String, String),
+
+    #[error("The pipeline is empty!")]
+    EmptyPipeline,
+
+    #[error("The script \"{0}\" could not be run: {1}")]
+    Script(PathBuf, String),
+
+    #[error("The script \"{0}\" returned an invalid series: {1}!")]
+    InvalidScriptResult(PathBuf, String),
+
+    #[error(transparent)]
+    Arithmetic(#[from] DataPointArithmeticError),
+}
+
+/// A step applied to every series between parsing an export and plotting it
+pub trait Transform: Debug + Send + Sync {
+    /// How the step is written in a pipeline and recorded in chart metadata, e.g. `ma:7`
+    fn name(&self) -> String;
+
+    /// Transforms one series, given its name and its points in date order
+    fn apply(&self, name: &str, points: Points) -> Result<Points, TransformError>;
+}
+
+/// Whether every point in the series is a whole number, so that results can be kept as whole
+/// numbers where they are meant to be counts
+fn is_whole(points: &Points) -> bool {
+    points
+        .iter()
+        .all(|(_, point)| !matches!(point, DataPoint::Float(_)))
+}
+
+/// Fills in missing days by drawing a straight line between the days either side. Series of whole
+/// numbers stay whole numbers.
+#[derive(Debug)]
+struct FillGaps;
+
+impl Transform for FillGaps {
+    fn name(&self) -> String {
+        "fill-gaps".to_string()
+    }
+
+    fn apply(&self, _: &str, points: Points) -> Result<Points, TransformError> {
+        let whole = is_whole(&points);
+        let mut filled = Vec::with_capacity(points.len());
+        for pair in points.windows(2) {
+            let ((start, from), (end, to)) = (pair[0], pair[1]);
+            filled.push((start, from));
+
+            let days = (end - start).num_days();
+            for day in 1..days {
+                let value =
+                    f64::from(from) + (f64::from(to) - f64::from(from)) * day as f64 / days as f64;
+                let point = if whole {
+                    DataPoint::from(value).round()
+                } else {
+                    DataPoint::checked_from_f64(value)?
+                };
+                filled.push((start + Duration::days(day), point));
+            }
+        }
+        filled.extend(points.last().copied());
+        Ok(filled)
+    }
+}
+
+/// Replaces each point with the mean of it and the points before it within a number of days.
+/// Points within the first window are averaged over the days available.
+#[derive(Debug)]
+struct MovingAverage(u32);
+
+impl Transform for MovingAverage {
+    fn name(&self) -> String {
+        format!("ma:{}", self.0)
+    }
+
+    fn apply(&self, _: &str, points: Points) -> Result<Points, TransformError> {
+        let window = Duration::days(self.0 as i64);
+        let mut start = 0;
+        let mut sum = 0f64;
+        let mut averaged = Vec::with_capacity(points.len());
+        for (index, (date, point)) in points.iter().enumerate() {
+            sum += f64::from(*point);
+            while *date - points[start].0 >= window {
+                sum -= f64::from(points[start].1);
+                start += 1;
+            }
+            let mean = sum / (index + 1 - start) as f64;
+            averaged.push((*date, DataPoint::checked_from_f64(mean)?));
+        }
+        Ok(averaged)
+    }
+}
+
+/// Exponential smoothing, where each point is blended with the smoothed point before it. Higher
+/// factors follow the series more closely.
+#[derive(Debug)]
+struct ExponentialAverage(f64);
+
+impl Transform for ExponentialAverage {
+    fn name(&self) -> String {
+        format!("ema:{}", self.0)
+    }
+
+    fn apply(&self, _: &str, points: Points) -> Result<Points, TransformError> {
+        let mut smoothed: Option<f64> = None;
+        points
+            .into_iter()
+            .map(|(date, point)| {
+                let value = match smoothed {
+                    Some(previous) => self.0 * f64::from(point) + (1.0 - self.0) * previous,
+                    None => point.into(),
+                };
+                smoothed = Some(value);
+                Ok((date, DataPoint::checked_from_f64(value)?))
+            })
+            .collect()
+    }
+}
+
+/// The periods a series can be resampled into
+#[derive(Clone, Copy, Debug)]
+enum Period {
+    Week,
+    Month,
+}
+
+/// Replaces the days in each week or month with their mean, dated on the first day of the period
+#[derive(Debug)]
+struct Resample(Period);
+
+impl Transform for Resample {
+    fn name(&self) -> String {
+        match self.0 {
+            Period::Week => "resample:week".to_string(),
+            Period::Month => "resample:month".to_string(),
+        }
+    }
+
+    fn apply(&self, _: &str, points: Points) -> Result<Points, TransformError> {
+        let mut periods: BTreeMap<DateTime<Utc>, (f64, u32)> = BTreeMap::new();
+        for (date, point) in points {
+            let day = date.date_naive();
+            let start = match self.0 {
+                Period::Week => day - Duration::days(day.weekday().num_days_from_monday() as i64),
+                Period::Month => day.with_day(1).unwrap_or(day),
+            };
+            let (sum, count) = periods
+                .entry(Utc.from_utc_datetime(&start.and_time(date.time())))
+                .or_default();
+            *sum += f64::from(point);
+            *count += 1;
+        }
+
+        periods
+            .into_iter()
+            .map(|(date, (sum, count))| {
+                Ok((date, DataPoint::checked_from_f64(sum / count as f64)?))
+            })
+            .collect()
+    }
+}
+
+/// The ways a series can be rescaled on its own, without a reference series
+#[derive(Clone, Copy, Debug)]
+enum Scale {
+    /// Standard deviations from the series' mean
+    ZScore,
+    /// Zero at the series' lowest point and one at its highest
+    MinMax,
+}
+
+/// Rescales a series by its own statistics, so that series of different sizes can be compared
+#[derive(Debug)]
+struct Normalize(Scale);
+
+impl Transform for Normalize {
+    fn name(&self) -> String {
+        match self.0 {
+            Scale::ZScore => "normalize:zscore".to_string(),
+            Scale::MinMax => "normalize:minmax".to_string(),
+        }
+    }
+
+    fn apply(&self, _: &str, points: Points) -> Result<Points, TransformError> {
+        let values: Vec<f64> = points.iter().map(|(_, point)| (*point).into()).collect();
+        let (offset, scale) = match self.0 {
+            Scale::ZScore => {
+                let mean = values.iter().sum::<f64>() / values.len() as f64;
+                let variance = values
+                    .iter()
+                    .map(|value| (value - mean) * (value - mean))
+                    .sum::<f64>()
+                    / values.len() as f64;
+                (mean, variance.sqrt())
+            }
+            Scale::MinMax => {
+                let min = values.iter().copied().fold(f64::INFINITY, f64::min);
+                let max = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+                (min, max - min)
+            }
+        };
+
+        points
+            .into_iter()
+            .zip(values)
+            .map(|((date, _), value)| {
+                // A flat series has nothing to scale, so it lies at zero throughout
+                let scaled = if scale > 0.0 {
+                    (value - offset) / scale
+                } else {
+                    0.0
+                };
+                Ok((date, DataPoint::checked_from_f64(scaled)?))
+            })
+            .collect()
+    }
+}
+
+/// A Rhai script defining `fn transform(name, points)`, which is given each series' name and its
+/// points as maps of `date` (e.g. "2024-03-10") and `value`, and returns the points to plot
+#[derive(Debug)]
+pub struct Script {
+    file: PathBuf,
+    engine: Engine,
+    ast: AST,
+}
+
+impl Script {
+    /// Compiles the script, so that mistakes in it are found before anything is plotted
+    pub fn load(file: &Path) -> Result<Self, TransformError> {
+        let engine = Engine::new();
+        let ast = engine
+            .compile_file(file.to_path_buf())
+            .map_err(|e| TransformError::Script(file.to_path_buf(), e.to_string()))?;
+        Ok(Script {
+            file: file.to_path_buf(),
+            engine,
+            ast,
+        })
+    }
+
+    /// Reads a point returned by the script
+    fn point(
+        &self,
+        point: Dynamic,
+        whole: bool,
+    ) -> Result<(DateTime<Utc>, DataPoint), TransformError> {
+        let invalid =
+            |reason: &str| TransformError::InvalidScriptResult(self.file.clone(), reason.into());
+
+        let mut point = point
+            .try_cast::<Map>()
+            .ok_or_else(|| invalid("every point must be a map with a date and a value"))?;
+        let date = point
+            .remove("date")
+            .and_then(|date| date.into_string().ok())
+            .and_then(|date| NaiveDate::parse_from_str(&date, "%F").ok())
+            .ok_or_else(|| invalid("every date must be written as YYYY-MM-DD"))?;
+        let value = point
+            .remove("value")
+            .and_then(|value| {
+                value
+                    .as_float()
+                    .or_else(|_| value.as_int().map(|value| value as f64))
+                    .ok()
+            })
+            .ok_or_else(|| invalid("every value must be a number"))?;
+
+        let point = if whole && value >= 0.0 && value.fract() == 0.0 {
+            DataPoint::from(value).round()
+        } else {
+            DataPoint::checked_from_f64(value)?
+        };
+        Ok((
+            Utc.from_utc_datetime(&date.and_time(Default::default())),
+            point,
+        ))
+    }
+}
+
+impl Transform for Script {
+    fn name(&self) -> String {
+        format!("script:{}", self.file.display())
+    }
+
+    fn apply(&self, name: &str, points: Points) -> Result<Points, TransformError> {
+        let whole = is_whole(&points);
+        let input: Array = points
+            .into_iter()
+            .map(|(date, point)| {
+                let mut map = Map::new();
+                map.insert("date".into(), date.format("%F").to_string().into());
+                map.insert("value".into(), f64::from(point).into());
+                map.into()
+            })
+            .collect();
+
+        let output: Array = self
+            .engine
+            .call_fn(
+                &mut Scope::new(),
+                &self.ast,
+                "transform",
+                (name.to_string(), input),
+            )
+            .map_err(|e| TransformError::Script(self.file.clone(), e.to_string()))?;
+
+        let mut points = output
+            .into_iter()
+            .map(|point| self.point(point, whole))
+            .collect::<Result<Points, _>>()?;
+        points.sort_by_key(|(date, _)| *date);
+        Ok(points)
+    }
+}
+
+/// Parses a single pipeline step, such as `ma:7`
+fn parse_transform(step: &str) -> Result<Arc<dyn Transform>, TransformError> {
+    let (name, argument) = match step.split_once(':') {
+        Some((name, argument)) => (name.trim(), Some(argument.trim())),
+        None => (step.trim(), None),
+    };
+    let invalid = |reason: &str| TransformError::InvalidArgument(step.to_string(), reason.into());
+
+    Ok(match (name, argument) {
+        ("fill-gaps", None) => Arc::new(FillGaps),
+        ("ma", Some(days)) => match days.parse::<u32>() {
+            Ok(days) if days > 0 => Arc::new(MovingAverage(days)),
+            _ => return Err(invalid("the window must be a whole number of days")),
+        },
+        ("ema", Some(alpha)) => match alpha.parse::<f64>() {
+            Ok(alpha) if alpha > 0.0 && alpha <= 1.0 => Arc::new(ExponentialAverage(alpha)),
+            _ => {
+                return Err(invalid(
+                    "the smoothing factor must be above 0 and at most 1",
+                ))
+            }
+        },
+        ("resample", Some("week")) => Arc::new(Resample(Period::Week)),
+        ("resample", Some("month")) => Arc::new(Resample(Period::Month)),
+        ("resample", _) => return Err(invalid("the period must be week or month")),
+        ("normalize", Some("zscore")) => Arc::new(Normalize(Scale::ZScore)),
+        ("normalize", Some("minmax")) => Arc::new(Normalize(Scale::MinMax)),
+        ("normalize", _) => return Err(invalid("the method must be zscore or minmax")),
+        ("fill-gaps", Some(_)) => return Err(invalid("it takes no argument")),
+        ("ma" | "ema", None) => return Err(invalid("it needs an argument")),
+        _ => return Err(TransformError::UnknownTransform(name.to_string())),
+    })
+}
+
+/// Transforms applied one after another, in the order they are written
+#[derive(Clone, Debug, Default)]
+pub struct Pipeline(Vec<Arc<dyn Transform>>);
+
+impl FromStr for Pipeline {
+    type Err = TransformError;
+
+    /// Parses comma-separated steps, e.g. `fill-gaps,ma:7,normalize:zscore`
+    fn from_str(pipeline: &str) -> Result<Self, Self::Err> {
+        let steps = pipeline
+            .split(',')
+            .filter(|step| !step.trim().is_empty())
+            .map(parse_transform)
+            .collect::<Result<Vec<_>, _>>()?;
+        if steps.is_empty() {
+            return Err(TransformError::EmptyPipeline);
+        }
+        Ok(Pipeline(steps))
+    }
+}
+
+impl Pipeline {
+    /// A pipeline of a single moving average over the given number of days
+    pub fn moving_average(days: u32) -> Self {
+        Pipeline(vec![Arc::new(MovingAverage(days))])
+    }
+
+    /// The pipeline with another step added at the end
+    pub fn then(mut self, transform: Arc<dyn Transform>) -> Self {
+        self.0.push(transform);
+        self
+    }
+
+    /// The names of the steps, as recorded in chart metadata
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|transform| transform.name()).collect()
+    }
+
+    /// Runs a single series through every step
+    pub fn apply_series(&self, name: &str, mut points: Points) -> Result<Points, TransformError> {
+        points.sort_by_key(|(date, _)| *date);
+        for transform in &self.0 {
+            if points.is_empty() {
+                break;
+            }
+            points = transform.apply(name, points)?;
+        }
+        Ok(points)
+    }
+
+    /// Runs every series in the export through every step
+    pub fn apply(&self, data: &mut AnalyticsData) -> Result<(), TransformError> {
+        for (name, points) in data.data.iter_mut() {
+            *points = self.apply_series(name, std::mem::take(points))?;
+        }
+        // A series the steps emptied, such as one a script filtered out, is left out entirely
+        data.data.retain(|_, points| !points.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,375 @@
+use crate::benchmark::BenchmarkSeries;
+use crate::config::{load_config, ConfigError};
+use crate::data::DataPoint;
+use crate::parse::{parse_analytics_file, AnalyticsData, AnalyticsParseError};
+use crate::plot::{normalize_data, plot_data, PlotOptions, PlottingError};
+use crate::watch::{unique_path, OutputFormat};
+use chrono::{DateTime, TimeDelta, Utc};
+use clap::Args;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::crossterm::ExecutableCommand;
+use ratatui::prelude::*;
+use ratatui::widgets::{
+    Axis, Block, Borders, Chart, Dataset, GraphType, List, ListItem, ListState, Paragraph,
+};
+use std::io::stdout;
+use std::path::PathBuf;
+use thiserror::Error;
+
+/// The colors series are drawn in, cycled through in order
+const SERIES_COLORS: [Color; 6] = [
+    Color::Cyan,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Green,
+    Color::Red,
+    Color::Blue,
+];
+
+/// The smallest date range that can be zoomed into
+const MIN_ZOOM_DAYS: i64 = 2;
+
+#[derive(Args)]
+pub struct TuiOptions {
+    #[arg(required = true)]
+    /// The CSV files exported from Roblox Analytics to browse
+    files: Vec<PathBuf>,
+
+    #[arg(short, long, value_name = "DIR", default_value = ".")]
+    /// The directory charts are exported to
+    out: PathBuf,
+
+    #[arg(long, default_value_t = OutputFormat::Png)]
+    /// The image format charts are exported in
+    format: OutputFormat,
+
+    #[command(flatten)]
+    plot: PlotOptions,
+}
+
+#[derive(Debug, Error)]
+pub enum TuiError {
+    #[error("Unable to draw to the terminal: {0}")]
+    Terminal(#[from] std::io::Error),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error("Unable to load \"{0}\": {1}")]
+    Parse(PathBuf, AnalyticsParseError),
+
+    #[error(transparent)]
+    Plot(#[from] PlottingError),
+
+    #[error("None of the exports contain any data!")]
+    NoData,
+}
+
+/// A single series from one of the loaded exports
+struct Series {
+    export: usize,
+    name: String,
+    points: Vec<(DateTime<Utc>, DataPoint)>,
+    visible: bool,
+}
+
+struct App {
+    exports: Vec<AnalyticsData>,
+    series: Vec<Series>,
+    list: ListState,
+    normalize: bool,
+    /// The full date range of every loaded export
+    bounds: (DateTime<Utc>, DateTime<Utc>),
+    /// The date range currently shown
+    view: (DateTime<Utc>, DateTime<Utc>),
+    status: String,
+}
+
+/// Puts the terminal and logger back into their normal state when dropped, even if drawing fails
+struct TerminalGuard(log::LevelFilter);
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = stdout().execute(LeaveAlternateScreen);
+        log::set_max_level(self.0);
+    }
+}
+
+impl App {
+    fn new(exports: Vec<AnalyticsData>) -> Result<Self, TuiError> {
+        let mut series: Vec<Series> = exports
+            .iter()
+            .enumerate()
+            .flat_map(|(export, data)| {
+                data.data.iter().map(move |(name, points)| {
+                    let mut points = points.clone();
+                    points.sort_by_key(|(date, _)| *date);
+                    Series {
+                        export,
+                        name: name.to_string(),
+                        points,
+                        visible: true,
+                    }
+                })
+            })
+            .filter(|series| !series.points.is_empty())
+            .collect();
+        series.sort_by(|a, b| a.export.cmp(&b.export).then(a.name.cmp(&b.name)));
+
+        let dates = series
+            .iter()
+            .flat_map(|series| series.points.iter())
+            .map(|(date, _)| *date);
+        let bounds = (
+            dates.clone().min().ok_or(TuiError::NoData)?,
+            dates.max().ok_or(TuiError::NoData)?,
+        );
+
+        let mut list = ListState::default();
+        list.select(Some(0));
+
+        Ok(App {
+            exports,
+            series,
+            list,
+            normalize: false,
+            bounds,
+            view: bounds,
+            status: String::new(),
+        })
+    }
+
+    fn selected(&self) -> &Series {
+        &self.series[self.list.selected().unwrap_or_default()]
+    }
+
+    fn label(&self, series: &Series) -> String {
+        let export = &self.exports[series.export];
+        format!(
+            "{} {}: {}",
+            export.universe_id, export.kpi_type, series.name
+        )
+    }
+
+    /// The points of a series as they are currently displayed, normalized against the benchmark of
+    /// its export if normalization is on and the export has one
+    fn points(&self, series: &Series) -> Vec<(DateTime<Utc>, DataPoint)> {
+        if self.normalize && BenchmarkSeries::parse(&series.name).is_none() {
+            if let Some(bench) = self.series.iter().find(|other| {
+                other.export == series.export && BenchmarkSeries::parse(&other.name).is_some()
+            }) {
+                // Series too large to normalize are shown as they are
+                if let Ok(points) = normalize_data(series.points.clone(), bench.points.clone()) {
+                    return points;
+                }
+            }
+        }
+
+        series.points.clone()
+    }
+
+    fn move_selection(&mut self, offset: isize) {
+        let selected = self.list.selected().unwrap_or_default() as isize + offset;
+        self.list.select(Some(
+            selected.clamp(0, self.series.len() as isize - 1) as usize
+        ));
+    }
+
+    /// Zooms the date range in or out around its center by the given factor
+    fn zoom(&mut self, factor: f64) {
+        let span = (self.view.1 - self.view.0).num_seconds() as f64 * factor;
+        let span = TimeDelta::seconds(span as i64)
+            .max(TimeDelta::days(MIN_ZOOM_DAYS))
+            .min(self.bounds.1 - self.bounds.0);
+        let center = self.view.0 + (self.view.1 - self.view.0) / 2;
+
+        self.view = (center - span / 2, center - span / 2 + span);
+        self.pan(0.0);
+    }
+
+    /// Moves the date range by the given fraction of its width, keeping it within the data
+    fn pan(&mut self, fraction: f64) {
+        let span = self.view.1 - self.view.0;
+        let offset = TimeDelta::seconds((span.num_seconds() as f64 * fraction) as i64);
+
+        let start = (self.view.0 + offset)
+            .max(self.bounds.0)
+            .min(self.bounds.1 - span);
+        self.view = (start, start + span);
+    }
+
+    /// Renders the export of the selected series to an image, limited to the current date range
+    fn export(&self, options: &TuiOptions) -> Result<PathBuf, TuiError> {
+        let config = load_config(options.plot.config.as_deref())?;
+        let mut plot = options.plot.clone();
+        plot.apply_profile(&config)?;
+        let style = plot.resolve_style(&config);
+
+        let mut data = self.exports[self.selected().export].clone();
+        for points in data.data.values_mut() {
+            points.retain(|(date, _)| (self.view.0..=self.view.1).contains(date));
+        }
+
+        let stem = format!(
+            "{}-{}-to-{}",
+            data.slug(),
+            self.view.0.format("%F"),
+            self.view.1.format("%F")
+        );
+        std::fs::create_dir_all(&options.out)?;
+        let out_file = unique_path(&options.out, &stem, &options.format.to_string());
+
+        plot.normalize = self.normalize;
+        plot_data(data, &out_file, &plot, &style)?;
+
+        Ok(out_file)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, footer] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(2)]).areas(frame.size());
+        let [sidebar, chart] =
+            Layout::horizontal([Constraint::Length(40), Constraint::Min(0)]).areas(main);
+
+        let items: Vec<ListItem> = self
+            .series
+            .iter()
+            .enumerate()
+            .map(|(index, series)| {
+                let marker = if series.visible { "■ " } else { "□ " };
+                ListItem::new(format!("{}{}", marker, self.label(series)))
+                    .style(Style::default().fg(SERIES_COLORS[index % SERIES_COLORS.len()]))
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title("Series"))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, sidebar, &mut self.list);
+
+        let (start, end) = self.view;
+        let shown: Vec<(usize, Vec<(f64, f64)>)> = self
+            .series
+            .iter()
+            .enumerate()
+            .filter(|(_, series)| series.visible)
+            .map(|(index, series)| {
+                let points = self
+                    .points(series)
+                    .into_iter()
+                    .filter(|(date, _)| (start..=end).contains(date))
+                    .map(|(date, point)| (date.timestamp() as f64, point.into()))
+                    .collect();
+                (index, points)
+            })
+            .collect();
+
+        let max = shown
+            .iter()
+            .flat_map(|(_, points)| points.iter().map(|(_, value)| *value))
+            .fold(0f64, f64::max)
+            * 1.1;
+        let datasets = shown
+            .iter()
+            .map(|(index, points)| {
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .graph_type(GraphType::Line)
+                    .style(Style::default().fg(SERIES_COLORS[index % SERIES_COLORS.len()]))
+                    .data(points)
+            })
+            .collect();
+
+        let middle = start + (end - start) / 2;
+        let title = if self.normalize {
+            "Chart (normalized)"
+        } else {
+            "Chart"
+        };
+        let chart_widget = Chart::new(datasets)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .x_axis(
+                Axis::default()
+                    .bounds([start.timestamp() as f64, end.timestamp() as f64])
+                    .labels(
+                        [start, middle, end]
+                            .iter()
+                            .map(|date| Span::raw(date.format("%F").to_string()))
+                            .collect(),
+                    ),
+            )
+            .y_axis(
+                Axis::default().bounds([0.0, max.max(1.0)]).labels(
+                    [0.0, max / 2.0, max]
+                        .iter()
+                        .map(|value| Span::raw(format!("{:.0}", value)))
+                        .collect(),
+                ),
+            );
+        frame.render_widget(chart_widget, chart);
+
+        let help =
+            "↑/↓ select  space toggle  n normalize  +/- zoom  ←/→ pan  0 reset  e export  q quit";
+        frame.render_widget(
+            Paragraph::new(vec![Line::raw(help), Line::raw(self.status.as_str())]),
+            footer,
+        );
+    }
+}
+
+impl TuiOptions {
+    pub fn run(&self) -> Result<(), TuiError> {
+        let exports = self
+            .files
+            .iter()
+            .map(|file| parse_analytics_file(file).map_err(|e| TuiError::Parse(file.clone(), e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut app = App::new(exports)?;
+
+        // Log output would be drawn over the interface, so it is silenced until the TUI exits
+        let _guard = TerminalGuard(log::max_level());
+        log::set_max_level(log::LevelFilter::Off);
+        enable_raw_mode()?;
+        stdout().execute(EnterAlternateScreen)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+        loop {
+            terminal.draw(|frame| app.draw(frame))?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => break,
+                KeyCode::Up | KeyCode::Char('k') => app.move_selection(-1),
+                KeyCode::Down | KeyCode::Char('j') => app.move_selection(1),
+                KeyCode::Char(' ') => {
+                    let selected = app.list.selected().unwrap_or_default();
+                    app.series[selected].visible = !app.series[selected].visible;
+                }
+                KeyCode::Char('n') => app.normalize = !app.normalize,
+                KeyCode::Char('+') | KeyCode::Char('=') => app.zoom(0.5),
+                KeyCode::Char('-') => app.zoom(2.0),
+                KeyCode::Left | KeyCode::Char('h') => app.pan(-0.25),
+                KeyCode::Right | KeyCode::Char('l') => app.pan(0.25),
+                KeyCode::Char('0') => app.view = app.bounds,
+                KeyCode::Char('e') => {
+                    app.status = match app.export(self) {
+                        Ok(file) => format!("Exported {}", file.display()),
+                        Err(e) => format!("Export failed: {}", e),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+}
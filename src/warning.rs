@@ -0,0 +1,129 @@
+use crate::data::KpiType;
+use crate::parse::Granularity;
+use crate::redact::redact;
+use log::warn;
+use serde::{Deserialize, Serialize, Serializer};
+use std::cell::RefCell;
+use strum::IntoStaticStr;
+use thiserror::Error;
+
+/// Something that did not stop an export being read or a chart being drawn, but that should be
+/// looked at before the chart is trusted
+#[derive(IntoStaticStr, Error, Clone, PartialEq, Eq, Debug)]
+#[strum(serialize_all = "kebab-case")]
+pub enum Warning {
+    #[error("Combining exports of different experiences, {0} and {1}...")]
+    DifferentExperiences(u64, u64),
+
+    #[error("Combining exports with {0} and {1} data points...")]
+    DifferentGranularities(Granularity, Granularity),
+
+    #[error("Combining exports of different KPIs, {0} and {1}...")]
+    DifferentKpis(KpiType, KpiType),
+
+    #[error("The export has no \"Total\" series, so one was computed by summing its {0} breakdown series...")]
+    ComputedTotal(usize),
+
+    #[error("The dau preset is meant for daily active users, but this export is of {0}!")]
+    PresetKpi(KpiType),
+
+    #[error("Failed to find benchmark series! Make sure you are exporting the analytics data with benchmarks. The \"View by\" option must be set to \"None\" in your analytics dashboard for benchmarks to appear.")]
+    BenchmarksMissing,
+
+    #[error("The analytics series does not go back far enough to compare with the days before, leaving out the delta badge...")]
+    DeltaBadgeTooShort,
+
+    #[error("The series \"{0}\" is missing from the {1} export, skipping...")]
+    SeriesMissing(String, KpiType),
+
+    #[error("{0} of the days of \"{1}\" have no {2} to divide by, leaving them out...")]
+    NothingToDivideBy(usize, String, KpiType),
+
+    #[error("{0} is not one of the KPIs of the profile \"{1}\"")]
+    ProfileKpi(KpiType, String),
+
+    #[error("--unit only applies to playtime exports, ignoring...")]
+    UnitIgnored,
+
+    #[error("\"{0}\" has no \"Total\" series, skipping...")]
+    NoTotal(String),
+
+    #[error("Data can only be embedded into SVG charts, skipping...")]
+    EmbedSvgOnly,
+
+    #[error("Charts can only be embedded in workbooks when rendering to PNG, skipping...")]
+    WorkbookChartNotPng,
+
+    #[error("The chart was written, but could not be opened: {0}")]
+    OpenFailed(String),
+
+    #[error("The chart was written, but could not be published to {0}: {1}")]
+    PublishFailed(&'static str, String),
+
+    #[error("Alert \"{0}\" for Experience ID {1}: {2}")]
+    AlertFired(String, u64, String),
+
+    #[error("The chart was written, but the alerting rules could not be checked: {0}")]
+    AlertCheckFailed(String),
+
+    #[error("The chart was written, but the alerts could not be sent to {0}: {1}")]
+    NotifyFailed(&'static str, String),
+}
+
+/// A warning as written to manifests and passed to report templates: a stable kebab-case `kind`
+/// to branch on, such as `benchmarks-missing`, and the message that was logged, redacted as logs are
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct WarningRecord {
+    pub kind: String,
+    pub message: String,
+}
+
+impl From<&Warning> for WarningRecord {
+    fn from(warning: &Warning) -> Self {
+        WarningRecord {
+            kind: <&str>::from(warning).to_string(),
+            message: redact(&warning.to_string()).into_owned(),
+        }
+    }
+}
+
+impl Serialize for Warning {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        WarningRecord::from(self).serialize(serializer)
+    }
+}
+
+thread_local! {
+    /// The warnings raised on this thread since collecting started, or None when not collecting
+    static COLLECTED: RefCell<Option<Vec<Warning>>> = const { RefCell::new(None) };
+}
+
+/// Logs a warning, and records it if warnings are being collected on this thread
+pub fn emit(warning: Warning) {
+    warn!("{}", warning);
+    COLLECTED.with_borrow_mut(|collected| {
+        if let Some(collected) = collected {
+            collected.push(warning);
+        }
+    });
+}
+
+/// Runs the closure, returning its result along with every warning it raised. Warnings are
+/// collected per thread, so that charts rendered side by side do not see each other's warnings.
+pub fn collect<T>(run: impl FnOnce() -> T) -> (T, Vec<Warning>) {
+    let outer = COLLECTED.replace(Some(Vec::new()));
+    let result = run();
+    let warnings = COLLECTED.replace(outer).unwrap_or_default();
+    // A collection within another still counts towards the outer one
+    COLLECTED.with_borrow_mut(|collected| {
+        if let Some(collected) = collected {
+            collected.extend(warnings.iter().cloned());
+        }
+    });
+    (result, warnings)
+}
+
+/// The warnings raised so far within the innermost [`collect`] on this thread
+pub fn collected() -> Vec<Warning> {
+    COLLECTED.with_borrow(|collected| collected.clone().unwrap_or_default())
+}
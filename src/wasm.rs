@@ -0,0 +1,19 @@
+//! Bindings for rendering charts in the browser. Build with
+//! `wasm-pack build --target web --out-dir web/pkg -- --features wasm` and see `web/index.html` for an example page.
+
+use crate::config::Config;
+use crate::parse::parse_analytics;
+use crate::plot::{render_svg, PlotArgs};
+use clap::Parser;
+use wasm_bindgen::prelude::*;
+
+/// Renders an export's CSV contents into an SVG document, plotted with the given command line
+/// flags, e.g. `["--goal", "300", "--preset", "social"]`
+#[wasm_bindgen(js_name = renderSvg)]
+pub fn render_chart_svg(csv: &str, args: Vec<String>) -> Result<String, JsError> {
+    let args = PlotArgs::try_parse_from(args).map_err(|e| JsError::new(&e.to_string()))?;
+    let style = args.plot.resolve_style(&Config::default());
+    let data = parse_analytics(csv.as_bytes())?;
+
+    Ok(render_svg(data, &args.plot, &style)?)
+}
@@ -0,0 +1,353 @@
+use crate::alert::check_rules;
+use crate::config::{load_config, ConfigError};
+use crate::gallery::write_gallery;
+use crate::notify::{emit_alerts, notify};
+use crate::parse::{
+    is_analytics_export, parse_localized_analytics_file, read_export_header, AnalyticsData,
+    AnalyticsParseError,
+};
+use crate::plot::{plot_data, PlotOptions, PlottingError};
+use crate::warning::{self, Warning};
+use chrono::NaiveDate;
+use clap::{Args, ValueEnum};
+use log::{info, warn};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+use strum::Display;
+use thiserror::Error;
+
+#[derive(ValueEnum, Display, Clone, Copy, Debug)]
+#[strum(serialize_all = "lowercase")]
+pub enum OutputFormat {
+    Png,
+    Svg,
+}
+
+#[derive(Args)]
+pub struct WatchOptions {
+    /// The directory to watch for new Roblox Analytics exports, e.g. your downloads folder
+    dir: PathBuf,
+
+    #[arg(short, long, value_name = "DIR")]
+    /// The directory to write charts to. Created if it does not exist. Defaults to the profile's output directory
+    out: Option<PathBuf>,
+
+    #[arg(long, default_value_t = OutputFormat::Png)]
+    /// The image format charts are written in
+    format: OutputFormat,
+
+    #[arg(long, default_value_t = 2.0, value_name = "SECONDS")]
+    /// How often to check the directory for new files
+    interval: f64,
+
+    #[arg(long, value_name = "DIR", conflicts_with = "delete")]
+    /// Moves each export into this directory once it has been plotted
+    archive: Option<PathBuf>,
+
+    #[arg(long)]
+    /// Deletes each export once it has been plotted
+    delete: bool,
+
+    #[arg(long)]
+    /// Also plots exports that are already in the directory when watching starts
+    existing: bool,
+
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    /// Keeps only the newest N charts of each experience and KPI, and the newest N of their exports in the archive, deleting older ones
+    keep: Option<u32>,
+
+    #[arg(long)]
+    /// Keeps an index.html thumbnail gallery of every chart in the output directory, grouped by experience and KPI
+    gallery: bool,
+
+    #[command(flatten)]
+    plot: PlotOptions,
+}
+
+#[derive(Debug, Error)]
+pub enum WatchError {
+    #[error("The directory \"{0}\" could not be read!")]
+    UnreadableDirectory(PathBuf),
+
+    #[error("The directory \"{0}\" could not be created!")]
+    UnwritableDirectory(PathBuf),
+
+    #[error(
+        "No output directory was given! Pass --out or choose a profile with an output directory."
+    )]
+    NoOutputDirectory,
+
+    #[error("The polling interval must be a positive number of seconds!")]
+    InvalidInterval,
+
+    #[error("The export \"{0}\" could not be moved to the archive!")]
+    UnarchivableFile(PathBuf),
+
+    #[error("The export \"{0}\" could not be deleted!")]
+    UndeletableFile(PathBuf),
+
+    #[error("The old file \"{0}\" could not be deleted!")]
+    UnprunableFile(PathBuf),
+
+    #[error(transparent)]
+    Config(#[from] ConfigError),
+
+    #[error(transparent)]
+    Parse(#[from] AnalyticsParseError),
+
+    #[error(transparent)]
+    Plot(#[from] PlottingError),
+}
+
+/// Lists the CSV files in a directory along with their sizes
+fn list_csv_files(dir: &Path) -> Result<HashMap<PathBuf, u64>, WatchError> {
+    let entries =
+        fs::read_dir(dir).map_err(|_| WatchError::UnreadableDirectory(dir.to_path_buf()))?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"))
+        })
+        .filter_map(|path| {
+            let metadata = fs::metadata(&path).ok()?;
+            metadata.is_file().then_some((path, metadata.len()))
+        })
+        .collect())
+}
+
+/// Picks a path in `dir` for `stem` that does not exist yet by adding a numeric suffix if needed
+pub fn unique_path(dir: &Path, stem: &str, extension: &str) -> PathBuf {
+    let mut path = dir.join(format!("{}.{}", stem, extension));
+    let mut suffix = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}.{}", stem, suffix, extension));
+        suffix += 1;
+    }
+    path
+}
+
+/// Deletes all but the `keep` most recently modified of the files
+fn prune(mut files: Vec<PathBuf>, keep: usize) -> Result<(), WatchError> {
+    files.sort_by_key(|file| {
+        Reverse(
+            fs::metadata(file)
+                .and_then(|metadata| metadata.modified())
+                .ok(),
+        )
+    });
+
+    for file in files.into_iter().skip(keep) {
+        info!("Pruning {}...", file.display());
+        fs::remove_file(&file).map_err(|_| WatchError::UnprunableFile(file.clone()))?;
+    }
+
+    Ok(())
+}
+
+impl WatchOptions {
+    /// The directory charts are written to, from --out and the chosen profile
+    fn out_dir(&self) -> Result<PathBuf, WatchError> {
+        let config = load_config(self.plot.config.as_deref())?;
+        let profile = config.profile(self.plot.profile.as_deref())?;
+
+        match (&self.out, profile) {
+            (Some(out), Some(profile)) => Ok(profile.out_path(out)),
+            (Some(out), None) => Ok(out.clone()),
+            (None, Some(profile)) => profile.out_dir.clone().ok_or(WatchError::NoOutputDirectory),
+            (None, None) => Err(WatchError::NoOutputDirectory),
+        }
+    }
+
+    /// Plots a single export into `out`, then archives or deletes it. Returns the path of the
+    /// chart, or nothing if the export is of a KPI the chosen profile leaves out.
+    fn process(&self, file: &Path, out: &Path) -> Result<Option<PathBuf>, WatchError> {
+        let config = load_config(self.plot.config.as_deref())?;
+        let mut plot = self.plot.clone();
+        let profile = plot.apply_profile(&config)?;
+        let style = plot.resolve_style(&config);
+
+        let analytics = parse_localized_analytics_file(file, plot.number_locale.as_ref())?;
+
+        if let (Some(profile), Some(name)) = (profile, &plot.profile) {
+            profile.check(name, &analytics)?;
+            if !profile.includes_kpi(&analytics) {
+                return Ok(None);
+            }
+        }
+
+        let end_date = analytics
+            .data
+            .values()
+            .flatten()
+            .map(|(date, _)| *date)
+            .max()
+            .ok_or(PlottingError::SeriesMissing)?;
+        let stem = format!("{}-{}", analytics.slug(), end_date.format("%F"));
+        let out_file = unique_path(out, &stem, &self.format.to_string());
+
+        plot_data(analytics.clone(), &out_file, &plot, &style)?;
+
+        // Alerts that cannot be checked or sent are only warned about, so that watching goes on
+        let alerts = check_rules(&config.rules, &analytics).unwrap_or_else(|e| {
+            warning::emit(Warning::AlertCheckFailed(e.to_string()));
+            Vec::new()
+        });
+        emit_alerts(&alerts);
+        for (notifier, e) in notify(&alerts, &out_file, &config.notify) {
+            warning::emit(Warning::NotifyFailed(notifier, e.to_string()));
+        }
+
+        if let Some(archive) = &self.archive {
+            fs::create_dir_all(archive)
+                .map_err(|_| WatchError::UnwritableDirectory(archive.clone()))?;
+
+            let name = file.file_name().unwrap_or_default().to_string_lossy();
+            let (stem, extension) = name.rsplit_once('.').unwrap_or((&name, "csv"));
+            let destination = unique_path(archive, stem, extension);
+
+            // Renaming fails across filesystems, in which case the export is copied instead
+            fs::rename(file, &destination)
+                .or_else(|_| fs::copy(file, &destination).and_then(|_| fs::remove_file(file)))
+                .map_err(|_| WatchError::UnarchivableFile(file.to_path_buf()))?;
+        } else if self.delete {
+            fs::remove_file(file).map_err(|_| WatchError::UndeletableFile(file.to_path_buf()))?;
+        }
+
+        if let Some(keep) = self.keep {
+            self.prune(&analytics, out, keep as usize)?;
+        }
+
+        Ok(Some(out_file))
+    }
+
+    /// Deletes all but the newest `keep` charts and archived exports of the experience and KPI
+    fn prune(&self, analytics: &AnalyticsData, out: &Path, keep: usize) -> Result<(), WatchError> {
+        // Charts are named after the export's slug and last date, so that other charts of the
+        // same experience and KPI can be told apart from anything else in the directory
+        let prefix = format!("{}-", analytics.slug());
+        let extension = self.format.to_string();
+        let charts = fs::read_dir(out)
+            .map_err(|_| WatchError::UnreadableDirectory(out.to_path_buf()))?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case(&extension))
+                    && path
+                        .file_name()
+                        .and_then(|name| name.to_str()?.strip_prefix(&prefix))
+                        .and_then(|rest| rest.get(..10))
+                        .is_some_and(|date| NaiveDate::parse_from_str(date, "%F").is_ok())
+            })
+            .collect();
+        prune(charts, keep)?;
+
+        if let Some(archive) = &self.archive {
+            let kpi = analytics.kpi_type.to_string();
+            let exports = list_csv_files(archive)?
+                .into_keys()
+                .filter(|path| {
+                    read_export_header(path).is_some_and(|(universe_id, kpi_type)| {
+                        universe_id == analytics.universe_id && kpi_type.to_string() == kpi
+                    })
+                })
+                .collect();
+            prune(exports, keep)?;
+        }
+
+        Ok(())
+    }
+
+    /// Rewrites the gallery of the output directory, if one was asked for
+    fn update_gallery(&self, out: &Path) {
+        if !self.gallery {
+            return;
+        }
+
+        let names = match load_config(self.plot.config.as_deref()) {
+            Ok(config) => config.experience_names(),
+            Err(e) => {
+                warn!("Unable to update the gallery: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = write_gallery(out, &names) {
+            warn!("Unable to update the gallery: {}", e);
+        }
+    }
+
+    /// Watches the directory until the process is stopped, plotting every new export that appears
+    pub fn watch(&self) -> Result<(), WatchError> {
+        if !(self.interval > 0.0 && self.interval.is_finite()) {
+            return Err(WatchError::InvalidInterval);
+        }
+
+        let out = self.out_dir()?;
+        fs::create_dir_all(&out).map_err(|_| WatchError::UnwritableDirectory(out.clone()))?;
+
+        // Files are only handled once, and files already present are ignored unless asked for
+        let mut handled: HashSet<PathBuf> = if self.existing {
+            HashSet::new()
+        } else {
+            list_csv_files(&self.dir)?.into_keys().collect()
+        };
+        // The size of each unhandled file at the last check, so that files still being downloaded
+        // are left alone until their size stops changing
+        let mut pending: HashMap<PathBuf, u64> = HashMap::new();
+
+        self.update_gallery(&out);
+
+        info!("Watching {} for new exports...", self.dir.display());
+
+        loop {
+            let files = list_csv_files(&self.dir)?;
+            pending.retain(|path, _| files.contains_key(path));
+            handled.retain(|path| files.contains_key(path));
+
+            for (path, size) in files {
+                if handled.contains(&path) {
+                    continue;
+                }
+
+                if size == 0 || pending.get(&path) != Some(&size) {
+                    pending.insert(path, size);
+                    continue;
+                }
+
+                pending.remove(&path);
+                handled.insert(path.clone());
+
+                if !is_analytics_export(&path) {
+                    info!(
+                        "Skipping {} as it is not an analytics export",
+                        path.display()
+                    );
+                    continue;
+                }
+
+                info!("Plotting {}...", path.display());
+
+                match self.process(&path, &out) {
+                    Ok(Some(out_file)) => {
+                        info!("Plotted {} to {}", path.display(), out_file.display());
+                        self.update_gallery(&out);
+                    }
+                    Ok(None) => info!(
+                        "Skipping {} as its KPI is not one of the profile's KPIs",
+                        path.display()
+                    ),
+                    Err(e) => warn!("Unable to plot {}: {}", path.display(), e),
+                }
+            }
+
+            thread::sleep(Duration::from_secs_f64(self.interval));
+        }
+    }
+}
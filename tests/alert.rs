@@ -0,0 +1,99 @@
+//! Tests for the alerting rules checked against exports once they are plotted
+
+use chrono::{Duration, TimeZone, Utc};
+use rasorite::alert::{check_rules, AlertRule};
+use rasorite::config::Config;
+use rasorite::parse::{parse_analytics, AnalyticsData};
+
+/// A daily active users export of two weeks, with the given value on each day of the first week
+/// and of the second
+fn export(first_week: u64, second_week: u64) -> AnalyticsData {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut csv = "Experience ID,1\n\nBreakdown,Date,Daily Active Users\n".to_string();
+    for day in 0..14 {
+        let value = if day < 7 { first_week } else { second_week };
+        csv.push_str(&format!(
+            "Total,{},{}\n",
+            (start + Duration::days(day)).format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            value
+        ));
+    }
+    parse_analytics(csv.as_bytes()).expect("Failed to parse test export!")
+}
+
+fn rules(toml: &str) -> Vec<AlertRule> {
+    toml::from_str::<Config>(toml)
+        .expect("Invalid rules!")
+        .rules
+}
+
+#[test]
+fn drops_week_over_week_fire() {
+    let rules = rules(
+        r#"
+        [[rules]]
+        name = "DAU falling"
+        kpi = "Daily Active Users"
+        drop_percent = 15
+        "#,
+    );
+
+    let alerts = check_rules(&rules, &export(1000, 800)).unwrap();
+    assert_eq!(alerts.len(), 1);
+    assert_eq!(alerts[0].rule, "DAU falling");
+    assert_eq!(alerts[0].series, "Total");
+    assert_eq!(alerts[0].change_percent, Some(-20.0));
+    assert_eq!(
+        alerts[0].message,
+        "Daily Active Users fell by 20.0% (7-day average 800 vs 1000)"
+    );
+
+    assert!(check_rules(&rules, &export(1000, 900)).unwrap().is_empty());
+    assert!(check_rules(&rules, &export(1000, 1200)).unwrap().is_empty());
+}
+
+#[test]
+fn rules_only_apply_to_their_kpi() {
+    let rules = rules(
+        r#"
+        [[rules]]
+        name = "Revenue falling"
+        kpi = "Daily Revenue"
+        drop_percent = 15
+        "#,
+    );
+
+    assert!(check_rules(&rules, &export(1000, 500)).unwrap().is_empty());
+}
+
+#[test]
+fn thresholds_fire_on_the_average() {
+    let rules = rules(
+        r#"
+        [[rules]]
+        name = "Surge"
+        rise_percent = 50
+
+        [[rules]]
+        name = "Quiet"
+        days = 3
+        below = 100
+        "#,
+    );
+
+    let fired = |data| -> Vec<String> {
+        check_rules(&rules, &data)
+            .unwrap()
+            .into_iter()
+            .map(|alert| alert.rule)
+            .collect()
+    };
+    assert_eq!(fired(export(100, 200)), ["Surge"]);
+    assert_eq!(fired(export(100, 50)), ["Quiet"]);
+    assert!(fired(export(100, 120)).is_empty());
+}
+
+#[test]
+fn no_rules_never_fire() {
+    assert!(check_rules(&[], &export(1000, 1)).unwrap().is_empty());
+}
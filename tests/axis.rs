@@ -0,0 +1,52 @@
+//! Tests for the value range of the y-axis
+
+use clap::Parser;
+use rasorite::config::Config;
+use rasorite::parse::{parse_analytics, AnalyticsData};
+use rasorite::plot::{render_svg, PlotArgs, PlottingError};
+
+fn render(data: AnalyticsData, args: &[&str]) -> Result<String, PlottingError> {
+    let args = PlotArgs::try_parse_from(["--deterministic"].iter().chain(args))
+        .expect("Invalid plot flags!");
+    let style = args.plot.resolve_style(&Config::default());
+    render_svg(data, &args.plot, &style)
+}
+
+fn constant_export(days: u32, value: u64) -> AnalyticsData {
+    let mut csv = "Experience ID,1\n\nBreakdown,Date,Daily Active Users\n".to_string();
+    for day in 1..=days {
+        csv.push_str(&format!(
+            "Total,2024-03-{:02}T00:00:00.000Z,{}\n",
+            day, value
+        ));
+    }
+    parse_analytics(csv.as_bytes()).unwrap()
+}
+
+#[test]
+fn constant_exports_are_charted() {
+    render(constant_export(14, 250), &[]).expect("Failed to render constant export!");
+    render(constant_export(14, 0), &[]).expect("Failed to render export of zeroes!");
+}
+
+#[test]
+fn single_points_are_charted() {
+    render(constant_export(1, 250), &[]).expect("Failed to render single point!");
+}
+
+#[test]
+fn constant_exports_take_a_single_bound() {
+    render(constant_export(14, 250), &["--y-from-zero"]).expect("Failed to render from zero!");
+    render(constant_export(14, 250), &["--y-max", "300"]).expect("Failed to render to 300!");
+}
+
+#[test]
+fn impossible_bounds_are_rejected() {
+    assert!(matches!(
+        render(
+            constant_export(14, 250),
+            &["--y-min", "500", "--y-max", "100"]
+        ),
+        Err(PlottingError::InvalidAxisBounds)
+    ));
+}
@@ -0,0 +1,45 @@
+//! Tests for finding shifts in the level of a series
+
+use chrono::{Duration, TimeZone, Utc};
+use rasorite::changepoint::find_changepoints;
+use rasorite::data::DataPoint;
+
+/// A daily series starting on the first of March, with a weekend peak every week
+fn series(levels: &[(usize, i64)]) -> Vec<(chrono::DateTime<Utc>, DataPoint)> {
+    let start = Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap();
+    levels
+        .iter()
+        .flat_map(|(days, level)| std::iter::repeat_n(*level, *days))
+        .enumerate()
+        .map(|(day, level)| {
+            let weekend = if day % 7 >= 5 { 40 } else { 0 };
+            let noise = [0, 3, -2, 5, -4, 1, -1][day % 7] * (day as i64 % 3);
+            (
+                start + Duration::days(day as i64),
+                DataPoint::Integer((level + weekend + noise) as u64),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn finds_a_lasting_shift() {
+    let changepoints = find_changepoints(&series(&[(28, 300), (28, 450)]));
+
+    assert_eq!(changepoints.len(), 1);
+    assert_eq!(
+        changepoints[0].date,
+        Utc.with_ymd_and_hms(2024, 3, 29, 0, 0, 0).unwrap()
+    );
+    assert!(changepoints[0].after > changepoints[0].before);
+}
+
+#[test]
+fn ignores_weekly_peaks() {
+    assert!(find_changepoints(&series(&[(56, 300)])).is_empty());
+}
+
+#[test]
+fn ignores_short_series() {
+    assert!(find_changepoints(&series(&[(5, 300), (5, 900)])).is_empty());
+}
@@ -0,0 +1,83 @@
+//! Tests for charting several KPIs together, indexed to their first day
+
+mod common;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use clap::Parser;
+use common::{fixture, fixture_path};
+use rasorite::config::Config;
+use rasorite::data::{DataPoint, KpiType};
+use rasorite::parse::AnalyticsData;
+use rasorite::plot::{rebase_data, render_svg, PlotArgs, PlottingError};
+
+fn day(offset: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(offset)
+}
+
+fn render(data: AnalyticsData, args: &[&str]) -> Result<String, PlottingError> {
+    let args = PlotArgs::try_parse_from(["--deterministic"].iter().chain(args))
+        .expect("Invalid plot flags!");
+    let style = args.plot.resolve_style(&Config::default());
+    render_svg(data, &args.plot, &style)
+}
+
+#[test]
+fn series_start_at_100() {
+    let points = [
+        (day(0), DataPoint::Integer(200)),
+        (day(1), DataPoint::Integer(300)),
+        (day(2), DataPoint::Integer(100)),
+    ];
+
+    assert_eq!(
+        rebase_data(&points).unwrap(),
+        [
+            (day(0), DataPoint::from(100f64)),
+            (day(1), DataPoint::from(150f64)),
+            (day(2), DataPoint::from(50f64)),
+        ]
+    );
+}
+
+#[test]
+fn leading_zeroes_are_not_divided_by() {
+    let points = [
+        (day(0), DataPoint::Zero),
+        (day(1), DataPoint::Integer(40)),
+        (day(2), DataPoint::Integer(10)),
+    ];
+
+    assert_eq!(
+        rebase_data(&points).unwrap(),
+        [
+            (day(0), DataPoint::from(0f64)),
+            (day(1), DataPoint::from(100f64)),
+            (day(2), DataPoint::from(25f64)),
+        ]
+    );
+}
+
+#[test]
+fn series_of_zeroes_are_left_out() {
+    assert!(
+        rebase_data(&[(day(0), DataPoint::Zero), (day(1), DataPoint::Zero)])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn each_kpi_is_combined_once() {
+    let revenue = fixture_path("revenue.csv");
+    let revenue = revenue.to_str().unwrap();
+
+    assert!(render(fixture("analytics.csv"), &["--combine", revenue]).is_ok());
+    assert!(matches!(
+        render(fixture("analytics.csv"), &["--combine", revenue, revenue]),
+        Err(PlottingError::CombinedKpiRepeated(KpiType::DailyRevenue))
+    ));
+    assert!(matches!(
+        render(fixture("revenue.csv"), &["--combine", revenue]),
+        Err(PlottingError::CombinedKpiRepeated(KpiType::DailyRevenue))
+    ));
+}
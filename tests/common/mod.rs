@@ -0,0 +1,18 @@
+//! Helpers shared by the integration tests. Each test file only uses some of them.
+#![allow(dead_code)]
+
+use rasorite::parse::{parse_analytics_file, AnalyticsData};
+use std::path::{Path, PathBuf};
+
+/// The path of the named export in `tests/fixtures`
+pub fn fixture_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Parses the named export in `tests/fixtures`
+pub fn fixture(name: &str) -> AnalyticsData {
+    parse_analytics_file(&fixture_path(name)).expect("Failed to parse fixture!")
+}
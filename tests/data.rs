@@ -0,0 +1,77 @@
+//! Tests for finding the ranges the axes of a chart span
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use plotters::coord::ranged1d::Ranged;
+use rasorite::data::{get_data_range, get_data_range_multi, DataPoint, Padding};
+
+/// A daily series of whole numbers starting on the given day of March
+fn series(first_day: u32, values: &[u64]) -> Vec<(DateTime<Utc>, DataPoint)> {
+    let start = Utc.with_ymd_and_hms(2024, 3, first_day, 0, 0, 0).unwrap();
+    values
+        .iter()
+        .enumerate()
+        .map(|(day, value)| {
+            (
+                start + Duration::days(day as i64),
+                DataPoint::Integer(*value),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn spans_every_series() {
+    let a = series(1, &[300, 320, 310]);
+    let b = series(2, &[150, 500, 200, 250]);
+
+    let (dates, values) = get_data_range_multi([a.as_slice(), b.as_slice()], Padding::default());
+
+    assert_eq!(
+        dates.start,
+        Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        dates.end,
+        Utc.with_ymd_and_hms(2024, 3, 5, 0, 0, 0).unwrap()
+    );
+    assert_eq!(
+        values.range(),
+        DataPoint::Integer(115)..DataPoint::Integer(535)
+    );
+}
+
+#[test]
+fn matches_the_range_of_the_points_together() {
+    let a = series(1, &[42, 7, 19]);
+    let b = series(10, &[3, 88]);
+    let c = series(4, &[]);
+    let together: Vec<_> = a.iter().chain(&b).chain(&c).copied().collect();
+
+    let (dates, values) = get_data_range_multi(
+        [a.as_slice(), b.as_slice(), c.as_slice()],
+        Padding::default(),
+    );
+    let (expected_dates, expected_values) = get_data_range(&together, Padding::default());
+
+    assert_eq!(dates, expected_dates);
+    assert_eq!(values.range(), expected_values.range());
+}
+
+#[test]
+fn flat_series_are_widened() {
+    let (_, values) = get_data_range(&series(1, &[500; 10]), Padding::default());
+    assert_eq!(
+        values.widened().range(),
+        DataPoint::Integer(450)..DataPoint::Integer(550)
+    );
+
+    let (_, values) = get_data_range(&series(1, &[0]), Padding::default());
+    assert_eq!(
+        values.widened().range(),
+        DataPoint::Zero..DataPoint::from(1f64)
+    );
+
+    let (_, values) = get_data_range(&series(1, &[300, 320]), Padding::default());
+    let range = values.range();
+    assert_eq!(values.widened().range(), range);
+}
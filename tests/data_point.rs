@@ -0,0 +1,143 @@
+//! Tests that data points are written the way they are read, so that exported values parse back
+//! into the same data points
+
+use fixed::types::I32F32;
+use proptest::prelude::*;
+use rasorite::data::DataPoint;
+
+fn round_trip(point: DataPoint) -> DataPoint {
+    point.to_string().parse().unwrap_or_else(|_| {
+        panic!(
+            "{:?} was written as unparsable {:?}",
+            point,
+            point.to_string()
+        )
+    })
+}
+
+#[test]
+fn zero_is_written_as_zero() {
+    assert_eq!(DataPoint::Zero.to_string(), "0");
+    assert_eq!(round_trip(DataPoint::Zero), DataPoint::Zero);
+}
+
+#[test]
+fn integers_have_no_decimal_point() {
+    assert_eq!(DataPoint::Integer(1234).to_string(), "1234");
+    assert_eq!(
+        DataPoint::Integer(u64::MAX).to_string(),
+        u64::MAX.to_string()
+    );
+}
+
+#[test]
+fn whole_decimals_keep_a_decimal_point() {
+    let point = DataPoint::Float(I32F32::from_num(5));
+    assert_eq!(point.to_string(), "5.0");
+    assert_eq!(round_trip(point), point);
+
+    let point = DataPoint::Float(I32F32::from_num(-12));
+    assert_eq!(point.to_string(), "-12.0");
+    assert_eq!(round_trip(point), point);
+}
+
+#[test]
+fn decimals_are_written_as_read() {
+    for value in ["0.5", "12.25", "-3.75", "0.001"] {
+        let point: DataPoint = value.parse().unwrap();
+        assert!(matches!(point, DataPoint::Float(_)));
+        assert_eq!(round_trip(point), point);
+    }
+}
+
+proptest! {
+    #[test]
+    fn integers_round_trip(value in 1u64..) {
+        prop_assert_eq!(round_trip(DataPoint::Integer(value)), DataPoint::Integer(value));
+    }
+
+    #[test]
+    fn decimals_round_trip(bits in any::<i64>()) {
+        let point = DataPoint::Float(I32F32::from_bits(bits));
+        prop_assert_eq!(round_trip(point), point);
+    }
+}
+
+#[test]
+fn scientific_notation_is_read() {
+    assert_eq!(
+        "1.2e5".parse::<DataPoint>().unwrap(),
+        DataPoint::Integer(120_000)
+    );
+    assert_eq!(
+        "1.234E+06".parse::<DataPoint>().unwrap(),
+        DataPoint::Integer(1_234_000)
+    );
+    assert_eq!("0E+00".parse::<DataPoint>().unwrap(), DataPoint::Zero);
+    assert_eq!(
+        "2.5e-1".parse::<DataPoint>().unwrap(),
+        DataPoint::Float(I32F32::from_num(0.25))
+    );
+    assert!("1e".parse::<DataPoint>().is_err());
+}
+
+#[test]
+fn grouping_separators_are_ignored() {
+    assert_eq!(
+        DataPoint::parse_localized("1,234", '.').unwrap(),
+        DataPoint::Integer(1234)
+    );
+    assert_eq!(
+        DataPoint::parse_localized("1,234,567.5", '.').unwrap(),
+        DataPoint::Float(I32F32::from_num(1_234_567.5))
+    );
+    assert_eq!(
+        DataPoint::parse_localized("1.234.567", ',').unwrap(),
+        DataPoint::Integer(1_234_567)
+    );
+    assert_eq!(
+        DataPoint::parse_localized("1\u{202f}234,75", ',').unwrap(),
+        DataPoint::Float(I32F32::from_num(1234.75))
+    );
+    assert_eq!(
+        DataPoint::parse_localized("1'234.5", '.').unwrap(),
+        DataPoint::Float(I32F32::from_num(1234.5))
+    );
+}
+
+#[test]
+fn decimal_commas_are_read_as_decimal_points() {
+    assert_eq!(
+        DataPoint::parse_localized("0,25", ',').unwrap(),
+        DataPoint::Float(I32F32::from_num(0.25))
+    );
+    assert_eq!(
+        DataPoint::parse_localized("1,2E+05", ',').unwrap(),
+        DataPoint::Integer(120_000)
+    );
+    assert_eq!(
+        DataPoint::parse_localized("0", ',').unwrap(),
+        DataPoint::Zero
+    );
+}
+
+#[test]
+fn grouped_thousands_are_read_without_a_locale() {
+    assert_eq!(
+        "1,234".parse::<DataPoint>().unwrap(),
+        DataPoint::Integer(1234)
+    );
+    assert_eq!(
+        "12,345,678".parse::<DataPoint>().unwrap(),
+        DataPoint::Integer(12_345_678)
+    );
+    assert_eq!(
+        "1,234.5".parse::<DataPoint>().unwrap(),
+        DataPoint::Float(I32F32::from_num(1234.5))
+    );
+    // Groups of any other length could be a decimal comma, so they are left for --number-locale
+    assert!("1,2".parse::<DataPoint>().is_err());
+    assert!("1,2345".parse::<DataPoint>().is_err());
+    assert!("1234,567".parse::<DataPoint>().is_err());
+    assert!(",234".parse::<DataPoint>().is_err());
+}
@@ -0,0 +1,127 @@
+//! Tests for metrics derived from two exports
+
+mod common;
+
+use chrono::{TimeZone, Utc};
+use common::fixture;
+use rasorite::data::KpiType;
+use rasorite::derive::{derive, Derivation, DerivationError};
+use rasorite::parse::MismatchError;
+
+#[test]
+fn divides_daily_by_monthly_active_users() {
+    let stickiness = derive(
+        Derivation::Stickiness,
+        fixture("analytics.csv"),
+        fixture("mau.csv"),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(stickiness.kpi_type, KpiType::Stickiness);
+    assert_eq!(stickiness.universe_id, 0);
+    let total = &stickiness.data["Total"];
+    assert_eq!(total.len(), 90);
+    let (date, point) = total[0];
+    assert_eq!(date, Utc.with_ymd_and_hms(2024, 2, 23, 0, 0, 0).unwrap());
+    // 264 daily out of 2376 monthly active users
+    assert!((f64::from(point) - 264.0 / 2376.0 * 100.0).abs() < 1e-6);
+    assert!(stickiness.data.contains_key("Benchmark (Top n experience)"));
+}
+
+#[test]
+fn takes_the_exports_in_either_order() {
+    let forwards = derive(
+        Derivation::Stickiness,
+        fixture("analytics.csv"),
+        fixture("mau.csv"),
+        false,
+    )
+    .unwrap();
+    let backwards = derive(
+        Derivation::Stickiness,
+        fixture("mau.csv"),
+        fixture("analytics.csv"),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(forwards.data, backwards.data);
+}
+
+#[test]
+fn divides_revenue_by_daily_active_users() {
+    let arpdau = derive(
+        Derivation::Arpdau,
+        fixture("analytics.csv"),
+        fixture("revenue.csv"),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(arpdau.kpi_type, KpiType::Arpdau);
+    // The revenue export has no benchmark to divide
+    assert_eq!(
+        arpdau.data.keys().map(|key| &**key).collect::<Vec<_>>(),
+        ["Total"]
+    );
+    let (_, point) = arpdau.data["Total"][0];
+    assert!((f64::from(point) - 893.0 / 264.0).abs() < 1e-6);
+}
+
+#[test]
+fn divides_paying_by_daily_active_users() {
+    let conversion = derive(
+        Derivation::Conversion,
+        fixture("paying.csv"),
+        fixture("analytics.csv"),
+        false,
+    )
+    .unwrap();
+
+    assert_eq!(conversion.kpi_type, KpiType::ConversionRate);
+    let (_, point) = conversion.data["Total"][0];
+    assert!((f64::from(point) - 13.0 / 264.0 * 100.0).abs() < 1e-6);
+}
+
+#[test]
+fn rejects_the_wrong_kpis() {
+    let derived = derive(
+        Derivation::Stickiness,
+        fixture("analytics.csv"),
+        fixture("peer.csv"),
+        false,
+    );
+
+    assert!(matches!(
+        derived,
+        Err(DerivationError::MismatchedInputs(..))
+    ));
+}
+
+#[test]
+fn rejects_exports_of_different_experiences() {
+    let mut other = fixture("mau.csv");
+    other.universe_id = 42;
+
+    let derived = derive(
+        Derivation::Stickiness,
+        fixture("analytics.csv"),
+        other.clone(),
+        false,
+    );
+    assert!(matches!(
+        derived,
+        Err(DerivationError::Mismatch(
+            MismatchError::DifferentExperiences(0, 42)
+        ))
+    ));
+
+    let derived = derive(
+        Derivation::Stickiness,
+        fixture("analytics.csv"),
+        other,
+        true,
+    );
+    assert_eq!(derived.unwrap().universe_id, 0);
+}
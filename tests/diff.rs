@@ -0,0 +1,74 @@
+//! Tests for comparing two exports of the same KPI
+
+use chrono::{TimeZone, Utc};
+use rasorite::diff::{diff, Discrepancy, Side};
+use rasorite::parse::{parse_analytics, AnalyticsData};
+
+fn export(records: &[(&str, u32, &str)]) -> AnalyticsData {
+    let mut csv = "Experience ID,123\n\nBreakdown,Date,Daily Active Users\n".to_string();
+    for (series, day, value) in records {
+        csv.push_str(&format!(
+            "{},2024-03-{:02}T00:00:00.000Z,{}\n",
+            series, day, value
+        ));
+    }
+    parse_analytics(csv.as_bytes()).expect("Failed to parse test export!")
+}
+
+#[test]
+fn identical_exports_have_no_discrepancies() {
+    let data = export(&[("Total", 1, "100"), ("Total", 2, "120")]);
+    assert!(diff(&data, &data, 0.0).is_empty());
+}
+
+#[test]
+fn reports_missing_days_on_both_sides() {
+    let old = export(&[("Total", 1, "100"), ("Total", 2, "120")]);
+    let new = export(&[("Total", 2, "120"), ("Total", 3, "130")]);
+
+    let diffs = diff(&old, &new, 0.0);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(
+        diffs[0].discrepancies,
+        vec![
+            Discrepancy::Missing {
+                date: Utc.with_ymd_and_hms(2024, 3, 1, 0, 0, 0).unwrap(),
+                side: Side::Old,
+                value: 100.0,
+            },
+            Discrepancy::Missing {
+                date: Utc.with_ymd_and_hms(2024, 3, 3, 0, 0, 0).unwrap(),
+                side: Side::New,
+                value: 130.0,
+            },
+        ]
+    );
+}
+
+#[test]
+fn ignores_changes_within_the_tolerance() {
+    let old = export(&[("Total", 1, "1000"), ("Total", 2, "1000")]);
+    let new = export(&[("Total", 1, "1004"), ("Total", 2, "1020")]);
+
+    let diffs = diff(&old, &new, 1.0);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(
+        diffs[0].discrepancies,
+        vec![Discrepancy::Changed {
+            date: Utc.with_ymd_and_hms(2024, 3, 2, 0, 0, 0).unwrap(),
+            old: 1000.0,
+            new: 1020.0,
+        }]
+    );
+}
+
+#[test]
+fn reports_series_missing_from_one_export() {
+    let old = export(&[("Total", 1, "100"), ("Benchmark Top 25%", 1, "90")]);
+    let new = export(&[("Total", 1, "100")]);
+
+    let diffs = diff(&old, &new, 0.0);
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].name, "Benchmark Top 25%");
+    assert_eq!(diffs[0].only_in, Some(Side::Old));
+}
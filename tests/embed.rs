@@ -0,0 +1,34 @@
+//! Tests for embedding the input export into the chart with --embed-data
+
+mod common;
+
+use clap::Parser;
+use common::{fixture, fixture_path};
+use rasorite::config::Config;
+use rasorite::parse::{parse_analytics_file, read_analytics_file};
+use rasorite::plot::{plot_data, PlotArgs};
+use std::fs;
+
+#[test]
+fn embedded_charts_read_back_as_their_export() {
+    let input = fixture_path("analytics.csv");
+    let out = std::env::temp_dir().join(format!("rasorite-embed-{}.svg", std::process::id()));
+
+    let mut args = PlotArgs::try_parse_from(["--deterministic"]).expect("Invalid plot flags!");
+    args.plot.embedded_data = Some((
+        "analytics.csv".to_string(),
+        fs::read(&input).expect("Failed to read fixture!"),
+    ));
+    let style = args.plot.resolve_style(&Config::default());
+    let data = fixture("analytics.csv");
+    plot_data(data.clone(), &out, &args.plot, &style).expect("Failed to plot chart!");
+
+    let embedded = read_analytics_file(&out);
+    let read_back = parse_analytics_file(&out);
+    let _ = fs::remove_file(&out);
+    assert_eq!(
+        embedded.expect("Failed to read embedded export!"),
+        fs::read(&input).unwrap()
+    );
+    assert_eq!(read_back.expect("Failed to parse chart!").data, data.data);
+}
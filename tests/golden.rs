@@ -0,0 +1,289 @@
+//! Golden-image tests for chart rendering. Each case renders a fixture export to SVG and compares
+//! it with the checked-in golden in `tests/goldens`. After an intended change to how charts look,
+//! regenerate the goldens with `UPDATE_GOLDENS=1 cargo test --test golden` and review the diff.
+//! Charts are rendered with `--deterministic`, so that the goldens do not depend on the fonts
+//! installed on the machine running the tests.
+
+mod common;
+
+use clap::Parser;
+use rasorite::config::Config;
+use rasorite::plot::{render_svg, PlotArgs};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+fn tests_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests")
+}
+
+/// Rounds every decimal number to one place and trims each line, so that floating point noise in
+/// text sizes and positions does not count as a change
+fn normalize(svg: &str) -> String {
+    let decimal = Regex::new(r"-?\d+\.\d+").unwrap();
+
+    svg.lines()
+        .map(|line| {
+            decimal
+                .replace_all(line.trim(), |captures: &regex::Captures| {
+                    format!("{:.1}", captures[0].parse::<f64>().unwrap())
+                })
+                .into_owned()
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Renders the fixture with the given flags and compares it with the named golden
+fn assert_golden(name: &str, fixture: &str, args: &[&str]) {
+    let args = PlotArgs::try_parse_from(["--deterministic"].iter().chain(args))
+        .expect("Invalid plot flags!");
+    let style = args.plot.resolve_style(&Config::default());
+    let data = common::fixture(fixture);
+    let svg = normalize(&render_svg(data, &args.plot, &style).expect("Failed to render chart!"));
+
+    let golden = tests_dir().join("goldens").join(format!("{}.svg", name));
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        std::fs::write(&golden, format!("{}\n", svg)).expect("Failed to write golden!");
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&golden).unwrap_or_else(|_| {
+        panic!(
+            "The golden \"{}\" is missing! Run with UPDATE_GOLDENS=1 to create it.",
+            golden.display()
+        )
+    });
+    let expected = normalize(&expected);
+    if svg != expected {
+        let (line, (actual, expected)) = svg
+            .lines()
+            .zip(expected.lines())
+            .enumerate()
+            .find(|(_, (actual, expected))| actual != expected)
+            .unwrap_or((
+                svg.lines().count().min(expected.lines().count()),
+                ("<end of output>", "<end of output>"),
+            ));
+        panic!(
+            "\"{}\" differs from its golden at line {}!\n  expected: {}\n    actual: {}\nRun with UPDATE_GOLDENS=1 to accept the change.",
+            name,
+            line + 1,
+            expected,
+            actual
+        );
+    }
+}
+
+#[test]
+fn default() {
+    assert_golden("default", "analytics.csv", &[]);
+}
+
+#[test]
+fn normalized() {
+    assert_golden("normalized", "analytics.csv", &["-n"]);
+}
+
+#[test]
+fn indexed() {
+    assert_golden("indexed", "analytics.csv", &["--normalize-mode", "index"]);
+}
+
+#[test]
+fn dark_theme() {
+    assert_golden(
+        "dark-theme",
+        "analytics.csv",
+        &["--theme", "dark", "--grid", "light"],
+    );
+}
+
+#[test]
+fn goal() {
+    assert_golden(
+        "goal",
+        "analytics.csv",
+        &["--goal", "800:Launch goal", "--goal-shade", "above"],
+    );
+}
+
+#[test]
+fn series_styles() {
+    assert_golden(
+        "series-styles",
+        "analytics.csv",
+        &[
+            "--line-style",
+            "^Benchmark=dotted",
+            "--opacity",
+            "^Benchmark=0.5",
+            "--stroke-width",
+            "3",
+            "--markers",
+        ],
+    );
+}
+
+#[test]
+fn value_labels() {
+    assert_golden("value-labels", "analytics.csv", &["--value-labels"]);
+}
+
+#[test]
+fn multiple_benchmarks() {
+    assert_golden("multiple-benchmarks", "benchmarks.csv", &[]);
+}
+
+#[test]
+fn localized() {
+    assert_golden(
+        "localized",
+        "analytics.csv",
+        &["--lang", "es", "-n", "--goal", "500"],
+    );
+}
+
+#[test]
+fn rotated_dates() {
+    assert_golden(
+        "rotated-dates",
+        "analytics.csv",
+        &[
+            "--width",
+            "600",
+            "--height",
+            "450",
+            "--date-labels",
+            "rotated",
+        ],
+    );
+}
+
+#[test]
+fn staggered_dates() {
+    assert_golden(
+        "staggered-dates",
+        "analytics.csv",
+        &[
+            "--width",
+            "600",
+            "--height",
+            "450",
+            "--date-labels",
+            "staggered",
+        ],
+    );
+}
+
+#[test]
+fn tight() {
+    assert_golden("tight", "analytics.csv", &["--tight", "-n"]);
+}
+
+#[test]
+fn footer() {
+    assert_golden(
+        "footer",
+        "analytics.csv",
+        &[
+            "--footer",
+            "Exported from Creator Dashboard",
+            "--footer-stamp",
+        ],
+    );
+}
+
+#[test]
+fn donut() {
+    assert_golden(
+        "donut",
+        "countries.csv",
+        &["--style", "donut", "--date", "2024-05-03"],
+    );
+}
+
+#[test]
+fn named_series() {
+    assert_golden("named-series", "countries.csv", &["--series", "Brazil"]);
+}
+
+#[test]
+fn peer() {
+    assert_golden(
+        "peer",
+        "analytics.csv",
+        &["--peer", "tests/fixtures/peer.csv"],
+    );
+}
+
+#[test]
+fn combined() {
+    assert_golden(
+        "combined",
+        "analytics.csv",
+        &[
+            "--combine",
+            "tests/fixtures/revenue.csv",
+            "tests/fixtures/paying.csv",
+        ],
+    );
+}
+
+#[test]
+fn excluded_dates() {
+    assert_golden(
+        "excluded-dates",
+        "analytics.csv",
+        &[
+            "--exclude-dates",
+            "2024-04-18..2024-04-21",
+            "--shade-excluded",
+        ],
+    );
+}
+
+#[test]
+fn rolling_averages() {
+    assert_golden("rolling-averages", "analytics.csv", &["--preset", "dau"]);
+}
+
+#[test]
+fn inline_stats() {
+    assert_golden("inline-stats", "analytics.csv", &["--inline-stats"]);
+}
+
+#[test]
+fn colorblind_palette() {
+    assert_golden(
+        "colorblind-palette",
+        "analytics.csv",
+        &["--palette", "colorblind"],
+    );
+}
+
+#[test]
+fn print_preset() {
+    assert_golden("print-preset", "analytics.csv", &["--preset", "print"]);
+}
+
+#[test]
+fn right_to_left() {
+    assert_golden(
+        "right-to-left",
+        "analytics.csv",
+        &[
+            "--locale",
+            "he",
+            "--goal",
+            "500",
+            "--value-labels",
+            "--inline-stats",
+        ],
+    );
+}
+
+#[test]
+fn delta_badge() {
+    assert_golden("delta-badge", "analytics.csv", &["--delta-badge"]);
+}
@@ -0,0 +1,98 @@
+//! Tests for the built-in holiday calendars and holiday files
+
+use chrono::NaiveDate;
+use rasorite::holiday::{holidays_between, HolidaySource};
+use std::path::PathBuf;
+
+fn day(year: i32, month: u32, day: u32) -> NaiveDate {
+    NaiveDate::from_ymd_opt(year, month, day).unwrap()
+}
+
+/// Writes a holiday file unique to the test
+fn holiday_file(test: &str, contents: &str) -> PathBuf {
+    let file = std::env::temp_dir().join(format!("rasorite-{}-{}.csv", test, std::process::id()));
+    std::fs::write(&file, contents).unwrap();
+    file
+}
+
+fn names_on(source: &str, date: NaiveDate) -> Vec<String> {
+    let source: HolidaySource = source.parse().unwrap();
+    holidays_between(&[source], date, date)
+        .unwrap()
+        .into_iter()
+        .map(|holiday| holiday.name)
+        .collect()
+}
+
+#[test]
+fn finds_moving_us_holidays() {
+    assert_eq!(names_on("us", day(2024, 11, 28)), ["Thanksgiving"]);
+    assert_eq!(names_on("us", day(2024, 5, 27)), ["Memorial Day"]);
+    assert_eq!(
+        names_on("us", day(2025, 1, 20)),
+        ["Martin Luther King Jr. Day"]
+    );
+}
+
+#[test]
+fn finds_easter_holidays() {
+    assert_eq!(names_on("uk", day(2024, 3, 29)), ["Good Friday"]);
+    assert_eq!(names_on("UK", day(2025, 4, 21)), ["Easter Monday"]);
+    assert_eq!(names_on("br", day(2024, 2, 13)), ["Carnival"]);
+}
+
+#[test]
+fn merges_calendars_in_date_order() {
+    let sources = [
+        "uk".parse().unwrap(),
+        "us".parse().unwrap(),
+        "br".parse().unwrap(),
+    ];
+    let holidays = holidays_between(&sources, day(2024, 12, 20), day(2025, 1, 5)).unwrap();
+    let found: Vec<(NaiveDate, &str)> = holidays
+        .iter()
+        .map(|holiday| (holiday.date, holiday.name.as_str()))
+        .collect();
+
+    assert_eq!(
+        found,
+        [
+            (day(2024, 12, 25), "Christmas Day"),
+            (day(2024, 12, 26), "Boxing Day"),
+            (day(2025, 1, 1), "New Year's Day"),
+        ]
+    );
+}
+
+#[test]
+fn reads_holiday_files() {
+    let file = holiday_file(
+        "reads",
+        "date,name\n# Our own events\n2024-04-19, Spring Update\n",
+    );
+    let holidays = holidays_between(
+        &[HolidaySource::File(file.clone())],
+        day(2024, 4, 1),
+        day(2024, 4, 30),
+    );
+    std::fs::remove_file(file).unwrap();
+
+    let holidays = holidays.unwrap();
+
+    assert_eq!(holidays.len(), 1);
+    assert_eq!(holidays[0].date, day(2024, 4, 19));
+    assert_eq!(holidays[0].name, "Spring Update");
+}
+
+#[test]
+fn rejects_invalid_lines() {
+    let file = holiday_file("rejects", "19/04/2024,Spring Update\n");
+    let holidays = holidays_between(
+        &[HolidaySource::File(file.clone())],
+        day(2024, 4, 1),
+        day(2024, 4, 30),
+    );
+    std::fs::remove_file(file).unwrap();
+
+    assert!(holidays.is_err());
+}
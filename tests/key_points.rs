@@ -0,0 +1,153 @@
+//! Tests for the y-axis key points of a chart. The algorithm is adapted from plotters' float ranges,
+//! so these cover the cases where data points behave differently from floats: tiny spans, huge
+//! whole numbers and ranges that start at or end near zero.
+
+use fixed::types::I32F32;
+use plotters::coord::ranged1d::Ranged;
+use proptest::prelude::*;
+use rasorite::data::{DataPoint, RangedDataPoint};
+
+fn float(value: f64) -> DataPoint {
+    DataPoint::Float(I32F32::from_num(value))
+}
+
+fn key_points(start: DataPoint, end: DataPoint, max_points: usize) -> Vec<f64> {
+    RangedDataPoint::new(start, end)
+        .key_points(max_points)
+        .into_iter()
+        .map(f64::from)
+        .collect()
+}
+
+/// Asserts that the key points are at most `max_points` strictly increasing values in the range
+fn assert_well_formed(points: &[f64], start: f64, end: f64, max_points: usize) {
+    assert!(
+        points.len() <= max_points,
+        "{} key points for {}..{} where at most {} were asked for: {:?}",
+        points.len(),
+        start,
+        end,
+        max_points,
+        points
+    );
+    assert!(
+        points.windows(2).all(|pair| pair[0] < pair[1]),
+        "The key points for {}..{} are not increasing: {:?}",
+        start,
+        end,
+        points
+    );
+    // Float bounds are rounded to I32F32, which is accurate to about 2.3e-10
+    let tolerance = (end - start).abs() * 1e-9 + 1e-9;
+    assert!(
+        points
+            .iter()
+            .all(|point| *point >= start - tolerance && *point <= end + tolerance),
+        "The key points for {}..{} leave the range: {:?}",
+        start,
+        end,
+        points
+    );
+}
+
+#[test]
+fn counts_use_round_numbers() {
+    assert_eq!(
+        key_points(DataPoint::Zero, DataPoint::Integer(1000), 11),
+        [0.0, 100.0, 200.0, 300.0, 400.0, 500.0, 600.0, 700.0, 800.0, 900.0, 1000.0]
+    );
+    assert_eq!(
+        key_points(DataPoint::Integer(230), DataPoint::Integer(470), 6),
+        [250.0, 300.0, 350.0, 400.0, 450.0]
+    );
+}
+
+#[test]
+fn no_points_asked_for() {
+    assert!(key_points(DataPoint::Zero, DataPoint::Integer(100), 0).is_empty());
+}
+
+#[test]
+fn single_value_range() {
+    assert_eq!(
+        key_points(DataPoint::Integer(42), DataPoint::Integer(42), 10),
+        [42.0]
+    );
+    assert_eq!(key_points(DataPoint::Zero, DataPoint::Zero, 10), [0.0]);
+}
+
+#[test]
+fn tiny_ranges() {
+    let points = key_points(float(0.5), float(0.5001), 10);
+    assert!(!points.is_empty());
+    assert_well_formed(&points, 0.5, 0.5001, 10);
+
+    let points = key_points(float(0.001), float(0.001_000_1), 5);
+    assert_well_formed(&points, 0.001, 0.001_000_1, 5);
+}
+
+#[test]
+fn near_zero_spans() {
+    let points = key_points(DataPoint::Zero, float(0.000_01), 10);
+    assert!(!points.is_empty());
+    assert_well_formed(&points, 0.0, 0.000_01, 10);
+}
+
+#[test]
+fn huge_integers() {
+    // Larger than a data point's fractional part can hold, as lifetime visit counts can be
+    let (start, end) = (10_000_000_000u64, 10_000_500_000u64);
+    let points = key_points(DataPoint::Integer(start), DataPoint::Integer(end), 6);
+    assert_eq!(
+        points,
+        [1.0e10, 1.00001e10, 1.00002e10, 1.00003e10, 1.00004e10, 1.00005e10]
+    );
+
+    let points = key_points(DataPoint::Zero, DataPoint::Integer(u64::MAX / 4), 10);
+    assert!(!points.is_empty());
+    assert_well_formed(&points, 0.0, (u64::MAX / 4) as f64, 10);
+}
+
+#[test]
+fn huge_single_value_range() {
+    assert_eq!(
+        key_points(
+            DataPoint::Integer(5_000_000_000),
+            DataPoint::Integer(5_000_000_000),
+            10
+        ),
+        [5.0e9]
+    );
+}
+
+#[test]
+fn reversed_ranges() {
+    assert_eq!(
+        key_points(DataPoint::Integer(100), DataPoint::Zero, 3),
+        key_points(DataPoint::Zero, DataPoint::Integer(100), 3)
+    );
+}
+
+proptest! {
+    #[test]
+    fn integer_key_points_are_well_formed(
+        start in 0u64..1_000_000_000_000,
+        span in 1u64..1_000_000_000_000,
+        max_points in 1usize..40,
+    ) {
+        let end = start + span;
+        let points = key_points(DataPoint::Integer(start), DataPoint::Integer(end), max_points);
+        assert_well_formed(&points, start as f64, end as f64, max_points);
+    }
+
+    #[test]
+    fn float_key_points_are_well_formed(
+        start in -1_000_000.0f64..1_000_000.0,
+        span in 0.000_001f64..1_000_000.0,
+        max_points in 1usize..40,
+    ) {
+        let (start, end) = (float(start), float(start + span));
+        let points = key_points(start, end, max_points);
+        assert_well_formed(&points, start.into(), end.into(), max_points);
+    }
+}
@@ -0,0 +1,63 @@
+//! Tests for the date orders and text directions of locales
+
+use chrono::{TimeZone, Utc};
+use rasorite::locale::{DateOrder, Locale};
+
+fn numeric_date(tag: &str) -> String {
+    let locale: Locale = tag.parse().unwrap();
+    locale.numeric_date(&Utc.with_ymd_and_hms(2024, 2, 3, 0, 0, 0).unwrap())
+}
+
+#[test]
+fn writes_dates_in_the_regions_order() {
+    assert_eq!(numeric_date("en-GB"), "03/02/2024");
+    assert_eq!(numeric_date("en-US"), "02/03/2024");
+    assert_eq!(numeric_date("en"), "02/03/2024");
+    assert_eq!(numeric_date("ja-JP"), "2024/02/03");
+    assert_eq!(numeric_date("en-CA"), "2024-02-03");
+}
+
+#[test]
+fn writes_dates_with_the_languages_separator() {
+    assert_eq!(numeric_date("de-DE"), "03.02.2024");
+    assert_eq!(numeric_date("nl"), "03-02-2024");
+    assert_eq!(numeric_date("pt-BR"), "03/02/2024");
+}
+
+#[test]
+fn regions_take_precedence_over_languages() {
+    let locale: Locale = "es-US".parse().unwrap();
+    assert_eq!(locale.date_order(), DateOrder::MonthDayYear);
+    let locale: Locale = "fr-CA".parse().unwrap();
+    assert_eq!(locale.date_order(), DateOrder::YearMonthDay);
+}
+
+#[test]
+fn finds_right_to_left_languages() {
+    for tag in ["ar", "ar-SA", "he-IL", "fa"] {
+        assert!(tag.parse::<Locale>().unwrap().is_right_to_left(), "{}", tag);
+    }
+    for tag in ["en-GB", "de", "ja"] {
+        assert!(
+            !tag.parse::<Locale>().unwrap().is_right_to_left(),
+            "{}",
+            tag
+        );
+    }
+}
+
+#[test]
+fn rejects_invalid_tags() {
+    assert!("not a locale".parse::<Locale>().is_err());
+}
+
+#[test]
+fn decimal_separators_follow_the_language_and_region() {
+    let decimal_separator = |tag: &str| tag.parse::<Locale>().unwrap().decimal_separator();
+    assert_eq!(decimal_separator("en"), '.');
+    assert_eq!(decimal_separator("de-DE"), ',');
+    assert_eq!(decimal_separator("pt-BR"), ',');
+    assert_eq!(decimal_separator("es-MX"), '.');
+    assert_eq!(decimal_separator("de-CH"), '.');
+    assert_eq!(decimal_separator("ja"), '.');
+}
@@ -0,0 +1,86 @@
+//! Tests for checking that exports can be combined with one another
+
+use chrono::{Duration, TimeZone, Utc};
+use rasorite::data::KpiType;
+use rasorite::parse::{parse_analytics, AnalyticsData, Granularity, MismatchError};
+
+/// An export of the given experience with a point every `step` days
+fn export(universe_id: u64, step: i64, points: i64) -> AnalyticsData {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut csv = format!(
+        "Experience ID,{}\n\nBreakdown,Date,Daily Active Users\n",
+        universe_id
+    );
+    for point in 0..points {
+        let date = start + Duration::days(point * step);
+        csv.push_str(&format!(
+            "Total,{},{}\n",
+            date.format("%Y-%m-%dT%H:%M:%S%.3fZ"),
+            100 + point
+        ));
+    }
+    parse_analytics(csv.as_bytes()).expect("Failed to parse test export!")
+}
+
+#[test]
+fn finds_the_granularity() {
+    assert_eq!(export(1, 1, 30).granularity(), Some(Granularity::Daily));
+    assert_eq!(export(1, 7, 12).granularity(), Some(Granularity::Weekly));
+    assert_eq!(export(1, 30, 12).granularity(), Some(Granularity::Monthly));
+    assert_eq!(export(1, 1, 1).granularity(), None);
+}
+
+#[test]
+fn missing_days_do_not_change_the_granularity() {
+    let mut data = export(1, 1, 30);
+    data.data.get_mut("Total").unwrap().retain(|(date, _)| {
+        !(5..=9).contains(&(*date - Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()).num_days())
+    });
+
+    assert_eq!(data.granularity(), Some(Granularity::Daily));
+}
+
+#[test]
+fn rejects_other_experiences() {
+    assert!(matches!(
+        export(1, 1, 30).check_combinable(&export(2, 1, 30), true, false),
+        Err(MismatchError::DifferentExperiences(1, 2))
+    ));
+    // Peers and references are meant to be other experiences
+    assert!(export(1, 1, 30)
+        .check_combinable(&export(2, 1, 30), false, false)
+        .is_ok());
+}
+
+#[test]
+fn rejects_other_granularities() {
+    assert!(matches!(
+        export(1, 1, 30).check_combinable(&export(1, 7, 12), false, false),
+        Err(MismatchError::DifferentGranularities(
+            Granularity::Daily,
+            Granularity::Weekly
+        ))
+    ));
+}
+
+#[test]
+fn allows_mismatches_when_asked() {
+    assert!(export(1, 1, 30)
+        .check_combinable(&export(2, 7, 12), true, true)
+        .is_ok());
+}
+
+#[test]
+fn rejects_other_kpis_unless_forced() {
+    let mut sessions = export(1, 1, 30);
+    sessions.kpi_type = KpiType::Visits;
+
+    assert!(matches!(
+        export(1, 1, 30).check_same_kpi(&sessions, false),
+        Err(MismatchError::DifferentKpis(
+            KpiType::DailyActiveUsers,
+            KpiType::Visits
+        ))
+    ));
+    assert!(export(1, 1, 30).check_same_kpi(&sessions, true).is_ok());
+}
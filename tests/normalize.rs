@@ -0,0 +1,43 @@
+//! Tests for normalizing charts against a reference series
+
+use clap::Parser;
+use rasorite::config::Config;
+use rasorite::parse::parse_analytics;
+use rasorite::plot::{render_svg, PlotArgs};
+use std::fs;
+
+const PLAYTIME: &str = "Experience ID,1\n\nBreakdown,Date,Playtime\n\
+                        Total,2024-03-01T00:00:00.000Z,10\n\
+                        Total,2024-03-02T00:00:00.000Z,20\n\
+                        Total,2024-03-03T00:00:00.000Z,30\n";
+
+#[test]
+fn reference_files_are_converted_to_the_unit() {
+    let reference =
+        std::env::temp_dir().join(format!("rasorite-normalize-{}.csv", std::process::id()));
+    fs::write(&reference, PLAYTIME).expect("Failed to write reference export!");
+
+    let args = PlotArgs::try_parse_from([
+        "--deterministic",
+        "--value-labels",
+        "--unit",
+        "minutes",
+        "--normalize-mode",
+        "index",
+        "--normalize-file",
+        reference.to_str().unwrap(),
+    ])
+    .expect("Invalid plot flags!");
+    let style = args.plot.resolve_style(&Config::default());
+    let svg = render_svg(
+        parse_analytics(PLAYTIME.as_bytes()).unwrap(),
+        &args.plot,
+        &style,
+    );
+    let _ = fs::remove_file(&reference);
+
+    // The latest value is labelled at the end of the line
+    let svg = svg.expect("Failed to render chart!");
+    assert!(svg.contains(">\n100\n</text>"));
+    assert!(!svg.contains(">\n6000\n</text>"));
+}
@@ -0,0 +1,199 @@
+//! Property-based tests for the export parser. Malformed exports must always be rejected with an
+//! error rather than panicking, and well-formed exports must keep every record.
+
+use chrono::{Duration, TimeZone, Utc};
+use proptest::prelude::*;
+use rasorite::data::DataPoint;
+use rasorite::locale::Locale;
+use rasorite::parse::{has_analytics_header, parse_analytics, parse_localized_analytics};
+
+const KPIS: [&str; 6] = [
+    "Daily Active Users",
+    "Monthly Active Users",
+    "Sessions",
+    "Playtime",
+    "Daily Revenue",
+    "Paying Users",
+];
+
+const SERIES: [&str; 4] = [
+    "Total",
+    "Benchmark (Top n experience)",
+    "Benchmark: Similar Experiences (Median)",
+    "Benchmark Top 25%",
+];
+
+/// Series an export viewed by platform has instead of a total
+const BREAKDOWNS: [&str; 3] = ["Computer", "Phone", "Console"];
+
+/// A value as Roblox writes it: a whole number, zero or a decimal
+fn value() -> impl Strategy<Value = String> {
+    prop_oneof![
+        (1u64..10_000_000).prop_map(|value| value.to_string()),
+        Just("0".to_string()),
+        (0.01f64..100_000.0).prop_map(|value| format!("{:.2}", value)),
+    ]
+}
+
+/// A data record on one of the first few hundred days of 2024
+fn record() -> impl Strategy<Value = (&'static str, i64, String)> {
+    (prop::sample::select(&SERIES[..]), 0i64..400, value())
+}
+
+fn export(universe_id: u64, kpi: &str, records: &[(&str, i64, String)]) -> String {
+    let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+    let mut csv = format!("Experience ID,{}\n\nBreakdown,Date,{}\n", universe_id, kpi);
+    for (series, day, value) in records {
+        let date = start + Duration::days(*day);
+        csv.push_str(&format!(
+            "{},{},{}\n",
+            series,
+            date.format("%FT%T%.3fZ"),
+            value
+        ));
+    }
+    csv
+}
+
+/// An export that may be broken anywhere: lines dropped, duplicated or replaced with noise, and
+/// fields cut short
+fn mangled_export() -> impl Strategy<Value = String> {
+    (
+        any::<u64>(),
+        prop::sample::select(&KPIS[..]),
+        prop::collection::vec(record(), 0..20),
+    )
+        .prop_flat_map(|(universe_id, kpi, records)| {
+            let lines: Vec<String> = export(universe_id, kpi, &records)
+                .lines()
+                .map(str::to_string)
+                .collect();
+            let count = lines.len();
+            (
+                Just(lines),
+                prop::collection::vec((0..count.max(1), 0u8..4, ".{0,12}"), 0..6),
+            )
+        })
+        .prop_map(|(mut lines, edits)| {
+            for (index, edit, noise) in edits {
+                if lines.is_empty() {
+                    break;
+                }
+                let index = index % lines.len();
+                match edit {
+                    0 => {
+                        lines.remove(index);
+                    }
+                    1 => lines.insert(index, lines[index].clone()),
+                    2 => lines[index] = noise,
+                    _ => {
+                        let cut = noise.len().min(lines[index].len());
+                        let cut = (0..=cut)
+                            .rev()
+                            .find(|cut| lines[index].is_char_boundary(*cut))
+                            .unwrap_or(0);
+                        lines[index].truncate(cut);
+                    }
+                }
+            }
+            lines.join("\n")
+        })
+}
+
+proptest! {
+    #[test]
+    fn parses_well_formed_exports(
+        universe_id in any::<u64>(),
+        kpi in prop::sample::select(&KPIS[..]),
+        records in prop::collection::vec(record(), 1..50),
+    ) {
+        let data = parse_analytics(export(universe_id, kpi, &records).as_bytes()).unwrap();
+
+        prop_assert_eq!(data.universe_id, universe_id);
+        prop_assert_eq!(data.kpi_type.to_string(), kpi);
+        prop_assert_eq!(
+            data.data.values().map(|points| points.len()).sum::<usize>(),
+            records.len()
+        );
+        for (series, _, _) in &records {
+            prop_assert!(data.data.contains_key(*series));
+        }
+    }
+
+    #[test]
+    fn never_panics_on_mangled_exports(csv in mangled_export()) {
+        let _ = parse_analytics(csv.as_bytes());
+        let _ = has_analytics_header(csv.as_bytes());
+    }
+
+    #[test]
+    fn never_panics_on_arbitrary_bytes(bytes in prop::collection::vec(any::<u8>(), 0..512)) {
+        let _ = parse_analytics(bytes.as_slice());
+        let _ = has_analytics_header(bytes.as_slice());
+    }
+
+    #[test]
+    fn rejects_exports_without_records(
+        universe_id in any::<u64>(),
+        kpi in prop::sample::select(&KPIS[..]),
+    ) {
+        prop_assert!(parse_analytics(export(universe_id, kpi, &[]).as_bytes()).is_err());
+        prop_assert!(has_analytics_header(export(universe_id, kpi, &[]).as_bytes()));
+    }
+
+    #[test]
+    fn computed_totals_sum_the_breakdowns(
+        records in prop::collection::vec(
+            (prop::sample::select(&BREAKDOWNS[..]), 0i64..30, value()),
+            1..50,
+        ),
+    ) {
+        let mut data = parse_analytics(export(1, "Daily Active Users", &records).as_bytes()).unwrap();
+        prop_assert!(data.add_computed_total().unwrap());
+        prop_assert!(!data.add_computed_total().unwrap());
+
+        for (date, total) in &data.data["Total"] {
+            let sum: f64 = data
+                .breakdowns()
+                .iter()
+                .flat_map(|key| &data.data[*key])
+                .filter(|(other, _)| other == date)
+                .map(|(_, point)| f64::from(*point))
+                .sum();
+            prop_assert!((f64::from(*total) - sum).abs() <= sum * 1e-6);
+        }
+    }
+}
+
+#[test]
+fn reads_values_in_the_number_locale() {
+    let csv = "Experience ID,1\n\nBreakdown,Date,Daily Active Users\n\
+               Total,2024-02-23T00:00:00.000Z,\"1.234\"\n\
+               Total,2024-02-24T00:00:00.000Z,\"1,2E+05\"\n";
+    let locale: Locale = "de-DE".parse().unwrap();
+
+    let data = parse_localized_analytics(csv.as_bytes(), Some(&locale)).unwrap();
+    let mut points: Vec<_> = data.data["Total"].iter().map(|(_, point)| *point).collect();
+    points.sort();
+
+    assert_eq!(
+        points,
+        [DataPoint::Integer(1234), DataPoint::Integer(120_000)]
+    );
+    // Without the locale, "1.234" is a decimal and "1,2E+05" is not a number at all
+    let data = parse_analytics(csv.as_bytes()).unwrap();
+    assert_eq!(data.data["Total"].len(), 1);
+}
+
+#[test]
+fn reads_grouped_thousands_without_a_number_locale() {
+    let csv = "Experience ID,1\n\nBreakdown,Date,Daily Active Users\n\
+               Total,2024-02-23T00:00:00.000Z,\"1,234\"\n\
+               Total,2024-02-24T00:00:00.000Z,987\n";
+
+    let data = parse_analytics(csv.as_bytes()).unwrap();
+    let mut points: Vec<_> = data.data["Total"].iter().map(|(_, point)| *point).collect();
+    points.sort();
+
+    assert_eq!(points, [DataPoint::Integer(987), DataPoint::Integer(1234)]);
+}
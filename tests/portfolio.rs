@@ -0,0 +1,108 @@
+//! Tests for drawing several experiences' exports as a grid of small charts
+
+mod common;
+
+use clap::Parser;
+use common::fixture_path;
+use rasorite::output::OutputError;
+use rasorite::portfolio::{PortfolioError, PortfolioOptions};
+use std::fs;
+
+#[derive(Parser)]
+struct Cli {
+    #[command(flatten)]
+    portfolio: PortfolioOptions,
+}
+
+/// The path of the named fixture, as passed on the command line
+fn fixture_arg(name: &str) -> String {
+    fixture_path(name).to_string_lossy().into_owned()
+}
+
+/// Draws a portfolio of the given fixtures to SVG, returning the SVG
+fn draw(test: &str, fixtures: &[&str], args: &[&str]) -> Result<String, PortfolioError> {
+    let out = std::env::temp_dir().join(format!("rasorite-{}-{}.svg", test, std::process::id()));
+    let out_arg = out.to_string_lossy().into_owned();
+    let files: Vec<String> = fixtures.iter().map(|name| fixture_arg(name)).collect();
+    let cli = Cli::try_parse_from(
+        ["portfolio", "-o", &out_arg]
+            .into_iter()
+            .chain(args.iter().copied())
+            .chain(files.iter().map(String::as_str)),
+    )
+    .expect("Invalid portfolio flags!");
+
+    let result = cli.portfolio.run();
+    let svg = fs::read_to_string(&out);
+    let _ = fs::remove_file(&out);
+    result.map(|()| svg.expect("Failed to read portfolio!"))
+}
+
+#[test]
+fn draws_a_chart_per_experience() {
+    let svg = draw("experiences", &["analytics.csv", "peer.csv"], &[]).unwrap();
+
+    assert!(svg.contains("Daily Active Users"));
+    assert!(svg.contains("Experience ID 0"));
+    assert!(svg.contains("Experience ID 42"));
+}
+
+#[test]
+fn shared_scales_label_every_chart_alike() {
+    let labels = |svg: &str| svg.matches("\n1000\n").count();
+
+    let svg = draw("separate", &["analytics.csv", "peer.csv"], &[]).unwrap();
+    assert_eq!(labels(&svg), 1);
+
+    let svg = draw("shared", &["analytics.csv", "peer.csv"], &["--shared-y"]).unwrap();
+    assert_eq!(labels(&svg), 2);
+}
+
+#[test]
+fn different_kpis_are_rejected() {
+    assert!(matches!(
+        draw("kpis", &["analytics.csv", "revenue.csv"], &[]),
+        Err(PortfolioError::Mismatch(..))
+    ));
+    assert!(draw("forced", &["analytics.csv", "revenue.csv"], &["--mix-kpis"]).is_ok());
+}
+
+#[test]
+fn outputs_are_checked_before_drawing() {
+    let out = std::env::temp_dir().join(format!("rasorite-existing-{}.png", std::process::id()));
+    fs::write(&out, "not a portfolio").unwrap();
+    let run = |args: &[&str]| {
+        Cli::try_parse_from(
+            ["portfolio", "-o", &out.to_string_lossy()]
+                .into_iter()
+                .chain(args.iter().copied())
+                .chain([fixture_arg("analytics.csv").as_str()]),
+        )
+        .expect("Invalid portfolio flags!")
+        .portfolio
+        .run()
+    };
+
+    let refused = run(&[]);
+    let contents = fs::read(&out).unwrap();
+    let forced = run(&["--force"]);
+    let _ = fs::remove_file(&out);
+    assert!(matches!(
+        refused,
+        Err(PortfolioError::Output(OutputError::Exists(_)))
+    ));
+    assert_eq!(contents, b"not a portfolio");
+    assert!(forced.is_ok());
+}
+
+#[test]
+fn unsupported_formats_are_rejected() {
+    let analytics = fixture_arg("analytics.csv");
+    let cli = Cli::try_parse_from(["portfolio", "-o", "portfolio.txt", analytics.as_str()])
+        .expect("Invalid portfolio flags!");
+
+    assert!(matches!(
+        cli.portfolio.run(),
+        Err(PortfolioError::Output(OutputError::UnsupportedFormat(_)))
+    ));
+}
@@ -0,0 +1,104 @@
+//! Tests for replaying manifests with the replay subcommand
+
+mod common;
+
+use common::fixture_path;
+use std::fs;
+use std::net::TcpListener;
+use std::process::Command;
+use std::sync::mpsc;
+use std::thread;
+
+#[test]
+fn replays_do_not_post_to_the_webhook() {
+    let dir = std::env::temp_dir().join(format!("rasorite-replay-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Failed to create temporary directory!");
+    fs::copy(fixture_path("analytics.csv"), dir.join("analytics.csv"))
+        .expect("Failed to copy fixture!");
+
+    // Nothing listens on the port while the chart is first rendered, so the post fails quickly
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to find a free port!")
+        .port();
+    let webhook = format!("http://127.0.0.1:{}/hook", port);
+    let recorded = Command::new(env!("CARGO_BIN_EXE_rasorite"))
+        .current_dir(&dir)
+        .args(["-i", "analytics.csv", "--silent", "--manifest"])
+        .args(["--webhook", &webhook, "dau.svg"])
+        .status()
+        .expect("Failed to run rasorite!");
+    assert_eq!(recorded.code(), Some(16));
+
+    // Connections are counted and closed straight away, so that a replay which does post fails
+    // quickly rather than waiting on a response
+    let listener = TcpListener::bind(("127.0.0.1", port)).expect("Failed to listen on the port!");
+    let (requests, received) = mpsc::channel();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let _ = requests.send(());
+            drop(stream);
+        }
+    });
+    let replayed = Command::new(env!("CARGO_BIN_EXE_rasorite"))
+        .current_dir(&dir)
+        .args(["replay", "dau.manifest.json"])
+        .status()
+        .expect("Failed to run rasorite!");
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(replayed.success());
+    assert!(received.try_recv().is_err());
+}
+
+#[test]
+fn replays_report_the_same_warnings() {
+    let dir = std::env::temp_dir().join(format!("rasorite-replay-warnings-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Failed to create temporary directory!");
+    // An export viewed by platform has no total, which is warned about when it is summed
+    fs::write(
+        dir.join("platforms.csv"),
+        "Experience ID,1\n\nBreakdown,Date,Daily Active Users\n\
+         Phone,2024-02-23T00:00:00.000Z,10\n\
+         Computer,2024-02-23T00:00:00.000Z,5\n\
+         Phone,2024-02-24T00:00:00.000Z,12\n\
+         Computer,2024-02-24T00:00:00.000Z,6\n",
+    )
+    .expect("Failed to write export!");
+    fs::write(
+        dir.join("report.j2"),
+        "{% for w in warnings %}{{ w.kind }}: {{ w.message }}\n{% endfor %}",
+    )
+    .expect("Failed to write template!");
+
+    let render = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_rasorite"))
+            .current_dir(&dir)
+            .args(args)
+            .status()
+            .expect("Failed to run rasorite!")
+    };
+    let recorded = render(&[
+        "-i",
+        "platforms.csv",
+        "--silent",
+        "--manifest",
+        "--template",
+        "report.j2",
+        "--report",
+        "report.txt",
+        "dau.svg",
+    ]);
+    let original = fs::read_to_string(dir.join("report.txt"));
+    let replayed = render(&["replay", "dau.manifest.json"]);
+    let replayed_report = fs::read_to_string(dir.join("report.txt"));
+    let _ = fs::remove_dir_all(&dir);
+
+    assert!(recorded.success() && replayed.success());
+    let original = original.expect("Failed to read report!");
+    assert!(original.starts_with("computed-total: "));
+    assert_eq!(
+        replayed_report.expect("Failed to read replayed report!"),
+        original
+    );
+}
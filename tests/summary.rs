@@ -0,0 +1,95 @@
+//! Tests for the Markdown summaries written for GitHub Actions
+
+mod common;
+
+use common::{fixture, fixture_path};
+use rasorite::data::KpiType;
+use rasorite::summary::render_summary;
+use rasorite::warning::Warning;
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[test]
+fn summarizes_every_series() {
+    let summary = render_summary(
+        &fixture("benchmarks.csv"),
+        Path::new("charts/dau.png"),
+        &[PathBuf::from("charts/dau.wide.csv")],
+        &[],
+    );
+
+    assert!(summary.starts_with("### Daily Active Users for Experience ID 0\n"));
+    assert!(summary.contains("_2024-02-23 to 2024-05-22_"));
+    assert!(summary.contains("| Total | 279 | 303 | -44.9% | 187 | 905 | 406 |\n"));
+    assert!(summary.contains("| Benchmark Top 25% |"));
+    assert!(summary.contains("- Chart: `charts/dau.png`\n- Export: `charts/dau.wide.csv`\n"));
+    assert!(!summary.contains("[!WARNING]"));
+}
+
+#[test]
+fn lists_warnings() {
+    let summary = render_summary(
+        &fixture("analytics.csv"),
+        Path::new("dau.svg"),
+        &[],
+        &[Warning::ProfileKpi(
+            KpiType::DailyActiveUsers,
+            "retention".to_string(),
+        )],
+    );
+
+    assert!(summary.contains(
+        "> [!WARNING]\n> - Daily Active Users is not one of the KPIs of the profile \"retention\"\n"
+    ));
+}
+
+#[test]
+fn webhook_urls_are_left_out_of_warnings() {
+    let summary = render_summary(
+        &fixture("analytics.csv"),
+        Path::new("dau.svg"),
+        &[],
+        &[Warning::PublishFailed(
+            "the webhook",
+            "https://discord.com/api/webhooks/123/token: 404".to_string(),
+        )],
+    );
+
+    assert!(summary.contains("the webhook: [REDACTED]: 404\n"));
+    assert!(!summary.contains("token"));
+}
+
+#[test]
+fn failed_webhooks_do_not_leak_their_url() {
+    let dir = std::env::temp_dir().join(format!("rasorite-summary-{}", std::process::id()));
+    fs::create_dir_all(&dir).expect("Failed to create temporary directory!");
+    // Nothing listens on the port, so posting fails
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("Failed to find a free port!")
+        .port();
+    let webhook = format!("http://127.0.0.1:{}/T0123/s3cr3t-t0k3n", port);
+
+    let status = Command::new(env!("CARGO_BIN_EXE_rasorite"))
+        .current_dir(&dir)
+        .env("GITHUB_STEP_SUMMARY", dir.join("summary.md"))
+        .arg("-i")
+        .arg(fixture_path("analytics.csv"))
+        .args(["--silent", "--manifest", "--github-summary"])
+        .args(["--webhook", &webhook, "dau.svg"])
+        .status()
+        .expect("Failed to run rasorite!");
+    let summary = fs::read_to_string(dir.join("summary.md"));
+    let manifest = fs::read_to_string(dir.join("dau.manifest.json"));
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(status.code(), Some(16));
+    let summary = summary.expect("Failed to read summary!");
+    assert!(summary.contains("could not be published to the webhook"));
+    assert!(!summary.contains("s3cr3t-t0k3n"));
+    assert!(!manifest
+        .expect("Failed to read manifest!")
+        .contains("s3cr3t-t0k3n"));
+}
@@ -0,0 +1,25 @@
+//! Tests for the stage durations printed with --timings. Stages are recorded globally, so
+//! everything is checked in a single test rather than in tests that could run at the same time.
+
+use rasorite::timings;
+
+#[test]
+fn records_stages_once_enabled() {
+    assert_eq!(timings::time("ignored", || 1), 1);
+    assert!(timings::recorded().is_empty());
+
+    timings::enable();
+    timings::time("parse", || ());
+    drop(timings::start("draw"));
+    timings::time("parse", || ());
+
+    let names: Vec<&str> = timings::recorded()
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names, ["parse", "draw"]);
+
+    let report = timings::report();
+    assert!(report.starts_with("parse"));
+    assert!(report.lines().last().unwrap().starts_with("total"));
+}
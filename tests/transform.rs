@@ -0,0 +1,111 @@
+//! Tests for the transforms applied between parsing and plotting
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rasorite::data::DataPoint;
+use rasorite::transform::{Pipeline, Points, Script};
+use std::sync::Arc;
+
+fn day(day: i64) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 3, 4, 0, 0, 0).unwrap() + Duration::days(day)
+}
+
+fn series(values: &[(i64, u64)]) -> Points {
+    values
+        .iter()
+        .map(|(offset, value)| (day(*offset), DataPoint::Integer(*value)))
+        .collect()
+}
+
+fn values(points: &Points) -> Vec<f64> {
+    points.iter().map(|(_, point)| (*point).into()).collect()
+}
+
+fn pipeline(steps: &str) -> Pipeline {
+    steps.parse().expect("Failed to parse pipeline!")
+}
+
+#[test]
+fn fills_gaps_with_whole_numbers() {
+    let filled = pipeline("fill-gaps")
+        .apply_series("Total", series(&[(0, 10), (3, 20)]))
+        .unwrap();
+    assert_eq!(
+        filled.iter().map(|(date, _)| *date).collect::<Vec<_>>(),
+        (0..4).map(day).collect::<Vec<_>>()
+    );
+    assert_eq!(values(&filled), vec![10.0, 13.0, 17.0, 20.0]);
+}
+
+#[test]
+fn moving_average_covers_the_days_available() {
+    let averaged = pipeline("ma:2")
+        .apply_series("Total", series(&[(0, 10), (1, 20), (2, 40), (4, 10)]))
+        .unwrap();
+    assert_eq!(values(&averaged), vec![10.0, 15.0, 30.0, 10.0]);
+}
+
+#[test]
+fn steps_apply_in_order() {
+    let points = series(&[(0, 10), (2, 30), (3, 50)]);
+    let normalized = pipeline("fill-gaps,normalize:minmax")
+        .apply_series("Total", points)
+        .unwrap();
+    assert_eq!(values(&normalized), vec![0.0, 0.25, 0.5, 1.0]);
+}
+
+#[test]
+fn resamples_into_weeks_starting_on_monday() {
+    let weekly = pipeline("resample:week")
+        .apply_series("Total", series(&[(0, 10), (6, 20), (7, 100)]))
+        .unwrap();
+    assert_eq!(
+        weekly.iter().map(|(date, _)| *date).collect::<Vec<_>>(),
+        vec![day(0), day(7)]
+    );
+    assert_eq!(values(&weekly), vec![15.0, 100.0]);
+}
+
+#[test]
+fn rejects_unknown_steps_and_bad_arguments() {
+    for steps in [
+        "smooth",
+        "ma:0",
+        "ma",
+        "ema:2",
+        "resample:year",
+        "fill-gaps:1",
+        "",
+    ] {
+        assert!(steps.parse::<Pipeline>().is_err(), "{} was accepted", steps);
+    }
+}
+
+#[test]
+fn scripts_can_drop_and_change_points() {
+    let file = std::env::temp_dir().join(format!("rasorite-{}.rhai", std::process::id()));
+    std::fs::write(
+        &file,
+        r#"
+        fn transform(name, points) {
+            points
+                .filter(|point| point.date != "2024-03-05")
+                .map(|point| #{ date: point.date, value: point.value * 2 })
+        }
+        "#,
+    )
+    .unwrap();
+    let script = Script::load(&file);
+    std::fs::remove_file(&file).unwrap();
+
+    let scripted = Pipeline::default()
+        .then(Arc::new(script.unwrap()))
+        .apply_series("Total", series(&[(0, 10), (1, 20), (2, 30)]))
+        .unwrap();
+    assert_eq!(
+        scripted,
+        vec![
+            (day(0), DataPoint::Integer(20)),
+            (day(2), DataPoint::Integer(60)),
+        ]
+    );
+}
@@ -0,0 +1,72 @@
+//! Tests for the warnings raised while reading exports and drawing charts
+
+mod common;
+
+use clap::Parser;
+use common::fixture;
+use rasorite::config::Config;
+use rasorite::parse::{parse_analytics, AnalyticsData};
+use rasorite::plot::{render_svg, PlotArgs};
+use rasorite::warning::{self, Warning, WarningRecord};
+
+/// The warnings raised while rendering the export with the given flags
+fn render_warnings(data: AnalyticsData, args: &[&str]) -> Vec<Warning> {
+    let args = PlotArgs::try_parse_from(["--deterministic"].iter().chain(args))
+        .expect("Invalid plot flags!");
+    let style = args.plot.resolve_style(&Config::default());
+    let (svg, warnings) = warning::collect(|| render_svg(data, &args.plot, &style));
+    svg.expect("Failed to render chart!");
+    warnings
+}
+
+#[test]
+fn clean_charts_have_no_warnings() {
+    assert_eq!(render_warnings(fixture("analytics.csv"), &[]), []);
+}
+
+#[test]
+fn normalizing_without_benchmarks_is_warned_about() {
+    assert_eq!(
+        render_warnings(fixture("paying.csv"), &["--normalize"]),
+        [Warning::BenchmarksMissing]
+    );
+}
+
+#[test]
+fn computed_totals_are_warned_about() {
+    let csv = "Experience ID,1\n\nBreakdown,Date,Daily Active Users\n\
+               Phone,2024-02-23T00:00:00.000Z,10\n\
+               Computer,2024-02-23T00:00:00.000Z,5\n\
+               Phone,2024-02-24T00:00:00.000Z,12\n\
+               Computer,2024-02-24T00:00:00.000Z,6\n";
+    let data = parse_analytics(csv.as_bytes()).unwrap();
+
+    assert_eq!(render_warnings(data, &[]), [Warning::ComputedTotal(2)]);
+}
+
+#[test]
+fn warnings_are_only_collected_within_the_closure() {
+    warning::emit(Warning::UnitIgnored);
+    let ((), warnings) = warning::collect(|| {
+        warning::emit(Warning::EmbedSvgOnly);
+        assert_eq!(warning::collected(), [Warning::EmbedSvgOnly]);
+    });
+
+    assert_eq!(warnings, [Warning::EmbedSvgOnly]);
+    assert!(warning::collected().is_empty());
+}
+
+#[test]
+fn records_have_a_kind_and_the_message() {
+    assert_eq!(
+        WarningRecord::from(&Warning::ComputedTotal(3)),
+        WarningRecord {
+            kind: "computed-total".to_string(),
+            message: "The export has no \"Total\" series, so one was computed by summing its 3 breakdown series...".to_string(),
+        }
+    );
+    assert_eq!(
+        serde_json::to_value(Warning::BenchmarksMissing).unwrap()["kind"],
+        "benchmarks-missing"
+    );
+}